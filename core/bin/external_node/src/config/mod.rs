@@ -7,7 +7,10 @@ use zksync_basic_types::{Address, L1ChainId, L2ChainId};
 use zksync_config::ObjectStoreConfig;
 use zksync_core::{
     api_server::{
-        tx_sender::TxSenderConfig,
+        tx_sender::{
+            proxy::TxProxy, ExcessiveGasPerPubdataPolicy, ExpectedNoncePolicy, FeeAccountTxPolicy,
+            TxSenderConfig,
+        },
         web3::{state::InternalApiConfig, Namespace},
     },
     consensus,
@@ -165,6 +168,37 @@ pub struct OptionalENConfig {
     /// different node.
     #[serde(default)]
     pub filters_disabled: bool,
+    /// Whether to mirror transactions proxied to the main node into the local mempool table, so that they
+    /// can be looked up locally before they are synced back from the main node. Disabled by default.
+    #[serde(default)]
+    pub mirror_proxied_txs: bool,
+    /// Time after which transactions mirrored into the local mempool (see `mirror_proxied_txs`) are pruned
+    /// if they were not synced back from the main node (in seconds). Default is 10 minutes.
+    #[serde(default = "OptionalENConfig::default_mirrored_tx_ttl_sec")]
+    mirrored_tx_ttl_sec: u64,
+    /// Fallback main node URLs. Currently only used by the batch status updater, which fails over to a fallback URL
+    /// if the main node returns repeated errors.
+    #[serde(default)]
+    pub main_node_fallback_urls: Vec<String>,
+    /// Whether to run the batch status updater in the dry-run mode, in which L1 batch status changes fetched
+    /// from the main node are only logged and not persisted. Disabled by default.
+    #[serde(default)]
+    pub batch_status_updater_dry_run: bool,
+    /// Interval between polls of the main node by the batch status updater, in milliseconds. Default is 5 seconds.
+    #[serde(default = "OptionalENConfig::default_batch_status_updater_poll_interval")]
+    pub batch_status_updater_poll_interval: u64,
+    /// Relative random jitter applied to the batch status updater poll interval (e.g., 0.1 for ±10%), so that
+    /// external nodes polling the same main node don't synchronize their requests. Disabled by default.
+    #[serde(default)]
+    pub batch_status_updater_poll_jitter: f64,
+    /// Number of consecutive errors returned by the main node after which the batch status updater fails over
+    /// to the next URL from `main_node_fallback_urls`. Default is 3.
+    #[serde(default = "OptionalENConfig::default_batch_status_updater_failover_max_errors")]
+    pub batch_status_updater_failover_max_errors: usize,
+    /// Interval after which the batch status updater retries the main node once it has failed over
+    /// to a fallback URL, in milliseconds. Default is 60 seconds.
+    #[serde(default = "OptionalENConfig::default_batch_status_updater_primary_retry_interval")]
+    pub batch_status_updater_primary_retry_interval: u64,
 
     // Health checks
     /// Time limit in milliseconds to mark a health check as slow and log the corresponding warning.
@@ -244,6 +278,90 @@ pub struct OptionalENConfig {
     /// Maximum number of transactions to be stored in the mempool cache. Default is 10000.
     #[serde(default = "OptionalENConfig::default_mempool_cache_size")]
     pub mempool_cache_size: usize,
+    /// Wall-clock time limit for a single VM execution in the API server (in ms). If not set, VM executions
+    /// are only limited by gas and storage invocations.
+    vm_execution_timeout_ms: Option<u64>,
+    /// Minimum allowed scale factor for estimated gas limits. Default is 1.0.
+    #[serde(default = "OptionalENConfig::default_min_estimate_gas_scale_factor")]
+    pub min_estimate_gas_scale_factor: f64,
+    /// Maximum allowed scale factor for estimated gas limits. Default is 10.0.
+    #[serde(default = "OptionalENConfig::default_max_estimate_gas_scale_factor")]
+    pub max_estimate_gas_scale_factor: f64,
+    /// Lower bound for `gas_per_pubdata` used during fee estimation. If not set, the value derived
+    /// from the batch fee input is used as is.
+    pub min_gas_per_pubdata: Option<u64>,
+    /// Maximum gas limit for the transaction body considered during gas estimation. Default is the maximum L2 tx gas limit.
+    #[serde(default = "OptionalENConfig::default_max_gas_limit_for_estimation")]
+    pub max_gas_limit_for_estimation: u32,
+    /// Number of nonces beyond `max_nonce_ahead` accepted if all preceding nonces of the account are pending
+    /// in the mempool. Default is 0.
+    #[serde(default)]
+    pub max_nonce_ahead_grace: u32,
+    /// Whether to re-execute transactions reverting during gas estimation with the `eth_call` contracts
+    /// to get a more human-readable revert reason.
+    #[serde(default)]
+    pub estimate_gas_playground_revert_reasons: bool,
+    /// Whether to execute transactions with the maximum gas limit before the gas estimation binary search.
+    #[serde(default)]
+    pub estimate_gas_probe_max_gas_limit: bool,
+    /// Whether to estimate gas by extrapolating from an execution with the maximum gas limit (experimental).
+    #[serde(default)]
+    pub estimate_gas_by_extrapolation: bool,
+    /// Whether to check signatures of submitted transactions before any DB reads or VM executions.
+    #[serde(default)]
+    pub early_signature_check: bool,
+    /// Recipient addresses that submitted transactions are not allowed to have.
+    pub forbidden_tx_targets: Option<Vec<Address>>,
+    /// Maximum number of miniblocks the pending block may lag behind the newest known miniblock. If not set,
+    /// the pending block is never considered stale.
+    pub max_pending_block_lag: Option<u32>,
+    /// Maximum number of concurrent gas estimations. Must be positive if set. If not set, estimations are only limited
+    /// by `vm_concurrency_limit`.
+    pub max_concurrent_estimations: Option<usize>,
+    /// Whether the expected nonce of submitted transactions accounts for the account's transactions pending
+    /// in the mempool. If not set, the nonce at the latest sealed miniblock is used.
+    #[serde(default)]
+    pub use_pending_nonces: bool,
+    /// Whether to reject submitted transactions with `gas_per_pubdata_limit` exceeding the maximum supported
+    /// by the VM, instead of accepting them with a warning.
+    #[serde(default)]
+    pub reject_excessive_gas_per_pubdata: bool,
+    /// Maximum size of data returned by `eth_call` (in bytes). If not set, the response size is not limited.
+    pub max_eth_call_response_bytes: Option<usize>,
+    /// Time-to-live for the base fee cached by `eth_gasPrice` (in ms). Default is 1 second.
+    #[serde(default = "OptionalENConfig::default_gas_price_cache_ttl_ms")]
+    gas_price_cache_ttl_ms: u64,
+    /// If set, `eth_call` is only allowed to target contracts from this list. If not set, all targets are allowed.
+    pub eth_call_target_allowlist: Option<Vec<Address>>,
+    /// Timeout for acquiring a connection to the replica DB in the API server (in ms). If not set, the acquisition
+    /// is only limited by the connection pool itself.
+    replica_connection_acquire_timeout_ms: Option<u64>,
+    /// Number of times the final gas estimation step is retried with a bumped gas limit if it runs out of gas. Default is 2.
+    #[serde(default = "OptionalENConfig::default_estimate_gas_final_step_retries")]
+    pub estimate_gas_final_step_retries: u32,
+    /// Relative gas limit increment applied on each retry of the final gas estimation step. Default is 0.05 (i.e., +5%).
+    #[serde(default = "OptionalENConfig::default_estimate_gas_retry_bump")]
+    pub estimate_gas_retry_bump: f64,
+    /// Number of times reading the expected account nonce is retried on transient DB errors. Default is 2.
+    #[serde(default = "OptionalENConfig::default_nonce_read_retries")]
+    pub nonce_read_retries: u32,
+    /// Interval between retries of reading the expected account nonce (in ms). Default is 50 ms.
+    #[serde(default = "OptionalENConfig::default_nonce_read_retry_interval_ms")]
+    nonce_read_retry_interval_ms: u64,
+    /// Whether to log a summary of each gas estimation at the debug level.
+    #[serde(default)]
+    pub log_estimation_summary: bool,
+    /// Whether to reject submitted transactions initiated by the fee account, instead of validating them like
+    /// transactions from any other account.
+    #[serde(default)]
+    pub reject_fee_account_txs: bool,
+    /// Maximum pubdata a single transaction may publish (in bytes, including its factory deps). Default is
+    /// the maximum pubdata per batch.
+    #[serde(default = "OptionalENConfig::default_max_pubdata_per_tx")]
+    pub max_pubdata_per_tx: u64,
+    /// Time-to-live for the fee account balance cached by the transaction sender (in ms). Default is 1 second.
+    #[serde(default = "OptionalENConfig::default_fee_account_balance_cache_ttl_ms")]
+    fee_account_balance_cache_ttl_ms: u64,
     /// Address of the L1 diamond proxy contract used by the consistency checker to match with the origin of logs emitted
     /// by commit transactions. If not set, it will not be verified.
     // This is intentionally not a part of `RemoteENConfig` because fetching this info from the main node would defeat
@@ -335,6 +453,10 @@ impl OptionalENConfig {
         30
     }
 
+    const fn default_mirrored_tx_ttl_sec() -> u64 {
+        TxProxy::DEFAULT_MIRRORED_TX_TTL.as_secs()
+    }
+
     const fn default_fee_history_limit() -> u64 {
         1_024
     }
@@ -363,6 +485,58 @@ impl OptionalENConfig {
         10_000
     }
 
+    const fn default_batch_status_updater_poll_interval() -> u64 {
+        5_000
+    }
+
+    const fn default_batch_status_updater_failover_max_errors() -> usize {
+        3
+    }
+
+    const fn default_batch_status_updater_primary_retry_interval() -> u64 {
+        60_000
+    }
+
+    const fn default_min_estimate_gas_scale_factor() -> f64 {
+        TxSenderConfig::DEFAULT_MIN_ESTIMATE_GAS_SCALE_FACTOR
+    }
+
+    const fn default_max_estimate_gas_scale_factor() -> f64 {
+        TxSenderConfig::DEFAULT_MAX_ESTIMATE_GAS_SCALE_FACTOR
+    }
+
+    const fn default_max_gas_limit_for_estimation() -> u32 {
+        TxSenderConfig::DEFAULT_MAX_GAS_LIMIT_FOR_ESTIMATION
+    }
+
+    const fn default_gas_price_cache_ttl_ms() -> u64 {
+        1_000
+    }
+
+    const fn default_estimate_gas_final_step_retries() -> u32 {
+        TxSenderConfig::DEFAULT_ESTIMATE_GAS_FINAL_STEP_RETRIES
+    }
+
+    const fn default_estimate_gas_retry_bump() -> f64 {
+        TxSenderConfig::DEFAULT_ESTIMATE_GAS_RETRY_BUMP
+    }
+
+    const fn default_nonce_read_retries() -> u32 {
+        TxSenderConfig::DEFAULT_NONCE_READ_RETRIES
+    }
+
+    const fn default_nonce_read_retry_interval_ms() -> u64 {
+        TxSenderConfig::DEFAULT_NONCE_READ_RETRY_INTERVAL.as_millis() as u64
+    }
+
+    const fn default_max_pubdata_per_tx() -> u64 {
+        TxSenderConfig::DEFAULT_MAX_PUBDATA_PER_TX
+    }
+
+    const fn default_fee_account_balance_cache_ttl_ms() -> u64 {
+        TxSenderConfig::DEFAULT_FEE_ACCOUNT_BALANCE_CACHE_TTL.as_millis() as u64
+    }
+
     pub fn polling_interval(&self) -> Duration {
         Duration::from_millis(self.polling_interval)
     }
@@ -401,6 +575,10 @@ impl OptionalENConfig {
         Duration::from_secs(self.merkle_tree_stalled_writes_timeout_sec)
     }
 
+    pub fn mirrored_tx_ttl(&self) -> Duration {
+        Duration::from_secs(self.mirrored_tx_ttl_sec)
+    }
+
     pub fn long_connection_threshold(&self) -> Option<Duration> {
         self.database_long_connection_threshold_ms
             .map(Duration::from_millis)
@@ -434,6 +612,35 @@ impl OptionalENConfig {
     pub fn mempool_cache_update_interval(&self) -> Duration {
         Duration::from_millis(self.mempool_cache_update_interval)
     }
+
+    pub fn batch_status_updater_poll_interval(&self) -> Duration {
+        Duration::from_millis(self.batch_status_updater_poll_interval)
+    }
+
+    pub fn batch_status_updater_primary_retry_interval(&self) -> Duration {
+        Duration::from_millis(self.batch_status_updater_primary_retry_interval)
+    }
+
+    pub fn vm_execution_timeout(&self) -> Option<Duration> {
+        self.vm_execution_timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn gas_price_cache_ttl(&self) -> Duration {
+        Duration::from_millis(self.gas_price_cache_ttl_ms)
+    }
+
+    pub fn replica_connection_acquire_timeout(&self) -> Option<Duration> {
+        self.replica_connection_acquire_timeout_ms
+            .map(Duration::from_millis)
+    }
+
+    pub fn nonce_read_retry_interval(&self) -> Duration {
+        Duration::from_millis(self.nonce_read_retry_interval_ms)
+    }
+
+    pub fn fee_account_balance_cache_ttl(&self) -> Duration {
+        Duration::from_millis(self.fee_account_balance_cache_ttl_ms)
+    }
 }
 
 /// This part of the external node config is required for its operation.
@@ -664,6 +871,48 @@ impl From<ExternalNodeConfig> for TxSenderConfig {
                 .optional
                 .l1_to_l2_transactions_compatibility_mode,
             max_pubdata_per_batch: config.remote.max_pubdata_per_batch,
+            max_pubdata_per_tx: config.optional.max_pubdata_per_tx,
+            eth_call_target_allowlist: config
+                .optional
+                .eth_call_target_allowlist
+                .map(|targets| targets.into_iter().collect()),
+            max_eth_call_response_bytes: config.optional.max_eth_call_response_bytes,
+            replica_connection_acquire_timeout: config
+                .optional
+                .replica_connection_acquire_timeout(),
+            estimate_gas_final_step_retries: config.optional.estimate_gas_final_step_retries,
+            estimate_gas_retry_bump: config.optional.estimate_gas_retry_bump,
+            nonce_read_retries: config.optional.nonce_read_retries,
+            nonce_read_retry_interval: config.optional.nonce_read_retry_interval(),
+            min_estimate_gas_scale_factor: config.optional.min_estimate_gas_scale_factor,
+            max_estimate_gas_scale_factor: config.optional.max_estimate_gas_scale_factor,
+            vm_execution_timeout: config.optional.vm_execution_timeout(),
+            min_gas_per_pubdata: config.optional.min_gas_per_pubdata,
+            max_gas_limit_for_estimation: config.optional.max_gas_limit_for_estimation,
+            max_nonce_ahead_grace: config.optional.max_nonce_ahead_grace,
+            estimate_gas_playground_revert_reasons: config
+                .optional
+                .estimate_gas_playground_revert_reasons,
+            estimate_gas_probe_max_gas_limit: config.optional.estimate_gas_probe_max_gas_limit,
+            estimate_gas_by_extrapolation: config.optional.estimate_gas_by_extrapolation,
+            log_estimation_summary: config.optional.log_estimation_summary,
+            early_signature_check: config.optional.early_signature_check,
+            forbidden_tx_targets: config
+                .optional
+                .forbidden_tx_targets
+                .iter()
+                .flatten()
+                .copied()
+                .collect(),
+            max_pending_block_lag: config.optional.max_pending_block_lag,
+            max_concurrent_estimations: config.optional.max_concurrent_estimations,
+            expected_nonce_policy: ExpectedNoncePolicy::new(config.optional.use_pending_nonces),
+            excessive_gas_per_pubdata_policy: ExcessiveGasPerPubdataPolicy::new(
+                config.optional.reject_excessive_gas_per_pubdata,
+            ),
+            fee_account_tx_policy: FeeAccountTxPolicy::new(config.optional.reject_fee_account_txs),
+            gas_price_cache_ttl: config.optional.gas_price_cache_ttl(),
+            fee_account_balance_cache_ttl: config.optional.fee_account_balance_cache_ttl(),
         }
     }
 }
@@ -25,12 +25,30 @@ fn parsing_optional_config_from_empty_env() {
         128 * BYTES_IN_MEGABYTE
     );
     assert_eq!(config.max_response_body_size(), 10 * BYTES_IN_MEGABYTE);
+    assert!(!config.mirror_proxied_txs);
+    assert_eq!(config.mirrored_tx_ttl(), Duration::from_secs(600));
+    assert!(config.main_node_fallback_urls.is_empty());
+    assert!(!config.batch_status_updater_dry_run);
+    assert_eq!(config.batch_status_updater_failover_max_errors, 3);
+    assert_eq!(
+        config.batch_status_updater_primary_retry_interval(),
+        Duration::from_secs(60)
+    );
 }
 
 #[test]
 fn parsing_optional_config_from_env() {
     let env_vars = [
         ("EN_FILTERS_DISABLED", "true"),
+        ("EN_MIRROR_PROXIED_TXS", "true"),
+        ("EN_MIRRORED_TX_TTL_SEC", "60"),
+        (
+            "EN_MAIN_NODE_FALLBACK_URLS",
+            "http://fallback-1:3050,http://fallback-2:3050",
+        ),
+        ("EN_BATCH_STATUS_UPDATER_DRY_RUN", "true"),
+        ("EN_BATCH_STATUS_UPDATER_FAILOVER_MAX_ERRORS", "5"),
+        ("EN_BATCH_STATUS_UPDATER_PRIMARY_RETRY_INTERVAL", "10000"),
         ("EN_FILTERS_LIMIT", "5000"),
         ("EN_SUBSCRIPTIONS_LIMIT", "20000"),
         ("EN_FEE_HISTORY_LIMIT", "1000"),
@@ -52,6 +70,18 @@ fn parsing_optional_config_from_env() {
 
     let config: OptionalENConfig = envy::prefixed("EN_").from_iter(env_vars).unwrap();
     assert!(config.filters_disabled);
+    assert!(config.mirror_proxied_txs);
+    assert_eq!(config.mirrored_tx_ttl(), Duration::from_secs(60));
+    assert_eq!(
+        config.main_node_fallback_urls,
+        ["http://fallback-1:3050", "http://fallback-2:3050"]
+    );
+    assert!(config.batch_status_updater_dry_run);
+    assert_eq!(config.batch_status_updater_failover_max_errors, 5);
+    assert_eq!(
+        config.batch_status_updater_primary_retry_interval(),
+        Duration::from_secs(10)
+    );
     assert_eq!(config.filters_limit, 5_000);
     assert_eq!(config.subscriptions_limit, 20_000);
     assert_eq!(config.fee_history_limit, 1_000);
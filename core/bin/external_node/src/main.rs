@@ -12,7 +12,7 @@ use zksync_core::{
     api_server::{
         execution_sandbox::VmConcurrencyLimiter,
         healthcheck::HealthCheckHandle,
-        tx_sender::{proxy::TxProxy, ApiContracts, TxSenderBuilder},
+        tx_sender::{proxy::TxProxy, ApiContracts, TxSenderBuilder, TxSenderConfig},
         web3::{ApiBuilder, Namespace},
     },
     block_reverter::{BlockReverter, BlockReverterFlags, L1ExecutedBatchesRevert, NodeRole},
@@ -271,13 +271,33 @@ async fn init_tasks(
     app_health.insert_component(consistency_checker.health_check().clone());
     let consistency_checker_handle = tokio::spawn(consistency_checker.run(stop_receiver.clone()));
 
+    let fallback_main_node_clients = config
+        .optional
+        .main_node_fallback_urls
+        .iter()
+        .map(|url| {
+            <dyn MainNodeClient>::json_rpc(url).with_context(|| {
+                format!("Failed creating JSON-RPC client for fallback main node {url}")
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
     let batch_status_updater = BatchStatusUpdater::new(
         main_node_client.clone(),
         singleton_pool_builder
             .build()
             .await
             .context("failed to build a connection pool for BatchStatusUpdater")?,
-    );
+    )
+    .with_fallback_clients(fallback_main_node_clients)
+    .with_failover_max_consecutive_errors(config.optional.batch_status_updater_failover_max_errors)
+    .with_failover_primary_retry_interval(
+        config
+            .optional
+            .batch_status_updater_primary_retry_interval(),
+    )
+    .with_dry_run(config.optional.batch_status_updater_dry_run)
+    .with_sleep_interval(config.optional.batch_status_updater_poll_interval())
+    .with_sleep_jitter(config.optional.batch_status_updater_poll_jitter);
     app_health.insert_component(batch_status_updater.health_check());
 
     // Run the components.
@@ -305,7 +325,11 @@ async fn init_tasks(
         tokio::spawn(fee_params_fetcher.clone().run(stop_receiver.clone()));
 
     let (tx_sender, vm_barrier, cache_update_handle, proxy_cache_updater_handle) = {
-        let tx_proxy = TxProxy::new(main_node_client);
+        let mut tx_proxy = TxProxy::new(main_node_client).with_sync_state(sync_state.clone());
+        if config.optional.mirror_proxied_txs {
+            tx_proxy = tx_proxy
+                .with_local_mempool(connection_pool.clone(), config.optional.mirrored_tx_ttl());
+        }
         let proxy_cache_updater_pool = singleton_pool_builder
             .build()
             .await
@@ -315,8 +339,12 @@ async fn init_tasks(
                 .run_account_nonce_sweeper(proxy_cache_updater_pool.clone(), stop_receiver.clone()),
         );
 
+        let tx_sender_config = TxSenderConfig::from(config.clone());
+        tx_sender_config
+            .validate()
+            .context("invalid transaction sender config")?;
         let tx_sender_builder = TxSenderBuilder::new(
-            config.clone().into(),
+            tx_sender_config,
             connection_pool.clone(),
             Arc::new(tx_proxy),
         );
@@ -346,11 +374,12 @@ async fn init_tasks(
         let tx_sender = tx_sender_builder
             .build(
                 fee_params_fetcher,
-                Arc::new(vm_concurrency_limiter),
+                Some(Arc::new(vm_concurrency_limiter)),
                 ApiContracts::load_from_disk(), // TODO (BFT-138): Allow to dynamically reload API contracts
                 storage_caches,
             )
-            .await;
+            .await
+            .context("failed building transaction sender")?;
         (
             tx_sender,
             vm_barrier,
@@ -1,7 +1,7 @@
 use std::{net::SocketAddr, num::NonZeroU32, time::Duration};
 
 use serde::Deserialize;
-use zksync_basic_types::H256;
+use zksync_basic_types::{Address, H256};
 
 pub use crate::configs::PrometheusConfig;
 
@@ -101,6 +101,79 @@ pub struct Web3JsonRpcConfig {
     pub mempool_cache_update_interval: Option<u64>,
     /// Maximum number of transactions to be stored in the mempool cache. Default is 10000.
     pub mempool_cache_size: Option<usize>,
+    /// Wall-clock time limit for a single VM execution in the API server (in ms). If not set, VM executions
+    /// are only limited by gas and storage invocations.
+    pub vm_execution_timeout_ms: Option<u64>,
+    /// Minimum allowed scale factor for estimated gas limits. Default is 1.0.
+    pub min_estimate_gas_scale_factor: Option<f64>,
+    /// Maximum allowed scale factor for estimated gas limits. Default is 10.0.
+    pub max_estimate_gas_scale_factor: Option<f64>,
+    /// Lower bound for `gas_per_pubdata` used during fee estimation. If not set, the value derived
+    /// from the batch fee input is used as is.
+    pub min_gas_per_pubdata: Option<u64>,
+    /// Maximum gas limit for the transaction body considered during gas estimation. Default is the maximum L2 tx gas limit.
+    pub max_gas_limit_for_estimation: Option<u32>,
+    /// Number of nonces beyond `max_nonce_ahead` accepted if all preceding nonces of the account are pending
+    /// in the mempool. Default is 0.
+    pub max_nonce_ahead_grace: Option<u32>,
+    /// Whether to re-execute transactions reverting during gas estimation with the `eth_call` contracts
+    /// to get a more human-readable revert reason.
+    #[serde(default)]
+    pub estimate_gas_playground_revert_reasons: bool,
+    /// Whether to execute transactions with the maximum gas limit before the gas estimation binary search.
+    #[serde(default)]
+    pub estimate_gas_probe_max_gas_limit: bool,
+    /// Whether to estimate gas by extrapolating from an execution with the maximum gas limit (experimental).
+    #[serde(default)]
+    pub estimate_gas_by_extrapolation: bool,
+    /// Whether to check signatures of submitted transactions before any DB reads or VM executions.
+    #[serde(default)]
+    pub early_signature_check: bool,
+    /// Recipient addresses that submitted transactions are not allowed to have.
+    pub forbidden_tx_targets: Option<Vec<Address>>,
+    /// Maximum number of miniblocks the pending block may lag behind the newest known miniblock. If not set,
+    /// the pending block is never considered stale.
+    pub max_pending_block_lag: Option<u32>,
+    /// Maximum number of concurrent gas estimations. Must be positive if set. If not set, estimations are only limited
+    /// by `vm_concurrency_limit`.
+    pub max_concurrent_estimations: Option<usize>,
+    /// Whether the expected nonce of submitted transactions accounts for the account's transactions pending
+    /// in the mempool. If not set, the nonce at the latest sealed miniblock is used.
+    #[serde(default)]
+    pub use_pending_nonces: bool,
+    /// Whether to reject submitted transactions with `gas_per_pubdata_limit` exceeding the maximum supported
+    /// by the VM, instead of accepting them with a warning.
+    #[serde(default)]
+    pub reject_excessive_gas_per_pubdata: bool,
+    /// Maximum size of data returned by `eth_call` (in bytes). If not set, the response size is not limited.
+    pub max_eth_call_response_bytes: Option<usize>,
+    /// Time-to-live for the base fee cached by `eth_gasPrice` (in ms). Default is 1 second.
+    pub gas_price_cache_ttl_ms: Option<u64>,
+    /// If set, `eth_call` is only allowed to target contracts from this list. If not set, all targets are allowed.
+    pub eth_call_target_allowlist: Option<Vec<Address>>,
+    /// Timeout for acquiring a connection to the replica DB in the API server (in ms). If not set, the acquisition
+    /// is only limited by the connection pool itself.
+    pub replica_connection_acquire_timeout_ms: Option<u64>,
+    /// Number of times the final gas estimation step is retried with a bumped gas limit if it runs out of gas. Default is 2.
+    pub estimate_gas_final_step_retries: Option<u32>,
+    /// Relative gas limit increment applied on each retry of the final gas estimation step. Default is 0.05 (i.e., +5%).
+    pub estimate_gas_retry_bump: Option<f64>,
+    /// Number of times reading the expected account nonce is retried on transient DB errors. Default is 2.
+    pub nonce_read_retries: Option<u32>,
+    /// Interval between retries of reading the expected account nonce (in ms). Default is 50 ms.
+    pub nonce_read_retry_interval_ms: Option<u64>,
+    /// Whether to log a summary of each gas estimation at the debug level.
+    #[serde(default)]
+    pub log_estimation_summary: bool,
+    /// Whether to reject submitted transactions initiated by the fee account, instead of validating them like
+    /// transactions from any other account.
+    #[serde(default)]
+    pub reject_fee_account_txs: bool,
+    /// Maximum pubdata a single transaction may publish (in bytes, including its factory deps). Default is
+    /// the maximum pubdata per batch.
+    pub max_pubdata_per_tx: Option<u64>,
+    /// Time-to-live for the fee account balance cached by the transaction sender (in ms). Default is 1 second.
+    pub fee_account_balance_cache_ttl_ms: Option<u64>,
 }
 
 impl Web3JsonRpcConfig {
@@ -138,6 +211,33 @@ impl Web3JsonRpcConfig {
             mempool_cache_update_interval: Default::default(),
             mempool_cache_size: Default::default(),
             tree_api_url: None,
+            vm_execution_timeout_ms: None,
+            min_estimate_gas_scale_factor: None,
+            max_estimate_gas_scale_factor: None,
+            min_gas_per_pubdata: None,
+            max_gas_limit_for_estimation: None,
+            max_nonce_ahead_grace: None,
+            estimate_gas_playground_revert_reasons: false,
+            estimate_gas_probe_max_gas_limit: false,
+            estimate_gas_by_extrapolation: false,
+            early_signature_check: false,
+            forbidden_tx_targets: None,
+            max_pending_block_lag: None,
+            max_concurrent_estimations: None,
+            use_pending_nonces: false,
+            reject_excessive_gas_per_pubdata: false,
+            max_eth_call_response_bytes: None,
+            gas_price_cache_ttl_ms: None,
+            eth_call_target_allowlist: None,
+            replica_connection_acquire_timeout_ms: None,
+            estimate_gas_final_step_retries: None,
+            estimate_gas_retry_bump: None,
+            nonce_read_retries: None,
+            nonce_read_retry_interval_ms: None,
+            log_estimation_summary: false,
+            reject_fee_account_txs: false,
+            max_pubdata_per_tx: None,
+            fee_account_balance_cache_ttl_ms: None,
         }
     }
 
@@ -225,6 +325,32 @@ impl Web3JsonRpcConfig {
     pub fn mempool_cache_size(&self) -> usize {
         self.mempool_cache_size.unwrap_or(10_000)
     }
+
+    pub fn vm_execution_timeout(&self) -> Option<Duration> {
+        self.vm_execution_timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn gas_price_cache_ttl(&self) -> Option<Duration> {
+        self.gas_price_cache_ttl_ms.map(Duration::from_millis)
+    }
+
+    pub fn forbidden_tx_targets(&self) -> Vec<Address> {
+        self.forbidden_tx_targets.clone().unwrap_or_default()
+    }
+
+    pub fn replica_connection_acquire_timeout(&self) -> Option<Duration> {
+        self.replica_connection_acquire_timeout_ms
+            .map(Duration::from_millis)
+    }
+
+    pub fn nonce_read_retry_interval(&self) -> Option<Duration> {
+        self.nonce_read_retry_interval_ms.map(Duration::from_millis)
+    }
+
+    pub fn fee_account_balance_cache_ttl(&self) -> Option<Duration> {
+        self.fee_account_balance_cache_ttl_ms
+            .map(Duration::from_millis)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -212,6 +212,33 @@ impl RandomConfig for configs::api::Web3JsonRpcConfig {
             tree_api_url: g.gen(),
             mempool_cache_update_interval: g.gen(),
             mempool_cache_size: g.gen(),
+            vm_execution_timeout_ms: g.gen(),
+            min_estimate_gas_scale_factor: g.gen(),
+            max_estimate_gas_scale_factor: g.gen(),
+            min_gas_per_pubdata: g.gen(),
+            max_gas_limit_for_estimation: g.gen(),
+            max_nonce_ahead_grace: g.gen(),
+            estimate_gas_playground_revert_reasons: g.gen(),
+            estimate_gas_probe_max_gas_limit: g.gen(),
+            estimate_gas_by_extrapolation: g.gen(),
+            early_signature_check: g.gen(),
+            forbidden_tx_targets: g.gen(),
+            max_pending_block_lag: g.gen(),
+            max_concurrent_estimations: g.gen(),
+            use_pending_nonces: g.gen(),
+            reject_excessive_gas_per_pubdata: g.gen(),
+            max_eth_call_response_bytes: g.gen(),
+            gas_price_cache_ttl_ms: g.gen(),
+            eth_call_target_allowlist: g.gen(),
+            replica_connection_acquire_timeout_ms: g.gen(),
+            estimate_gas_final_step_retries: g.gen(),
+            estimate_gas_retry_bump: g.gen(),
+            nonce_read_retries: g.gen(),
+            nonce_read_retry_interval_ms: g.gen(),
+            log_estimation_summary: g.gen(),
+            reject_fee_account_txs: g.gen(),
+            max_pubdata_per_tx: g.gen(),
+            fee_account_balance_cache_ttl_ms: g.gen(),
         }
     }
 }
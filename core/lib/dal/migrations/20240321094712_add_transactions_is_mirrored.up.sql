@@ -0,0 +1 @@
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS is_mirrored BOOLEAN NOT NULL DEFAULT FALSE;
@@ -64,6 +64,11 @@ pub struct StorageTransaction {
 
     pub upgrade_id: Option<i32>,
 
+    /// Deadline for the transaction inclusion; only set for L2 transactions.
+    pub valid_until: Option<NaiveDateTime>,
+    /// Whether the transaction was proxied to the main node and mirrored locally by an external node.
+    pub is_mirrored: bool,
+
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
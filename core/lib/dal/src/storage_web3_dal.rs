@@ -54,6 +54,36 @@ impl StorageWeb3Dal<'_, '_> {
         Ok(res)
     }
 
+    /// Returns the stored nonces for the specified accounts at the specified miniblock, using a single DB query.
+    /// Accounts without a stored nonce have zero nonce. As with [`Self::get_historical_value_unchecked()`],
+    /// the miniblock is not checked to exist.
+    pub async fn get_historical_nonces_for_addresses(
+        &mut self,
+        addresses: &[Address],
+        block_number: MiniblockNumber,
+    ) -> sqlx::Result<HashMap<Address, Nonce>> {
+        let nonce_keys: HashMap<_, _> = addresses
+            .iter()
+            .map(|address| (get_nonce_key(address).hashed_key(), *address))
+            .collect();
+        let hashed_keys: Vec<_> = nonce_keys.keys().copied().collect();
+        let values = self
+            .storage
+            .storage_logs_dal()
+            .get_storage_values(&hashed_keys, block_number)
+            .await?;
+
+        Ok(nonce_keys
+            .into_iter()
+            .map(|(hashed_key, address)| {
+                let value = values.get(&hashed_key).copied().flatten();
+                let full_nonce = h256_to_u256(value.unwrap_or_default());
+                let (nonce, _) = decompose_full_nonce(full_nonce);
+                (address, Nonce(nonce.as_u32()))
+            })
+            .collect())
+    }
+
     pub async fn standard_token_historical_balance(
         &mut self,
         token_id: AccountTreeId,
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use chrono::Utc;
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_db_connection::connection_pool::ConnectionPool;
 use zksync_types::{
@@ -13,7 +14,7 @@ use zksync_types::{
     snapshots::SnapshotRecoveryStatus,
     tx::{tx_execution_info::TxExecutionStatus, ExecutionMetrics, TransactionExecutionResult},
     Address, Execute, L1BatchNumber, L1BlockNumber, L1TxCommonData, L2ChainId, MiniblockNumber,
-    PriorityOpId, ProtocolVersionId, H160, H256, U256,
+    PriorityOpId, ProtocolVersionId, Transaction, H160, H256, U256,
 };
 
 use crate::{
@@ -214,6 +215,98 @@ async fn workflow_with_submit_tx_diff_hashes() {
     assert_eq!(result, L2TxSubmissionResult::Replaced);
 }
 
+#[tokio::test]
+async fn expired_txs_are_not_synced_to_mempool() {
+    let connection_pool = ConnectionPool::<Core>::test_pool().await;
+    let storage = &mut connection_pool.connection().await.unwrap();
+    let mut protocol_versions_dal = ProtocolVersionsDal { storage };
+    protocol_versions_dal
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+
+    let storage = protocol_versions_dal.storage;
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let expired_tx = mock_l2_transaction();
+    let valid_until = Utc::now() - chrono::Duration::seconds(10);
+    transactions_dal
+        .insert_transaction_l2_with_deadline(
+            expired_tx,
+            mock_tx_execution_metrics(),
+            Some(valid_until),
+        )
+        .await
+        .unwrap();
+    let tx_with_future_deadline = mock_l2_transaction();
+    let valid_until = Utc::now() + chrono::Duration::hours(1);
+    transactions_dal
+        .insert_transaction_l2_with_deadline(
+            tx_with_future_deadline.clone(),
+            mock_tx_execution_metrics(),
+            Some(valid_until),
+        )
+        .await
+        .unwrap();
+    let tx_without_deadline = mock_l2_transaction();
+    transactions_dal
+        .insert_transaction_l2(tx_without_deadline.clone(), mock_tx_execution_metrics())
+        .await
+        .unwrap();
+
+    let txs = transactions_dal
+        .sync_mempool(&[], &[], 0, 0, 1000)
+        .await
+        .unwrap();
+    let mut tx_hashes: Vec<_> = txs.iter().map(Transaction::hash).collect();
+    tx_hashes.sort_unstable();
+    let mut expected_hashes = [tx_with_future_deadline.hash(), tx_without_deadline.hash()];
+    expected_hashes.sort_unstable();
+    assert_eq!(tx_hashes, expected_hashes);
+}
+
+#[tokio::test]
+async fn remove_stale_mirrored_txs() {
+    let connection_pool = ConnectionPool::<Core>::test_pool().await;
+    let storage = &mut connection_pool.connection().await.unwrap();
+    let mut protocol_versions_dal = ProtocolVersionsDal { storage };
+    protocol_versions_dal
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+
+    let storage = protocol_versions_dal.storage;
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let received_timestamp_ms = unix_timestamp_ms() - Duration::new(1000, 0).as_millis() as u64;
+    let mut mirrored_tx = mock_l2_transaction();
+    mirrored_tx.received_timestamp_ms = received_timestamp_ms;
+    transactions_dal
+        .insert_transaction_l2(mirrored_tx.clone(), mock_tx_execution_metrics())
+        .await
+        .unwrap();
+    transactions_dal
+        .mark_tx_as_mirrored(mirrored_tx.hash())
+        .await
+        .unwrap();
+    // Old pending tx that is not mirrored
+    let mut tx = mock_l2_transaction();
+    tx.received_timestamp_ms = received_timestamp_ms;
+    transactions_dal
+        .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+        .await
+        .unwrap();
+
+    let removed_txs = transactions_dal
+        .remove_stale_mirrored_txs(Duration::from_secs(500))
+        .await
+        .unwrap();
+    assert_eq!(removed_txs, 1);
+    assert!(transactions_dal
+        .get_tx_by_hash(mirrored_tx.hash())
+        .await
+        .is_none());
+    assert!(transactions_dal.get_tx_by_hash(tx.hash()).await.is_some());
+}
+
 #[tokio::test]
 async fn remove_stuck_txs() {
     let connection_pool = ConnectionPool::<Core>::test_pool().await;
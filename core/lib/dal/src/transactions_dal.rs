@@ -3,7 +3,10 @@ use std::{collections::HashMap, fmt, time::Duration};
 use anyhow::Context as _;
 use bigdecimal::BigDecimal;
 use itertools::Itertools;
-use sqlx::{error, types::chrono::NaiveDateTime};
+use sqlx::{
+    error,
+    types::chrono::{DateTime, NaiveDateTime, Utc},
+};
 use zksync_db_connection::{
     connection::Connection, instrument::InstrumentExt, utils::pg_interval_from_duration,
 };
@@ -259,6 +262,19 @@ impl TransactionsDal<'_, '_> {
         &mut self,
         tx: L2Tx,
         exec_info: TransactionExecutionMetrics,
+    ) -> sqlx::Result<L2TxSubmissionResult> {
+        self.insert_transaction_l2_with_deadline(tx, exec_info, None)
+            .await
+    }
+
+    /// Same as [`Self::insert_transaction_l2()`], but additionally stores the deadline for the transaction inclusion.
+    /// Transactions past their deadline are not returned by [`Self::sync_mempool()`], i.e., they are not picked up
+    /// by the state keeper.
+    pub async fn insert_transaction_l2_with_deadline(
+        &mut self,
+        tx: L2Tx,
+        exec_info: TransactionExecutionMetrics,
+        valid_until: Option<DateTime<Utc>>,
     ) -> sqlx::Result<L2TxSubmissionResult> {
         let tx_hash = tx.hash();
         let is_duplicate = sqlx::query!(
@@ -301,6 +317,7 @@ impl TransactionsDal<'_, '_> {
         let nanosecs = ((tx.received_timestamp_ms % 1000) * 1_000_000) as u32;
         #[allow(deprecated)]
         let received_at = NaiveDateTime::from_timestamp_opt(secs, nanosecs).unwrap();
+        let valid_until = valid_until.map(|timestamp| timestamp.naive_utc());
         // Besides just adding or updating(on conflict) the record, we want to extract some info
         // from the query below, to indicate what actually happened:
         // 1) transaction is added
@@ -333,6 +350,7 @@ impl TransactionsDal<'_, '_> {
                     paymaster_input,
                     execution_info,
                     received_at,
+                    valid_until,
                     created_at,
                     updated_at
                 )
@@ -356,6 +374,7 @@ impl TransactionsDal<'_, '_> {
                     $15,
                     JSONB_BUILD_OBJECT('gas_used', $16::BIGINT, 'storage_writes', $17::INT, 'contracts_used', $18::INT),
                     $19,
+                    $20,
                     NOW(),
                     NOW()
                 )
@@ -378,6 +397,7 @@ impl TransactionsDal<'_, '_> {
                 execution_info = JSONB_BUILD_OBJECT('gas_used', $16::BIGINT, 'storage_writes', $17::INT, 'contracts_used', $18::INT),
                 in_mempool = FALSE,
                 received_at = $19,
+                valid_until = $20,
                 created_at = NOW(),
                 updated_at = NOW(),
                 error = NULL
@@ -413,7 +433,8 @@ impl TransactionsDal<'_, '_> {
             exec_info.gas_used as i64,
             (exec_info.initial_storage_writes + exec_info.repeated_storage_writes) as i32,
             exec_info.contracts_used as i32,
-            received_at
+            received_at,
+            valid_until
         )
             .fetch_optional(self.storage.conn())
             .await
@@ -882,6 +903,45 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Marks an L2 transaction as mirrored, i.e., proxied to the main node and inserted locally by an external node.
+    pub async fn mark_tx_as_mirrored(&mut self, tx_hash: H256) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET
+                is_mirrored = TRUE
+            WHERE
+                hash = $1
+            "#,
+            tx_hash.as_bytes()
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Removes mirrored transactions that were not synced back from the main node within `ttl`.
+    /// Unlike [`Self::remove_stuck_txs()`], other pending transactions are not affected.
+    pub async fn remove_stale_mirrored_txs(&mut self, ttl: Duration) -> sqlx::Result<usize> {
+        let ttl = pg_interval_from_duration(ttl);
+        let rows = sqlx::query!(
+            r#"
+            DELETE FROM transactions
+            WHERE
+                is_mirrored = TRUE
+                AND miniblock_number IS NULL
+                AND received_at < NOW() - $1::INTERVAL
+            RETURNING
+                hash
+            "#,
+            ttl
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows.len())
+    }
+
     pub async fn remove_stuck_txs(&mut self, stuck_tx_timeout: Duration) -> sqlx::Result<usize> {
         let stuck_tx_timeout = pg_interval_from_duration(stuck_tx_timeout);
         let rows = sqlx::query!(
@@ -964,6 +1024,10 @@ impl TransactionsDal<'_, '_> {
                                 miniblock_number IS NULL
                                 AND in_mempool = FALSE
                                 AND error IS NULL
+                                AND (
+                                    valid_until IS NULL
+                                    OR valid_until > NOW()
+                                )
                                 AND (
                                     is_priority = TRUE
                                     OR (
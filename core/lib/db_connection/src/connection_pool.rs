@@ -325,6 +325,13 @@ impl<DB: DbMarker> ConnectionPool<DB> {
         self.max_size
     }
 
+    /// Checks whether this pool connects to the same database as `other`. The check compares database URLs,
+    /// so pools may point to the same database even if this method returns `false` (e.g., if URLs use different hosts
+    /// resolving to the same server).
+    pub fn has_same_database<Other: DbMarker>(&self, other: &ConnectionPool<Other>) -> bool {
+        self.database_url == other.database_url
+    }
+
     /// Creates a `Connection` entity over a recoverable connection.
     /// Upon a database outage connection will block the thread until
     /// it will be able to recover the connection (or, if connection cannot
@@ -465,4 +472,26 @@ mod tests {
             sqlx::Error::Database(db_err) if db_err.message().contains("statement timeout")
         );
     }
+
+    #[tokio::test]
+    async fn comparing_pool_databases() {
+        let db_url = TestTemplate::empty()
+            .unwrap()
+            .create_db::<InternalMarker>(2)
+            .await
+            .unwrap()
+            .database_url;
+        let pool = ConnectionPool::<InternalMarker>::singleton(&db_url)
+            .build()
+            .await
+            .unwrap();
+        let other_pool = ConnectionPool::<InternalMarker>::builder(&db_url, 2)
+            .build()
+            .await
+            .unwrap();
+        assert!(pool.has_same_database(&other_pool));
+
+        let test_pool = ConnectionPool::<InternalMarker>::test_pool().await;
+        assert!(!pool.has_same_database(&test_pool));
+    }
 }
@@ -51,7 +51,7 @@ mod tests {
     use std::num::NonZeroU32;
 
     use super::*;
-    use crate::test_utils::{hash, EnvMutex};
+    use crate::test_utils::{addr, hash, EnvMutex};
 
     static MUTEX: EnvMutex = EnvMutex::new();
 
@@ -90,6 +90,38 @@ mod tests {
                 tree_api_url: None,
                 mempool_cache_update_interval: Some(50),
                 mempool_cache_size: Some(10000),
+                vm_execution_timeout_ms: Some(5000),
+                min_estimate_gas_scale_factor: None,
+                max_estimate_gas_scale_factor: None,
+                min_gas_per_pubdata: None,
+                max_gas_limit_for_estimation: None,
+                max_nonce_ahead_grace: Some(2),
+                estimate_gas_playground_revert_reasons: false,
+                estimate_gas_probe_max_gas_limit: false,
+                estimate_gas_by_extrapolation: false,
+                early_signature_check: true,
+                forbidden_tx_targets: Some(vec![addr(
+                    "0x0000000000000000000000000000000000008006",
+                )]),
+                max_pending_block_lag: None,
+                max_concurrent_estimations: Some(16),
+                use_pending_nonces: false,
+                reject_excessive_gas_per_pubdata: false,
+                max_eth_call_response_bytes: None,
+                gas_price_cache_ttl_ms: Some(500),
+                eth_call_target_allowlist: Some(vec![
+                    addr("0x0000000000000000000000000000000000008008"),
+                    addr("0x000000000000000000000000000000000000800a"),
+                ]),
+                replica_connection_acquire_timeout_ms: Some(2000),
+                estimate_gas_final_step_retries: Some(3),
+                estimate_gas_retry_bump: Some(0.1),
+                nonce_read_retries: Some(5),
+                nonce_read_retry_interval_ms: Some(100),
+                log_estimation_summary: true,
+                reject_fee_account_txs: true,
+                max_pubdata_per_tx: Some(60000),
+                fee_account_balance_cache_ttl_ms: Some(200),
             },
             contract_verification: ContractVerificationApiConfig {
                 port: 3070,
@@ -138,6 +170,22 @@ mod tests {
             API_WEB3_JSON_RPC_MAX_BATCH_REQUEST_SIZE=200
             API_WEB3_JSON_RPC_WEBSOCKET_REQUESTS_PER_MINUTE_LIMIT=10
             API_WEB3_JSON_RPC_MEMPOOL_CACHE_SIZE=10000
+            API_WEB3_JSON_RPC_VM_EXECUTION_TIMEOUT_MS=5000
+            API_WEB3_JSON_RPC_MAX_NONCE_AHEAD_GRACE=2
+            API_WEB3_JSON_RPC_EARLY_SIGNATURE_CHECK=true
+            API_WEB3_JSON_RPC_FORBIDDEN_TX_TARGETS="0x0000000000000000000000000000000000008006"
+            API_WEB3_JSON_RPC_MAX_CONCURRENT_ESTIMATIONS=16
+            API_WEB3_JSON_RPC_GAS_PRICE_CACHE_TTL_MS=500
+            API_WEB3_JSON_RPC_ETH_CALL_TARGET_ALLOWLIST="0x0000000000000000000000000000000000008008,0x000000000000000000000000000000000000800a"
+            API_WEB3_JSON_RPC_REPLICA_CONNECTION_ACQUIRE_TIMEOUT_MS=2000
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_FINAL_STEP_RETRIES=3
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_RETRY_BUMP=0.1
+            API_WEB3_JSON_RPC_NONCE_READ_RETRIES=5
+            API_WEB3_JSON_RPC_NONCE_READ_RETRY_INTERVAL_MS=100
+            API_WEB3_JSON_RPC_LOG_ESTIMATION_SUMMARY=true
+            API_WEB3_JSON_RPC_REJECT_FEE_ACCOUNT_TXS=true
+            API_WEB3_JSON_RPC_MAX_PUBDATA_PER_TX=60000
+            API_WEB3_JSON_RPC_FEE_ACCOUNT_BALANCE_CACHE_TTL_MS=200
             API_WEB3_JSON_RPC_MEMPOOL_CACHE_UPDATE_INTERVAL=50
             API_CONTRACT_VERIFICATION_PORT="3070"
             API_CONTRACT_VERIFICATION_URL="http://127.0.0.1:3070"
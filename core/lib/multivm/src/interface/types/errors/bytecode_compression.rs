@@ -1,8 +1,40 @@
 use thiserror::Error;
+use zksync_types::H256;
+use zksync_utils::bytecode::{hash_bytecode, CompressedBytecodeInfo};
 
 /// Errors related to bytecode compression.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum BytecodeCompressionError {
     #[error("Bytecode compression failed")]
     BytecodeCompressionFailed,
+    /// A bytecode was compressed, but its compressed form was not published during transaction execution
+    /// (e.g., there wasn't enough gas to publish it, or the bootloader rejected the compressed form).
+    #[error(
+        "Compressed bytecode {bytecode_hash:?} was not published \
+         (original size: {original_len} bytes, compressed size: {compressed_len} bytes)"
+    )]
+    BytecodeNotPublished {
+        bytecode_hash: H256,
+        original_len: usize,
+        compressed_len: usize,
+    },
+}
+
+impl BytecodeCompressionError {
+    /// Creates an error for a compressed bytecode that was not published.
+    pub fn not_published(info: &CompressedBytecodeInfo) -> Self {
+        Self::BytecodeNotPublished {
+            bytecode_hash: hash_bytecode(&info.original),
+            original_len: info.original.len(),
+            compressed_len: info.compressed.len(),
+        }
+    }
+
+    /// Returns the hash of the offending bytecode, if it is known.
+    pub fn bytecode_hash(&self) -> Option<H256> {
+        match self {
+            Self::BytecodeCompressionFailed => None,
+            Self::BytecodeNotPublished { bytecode_hash, .. } => Some(*bytecode_hash),
+        }
+    }
 }
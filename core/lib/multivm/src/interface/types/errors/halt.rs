@@ -42,6 +42,8 @@ pub enum Halt {
     VMPanic,
     TracerCustom(String),
     FailedToPublishCompressedBytecodes,
+    // Execution was aborted because its wall-clock deadline has passed
+    ExecutionDeadlineExceeded,
 }
 
 impl Display for Halt {
@@ -116,6 +118,9 @@ impl Display for Halt {
             Halt::FailedToPublishCompressedBytecodes => {
                 write!(f, "Failed to publish compressed bytecodes")
             }
+            Halt::ExecutionDeadlineExceeded => {
+                write!(f, "Execution deadline exceeded")
+            }
         }
     }
 }
@@ -0,0 +1,48 @@
+use std::time::Instant;
+
+use crate::glue::tracers::IntoOldVmTracer;
+
+pub mod vm_1_4_1;
+pub mod vm_boojum_integration;
+pub mod vm_latest;
+pub mod vm_refunds_enhancement;
+pub mod vm_virtual_blocks;
+
+/// Tracer stopping the VM execution once the wall-clock deadline has passed.
+/// The deadline is checked every [`Self::CHECK_INTERVAL`] VM cycles to keep the overhead low.
+///
+/// Not supported for VM versions before virtual blocks; for these versions, the deadline is ignored.
+#[derive(Debug, Clone)]
+pub struct ExecutionDeadline {
+    deadline: Instant,
+    cycles_since_check: usize,
+    /// Set once the deadline check succeeds; used by VM versions that query the stop condition separately
+    /// from processing cycles.
+    exceeded: bool,
+}
+
+impl ExecutionDeadline {
+    /// Number of VM cycles between deadline checks.
+    pub const CHECK_INTERVAL: usize = 256;
+
+    pub fn new(deadline: Instant) -> Self {
+        Self {
+            deadline,
+            cycles_since_check: 0,
+            exceeded: false,
+        }
+    }
+
+    /// Returns whether the deadline is exceeded. Checks the current time only once per [`Self::CHECK_INTERVAL`] calls.
+    fn is_exceeded(&mut self) -> bool {
+        self.cycles_since_check += 1;
+        if self.cycles_since_check < Self::CHECK_INTERVAL {
+            return false;
+        }
+        self.cycles_since_check = 0;
+        Instant::now() >= self.deadline
+    }
+}
+
+/// Old VMs don't support stopping execution by deadline.
+impl IntoOldVmTracer for ExecutionDeadline {}
@@ -36,26 +36,58 @@ pub struct ValidationTracer<H> {
     #[allow(dead_code)]
     paymaster_address: Address,
     should_stop_execution: bool,
+    /// Whether the VM should be stopped once the validation step ends.
+    stop_after_validation: bool,
     trusted_slots: HashSet<(Address, U256)>,
     trusted_addresses: HashSet<Address>,
     trusted_address_slots: HashSet<(Address, U256)>,
     computational_gas_used: u32,
     computational_gas_limit: u32,
     pub result: Arc<OnceCell<ViolatedValidationRule>>,
+    validation_ended: Arc<OnceCell<()>>,
     _marker: PhantomData<fn(H) -> H>,
 }
 
 type ValidationRoundResult = Result<NewTrustedValidationItems, ViolatedValidationRule>;
 
 impl<H> ValidationTracer<H> {
+    /// Creates a tracer that stops the VM once the validation step of the transaction ends.
     pub fn new(params: ValidationTracerParams) -> (Self, Arc<OnceCell<ViolatedValidationRule>>) {
+        let (tracer, result, _) = Self::new_inner(params, true);
+        (tracer, result)
+    }
+
+    /// Creates a tracer that lets the VM execute the transaction after the validation step ends, so that
+    /// the transaction is validated and executed in a single VM run. Besides the validation result, returns a cell
+    /// that is set once the validation step ends; if it's not set after the run, the transaction halted
+    /// during validation.
+    pub fn new_for_execution(
+        params: ValidationTracerParams,
+    ) -> (
+        Self,
+        Arc<OnceCell<ViolatedValidationRule>>,
+        Arc<OnceCell<()>>,
+    ) {
+        Self::new_inner(params, false)
+    }
+
+    fn new_inner(
+        params: ValidationTracerParams,
+        stop_after_validation: bool,
+    ) -> (
+        Self,
+        Arc<OnceCell<ViolatedValidationRule>>,
+        Arc<OnceCell<()>>,
+    ) {
         let result = Arc::new(OnceCell::new());
+        let validation_ended = Arc::new(OnceCell::new());
         (
             Self {
                 validation_mode: ValidationTracerMode::NoValidation,
                 auxilary_allowed_slots: Default::default(),
 
                 should_stop_execution: false,
+                stop_after_validation,
                 user_address: params.user_address,
                 paymaster_address: params.paymaster_address,
                 trusted_slots: params.trusted_slots,
@@ -64,12 +96,22 @@ impl<H> ValidationTracer<H> {
                 computational_gas_used: 0,
                 computational_gas_limit: params.computational_gas_limit,
                 result: result.clone(),
+                validation_ended: validation_ended.clone(),
                 _marker: Default::default(),
             },
             result,
+            validation_ended,
         )
     }
 
+    fn end_validation(&mut self) {
+        self.validation_mode = ValidationTracerMode::NoValidation;
+        self.validation_ended.set(()).ok();
+        if self.stop_after_validation {
+            self.should_stop_execution = true;
+        }
+    }
+
     fn process_validation_round_result(&mut self, result: ValidationRoundResult) {
         match result {
             Ok(NewTrustedValidationItems {
@@ -173,7 +173,7 @@ impl<S: WriteStorage, H: HistoryMode> DynTracer<S, SimpleMemory<H::VmBoojumInteg
             }
             (_, VmHook::ValidationStepEndeded) => {
                 // The validation step has ended.
-                self.should_stop_execution = true;
+                self.end_validation();
             }
             (_, _) => {
                 // The hook is not relevant to the validation tracer. Ignore.
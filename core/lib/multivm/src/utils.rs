@@ -1,3 +1,4 @@
+use zksync_system_constants::IntrinsicSystemGasConstants;
 use zksync_types::{
     fee_model::{BatchFeeInput, L1PeggedBatchFeeModelInput, PubdataIndependentBatchFeeModelInput},
     VmVersion, U256,
@@ -366,3 +367,22 @@ pub fn get_used_bootloader_memory_words(version: VmVersion) -> usize {
         VmVersion::Vm1_4_2 => crate::vm_latest::constants::USED_BOOTLOADER_MEMORY_WORDS,
     }
 }
+
+/// Returns intrinsic gas constants for transactions executed by the specified VM version.
+///
+/// Intrinsic constants are only generated for the latest bootloader, so they are used for all VM versions.
+/// A VM version with diverging constants should get its own match arm.
+pub fn get_intrinsic_constants(version: VmVersion) -> IntrinsicSystemGasConstants {
+    match version {
+        VmVersion::M5WithRefunds
+        | VmVersion::M5WithoutRefunds
+        | VmVersion::M6Initial
+        | VmVersion::M6BugWithCompressionFixed
+        | VmVersion::Vm1_3_2
+        | VmVersion::VmVirtualBlocks
+        | VmVersion::VmVirtualBlocksRefundsEnhancement
+        | VmVersion::VmBoojumIntegration
+        | VmVersion::Vm1_4_1
+        | VmVersion::Vm1_4_2 => zksync_system_constants::get_intrinsic_constants(),
+    }
+}
@@ -150,11 +150,8 @@ impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
     ) {
         self.push_transaction_with_compression(tx, with_compression);
         let result = self.inspect_inner(tracer, VmExecutionMode::OneTx, None);
-        if self.has_unpublished_bytecodes() {
-            (
-                Err(BytecodeCompressionError::BytecodeCompressionFailed),
-                result,
-            )
+        if let Some(info) = self.find_unpublished_bytecode() {
+            (Err(BytecodeCompressionError::not_published(&info)), result)
         } else {
             (Ok(()), result)
         }
@@ -9,17 +9,20 @@ use zksync_utils::{
 use crate::{interface::VmInterface, vm_boojum_integration::Vm, HistoryMode};
 
 impl<S: WriteStorage, H: HistoryMode> Vm<S, H> {
-    /// Checks the last transaction has successfully published compressed bytecodes and returns `true` if there is at least one is still unknown.
-    pub(crate) fn has_unpublished_bytecodes(&mut self) -> bool {
-        self.get_last_tx_compressed_bytecodes().iter().any(|info| {
-            !self
-                .state
-                .storage
-                .storage
-                .get_ptr()
-                .borrow_mut()
-                .is_bytecode_known(&hash_bytecode(&info.original))
-        })
+    /// Checks the last transaction has successfully published compressed bytecodes and returns the first one
+    /// that is still unknown, if any.
+    pub(crate) fn find_unpublished_bytecode(&mut self) -> Option<CompressedBytecodeInfo> {
+        self.get_last_tx_compressed_bytecodes()
+            .into_iter()
+            .find(|info| {
+                !self
+                    .state
+                    .storage
+                    .storage
+                    .get_ptr()
+                    .borrow_mut()
+                    .is_bytecode_known(&hash_bytecode(&info.original))
+            })
     }
 }
 
@@ -5,7 +5,7 @@ use zksync_protobuf::{
     required,
 };
 
-use crate::{parse_h256, proto::api as proto};
+use crate::{parse_h160, parse_h256, proto::api as proto};
 
 impl ProtoRepr for proto::Api {
     type Type = ApiConfig;
@@ -126,6 +126,73 @@ impl ProtoRepr for proto::Web3JsonRpc {
                 .map(|x| x.try_into())
                 .transpose()
                 .context("mempool_cache_size")?,
+            vm_execution_timeout_ms: self.vm_execution_timeout_ms,
+            min_estimate_gas_scale_factor: self.min_estimate_gas_scale_factor,
+            max_estimate_gas_scale_factor: self.max_estimate_gas_scale_factor,
+            min_gas_per_pubdata: self.min_gas_per_pubdata,
+            max_gas_limit_for_estimation: self.max_gas_limit_for_estimation,
+            max_nonce_ahead_grace: self.max_nonce_ahead_grace,
+            estimate_gas_playground_revert_reasons: self
+                .estimate_gas_playground_revert_reasons
+                .unwrap_or(false),
+            estimate_gas_probe_max_gas_limit: self
+                .estimate_gas_probe_max_gas_limit
+                .unwrap_or(false),
+            estimate_gas_by_extrapolation: self.estimate_gas_by_extrapolation.unwrap_or(false),
+            early_signature_check: self.early_signature_check.unwrap_or(false),
+            forbidden_tx_targets: self
+                .forbidden_tx_targets
+                .as_ref()
+                .map(|targets| {
+                    targets
+                        .addresses
+                        .iter()
+                        .enumerate()
+                        .map(|(i, addr)| parse_h160(addr).context(i))
+                        .collect::<Result<_, _>>()
+                        .context("addresses")
+                })
+                .transpose()
+                .context("forbidden_tx_targets")?,
+            max_pending_block_lag: self.max_pending_block_lag,
+            max_concurrent_estimations: self
+                .max_concurrent_estimations
+                .map(|x| x.try_into())
+                .transpose()
+                .context("max_concurrent_estimations")?,
+            use_pending_nonces: self.use_pending_nonces.unwrap_or(false),
+            reject_excessive_gas_per_pubdata: self
+                .reject_excessive_gas_per_pubdata
+                .unwrap_or(false),
+            max_eth_call_response_bytes: self
+                .max_eth_call_response_bytes
+                .map(|x| x.try_into())
+                .transpose()
+                .context("max_eth_call_response_bytes")?,
+            gas_price_cache_ttl_ms: self.gas_price_cache_ttl_ms,
+            eth_call_target_allowlist: self
+                .eth_call_target_allowlist
+                .as_ref()
+                .map(|targets| {
+                    targets
+                        .addresses
+                        .iter()
+                        .enumerate()
+                        .map(|(i, addr)| parse_h160(addr).context(i))
+                        .collect::<Result<_, _>>()
+                        .context("addresses")
+                })
+                .transpose()
+                .context("eth_call_target_allowlist")?,
+            replica_connection_acquire_timeout_ms: self.replica_connection_acquire_timeout_ms,
+            estimate_gas_final_step_retries: self.estimate_gas_final_step_retries,
+            estimate_gas_retry_bump: self.estimate_gas_retry_bump,
+            nonce_read_retries: self.nonce_read_retries,
+            nonce_read_retry_interval_ms: self.nonce_read_retry_interval_ms,
+            log_estimation_summary: self.log_estimation_summary.unwrap_or(false),
+            reject_fee_account_txs: self.reject_fee_account_txs.unwrap_or(false),
+            max_pubdata_per_tx: self.max_pubdata_per_tx,
+            fee_account_balance_cache_ttl_ms: self.fee_account_balance_cache_ttl_ms,
         })
     }
     fn build(this: &Self::Type) -> Self {
@@ -177,6 +244,47 @@ impl ProtoRepr for proto::Web3JsonRpc {
                 .websocket_requests_per_minute_limit
                 .map(|x| x.into()),
             tree_api_url: this.tree_api_url.clone(),
+            vm_execution_timeout_ms: this.vm_execution_timeout_ms,
+            min_estimate_gas_scale_factor: this.min_estimate_gas_scale_factor,
+            max_estimate_gas_scale_factor: this.max_estimate_gas_scale_factor,
+            min_gas_per_pubdata: this.min_gas_per_pubdata,
+            max_gas_limit_for_estimation: this.max_gas_limit_for_estimation,
+            max_nonce_ahead_grace: this.max_nonce_ahead_grace,
+            estimate_gas_playground_revert_reasons: Some(
+                this.estimate_gas_playground_revert_reasons,
+            ),
+            estimate_gas_probe_max_gas_limit: Some(this.estimate_gas_probe_max_gas_limit),
+            estimate_gas_by_extrapolation: Some(this.estimate_gas_by_extrapolation),
+            early_signature_check: Some(this.early_signature_check),
+            forbidden_tx_targets: this.forbidden_tx_targets.as_ref().map(|targets| {
+                proto::Addresses {
+                    addresses: targets.iter().map(|addr| addr.as_bytes().into()).collect(),
+                }
+            }),
+            max_pending_block_lag: this.max_pending_block_lag,
+            max_concurrent_estimations: this
+                .max_concurrent_estimations
+                .map(|x| x.try_into().unwrap()),
+            use_pending_nonces: Some(this.use_pending_nonces),
+            reject_excessive_gas_per_pubdata: Some(this.reject_excessive_gas_per_pubdata),
+            max_eth_call_response_bytes: this
+                .max_eth_call_response_bytes
+                .map(|x| x.try_into().unwrap()),
+            gas_price_cache_ttl_ms: this.gas_price_cache_ttl_ms,
+            eth_call_target_allowlist: this.eth_call_target_allowlist.as_ref().map(|targets| {
+                proto::Addresses {
+                    addresses: targets.iter().map(|addr| addr.as_bytes().into()).collect(),
+                }
+            }),
+            replica_connection_acquire_timeout_ms: this.replica_connection_acquire_timeout_ms,
+            estimate_gas_final_step_retries: this.estimate_gas_final_step_retries,
+            estimate_gas_retry_bump: this.estimate_gas_retry_bump,
+            nonce_read_retries: this.nonce_read_retries,
+            nonce_read_retry_interval_ms: this.nonce_read_retry_interval_ms,
+            log_estimation_summary: Some(this.log_estimation_summary),
+            reject_fee_account_txs: Some(this.reject_fee_account_txs),
+            max_pubdata_per_tx: this.max_pubdata_per_tx,
+            fee_account_balance_cache_ttl_ms: this.fee_account_balance_cache_ttl_ms,
         }
     }
 }
@@ -1,14 +1,29 @@
-use std::hash::Hash;
+use std::{
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 use crate::cache::{
     metrics::{Method, RequestOutcome, METRICS},
-    CacheValue, MokaBase,
+    CacheStats, CacheValue, MokaBase,
 };
+
+/// Hit / miss counters shared among all clones of an [`LruCache`].
+#[derive(Debug, Default)]
+struct StatsCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
 /// Cache implementation that uses LRU eviction policy.
 #[derive(Debug, Clone)]
 pub struct LruCache<K: Eq + Hash, V> {
     name: &'static str,
     cache: Option<MokaBase<K, V>>,
+    stats: Arc<StatsCounters>,
 }
 
 impl<K, V> LruCache<K, V>
@@ -33,7 +48,11 @@ where
             )
         };
 
-        Self { name, cache }
+        Self {
+            name,
+            cache,
+            stats: Arc::default(),
+        }
     }
 
     /// Gets an entry and pulls it to the front if it exists.
@@ -44,8 +63,10 @@ where
 
         latency.observe();
         let request_outcome = if entry.is_some() {
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
             RequestOutcome::Hit
         } else {
+            self.stats.misses.fetch_add(1, Ordering::Relaxed);
             RequestOutcome::Miss
         };
         METRICS.requests[&(self.name, request_outcome)].inc();
@@ -88,6 +109,14 @@ where
         }
     }
 
+    /// Returns the total number of hits and misses recorded by this cache since its creation.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.stats.hits.load(Ordering::Relaxed),
+            misses: self.stats.misses.load(Ordering::Relaxed),
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn estimated_len(&self) -> u64 {
         self.cache.as_ref().map_or(0, MokaBase::entry_count)
@@ -116,4 +145,23 @@ mod tests {
         // The item is evicted after the first access.
         assert_eq!(not_quite_zero_cache.get(&H256::zero()), None);
     }
+
+    #[test]
+    fn cache_stats() {
+        let cache = LruCache::<H256, Vec<u8>>::new("test", 1 << 20);
+        assert_eq!(cache.stats(), CacheStats::default());
+
+        assert_eq!(cache.get(&H256::zero()), None);
+        cache.insert(H256::zero(), vec![1, 2, 3]);
+        let cloned_cache = cache.clone();
+        assert_eq!(cloned_cache.get(&H256::zero()), Some(vec![1, 2, 3]));
+        let stats = cache.stats();
+        assert_eq!(stats, CacheStats { hits: 1, misses: 1 });
+
+        assert_eq!(cache.get(&H256::repeat_byte(1)), None);
+        assert_eq!(
+            cache.stats().since(stats),
+            CacheStats { hits: 0, misses: 1 }
+        );
+    }
 }
@@ -1,5 +1,7 @@
 //! Generic cache abstraction used by storage implementations.
 
+use std::ops;
+
 pub mod lru_cache;
 mod metrics;
 pub mod sequential_cache;
@@ -13,3 +15,33 @@ pub trait CacheValue<K>: Clone + Send + Sync {
     /// data (and, potentially, the key byte size if the value size is always small).
     fn cache_weight(&self) -> u32;
 }
+
+/// Snapshot of hit / miss counters for a cache or a group of caches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of cache lookups that returned a value.
+    pub hits: u64,
+    /// Number of cache lookups that didn't return a value.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Returns the number of hits and misses recorded since the `earlier` snapshot was taken.
+    pub fn since(self, earlier: Self) -> Self {
+        Self {
+            hits: self.hits.saturating_sub(earlier.hits),
+            misses: self.misses.saturating_sub(earlier.misses),
+        }
+    }
+}
+
+impl ops::Add for CacheStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            hits: self.hits + rhs.hits,
+            misses: self.misses + rhs.misses,
+        }
+    }
+}
@@ -16,7 +16,7 @@ use zksync_types::{L1BatchNumber, MiniblockNumber, StorageKey, StorageValue, H25
 
 use self::metrics::{Method, ValuesUpdateStage, CACHE_METRICS, STORAGE_METRICS};
 use crate::{
-    cache::{lru_cache::LruCache, CacheValue},
+    cache::{lru_cache::LruCache, CacheStats, CacheValue},
     ReadStorage,
 };
 
@@ -112,6 +112,14 @@ impl ValuesCache {
         self.0.read().expect("values cache is poisoned").valid_for
     }
 
+    fn stats(&self) -> CacheStats {
+        self.0
+            .read()
+            .expect("values cache is poisoned")
+            .values
+            .stats()
+    }
+
     /// Gets the cached value for `key` provided that the cache currently holds values
     /// for `miniblock_number`.
     fn get(&self, miniblock_number: MiniblockNumber, key: &StorageKey) -> Option<StorageValue> {
@@ -339,6 +347,21 @@ impl PostgresStorageCaches {
                 .expect("values cache update task failed");
         }
     }
+
+    /// Returns total hit / miss counts across all caches. Counts are cumulative since the caches were created,
+    /// and are shared among all clones of the caches; thus, to attribute cache usage to a specific operation,
+    /// take the difference of stats before and after the operation (keeping in mind that concurrently running
+    /// operations will be attributed as well).
+    pub fn stats(&self) -> CacheStats {
+        let values_stats = self
+            .values
+            .as_ref()
+            .map_or_else(CacheStats::default, |values| values.cache.stats());
+        self.factory_deps.stats()
+            + self.initial_writes.stats()
+            + self.negative_initial_writes.stats()
+            + values_stats
+    }
 }
 
 /// An asynchronous task that updates the VM storage values cache.
@@ -593,6 +593,23 @@ pub struct ResultDebugCall {
     pub result: DebugCall,
 }
 
+/// Outcome of replaying the transaction submission checks, as returned by `debug_auditSubmission`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmissionAudit {
+    /// Miniblock the checks were replayed at.
+    pub block_number: U64,
+    /// Whether the transaction would be accepted.
+    pub accepted: bool,
+    /// Error the transaction submission would be rejected with.
+    pub rejection_reason: Option<String>,
+    /// Short code of the rejection reason (e.g., `nonce-is-too-low`).
+    pub rejection_code: Option<String>,
+    /// Gas used by the transaction execution in the sandbox. `None` if the transaction was rejected before execution
+    /// or if the execution failed.
+    pub gas_used: Option<U256>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum DebugCallType {
     Call,
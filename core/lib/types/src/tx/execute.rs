@@ -22,6 +22,18 @@ pub struct Execute {
     pub factory_deps: Option<Vec<Vec<u8>>>,
 }
 
+/// Parameters of the `create2Account` deployer method; the `create2` method has the same parameters except for
+/// the last one (the account abstraction version).
+const CREATE2_ACCOUNT_PARAMS: [ethabi::ParamType; 4] = [
+    ethabi::ParamType::FixedBytes(32),
+    ethabi::ParamType::FixedBytes(32),
+    ethabi::ParamType::Bytes,
+    ethabi::ParamType::Uint(8),
+];
+
+static CREATE2_SIGNATURE: Lazy<[u8; 4]> =
+    Lazy::new(|| ethabi::short_signature("create2", &CREATE2_ACCOUNT_PARAMS[..3]));
+
 impl EIP712TypedStructure for Execute {
     const TYPE_NAME: &'static str = "Transaction";
 
@@ -69,6 +81,50 @@ impl Execute {
         FUNCTION_SIGNATURE.iter().copied().chain(params).collect()
     }
 
+    /// Prepares calldata to invoke deployer contract.
+    /// This method encodes parameters for the `create2` method.
+    pub fn encode_deploy_params_create2(
+        salt: H256,
+        contract_hash: H256,
+        constructor_input: Vec<u8>,
+    ) -> Vec<u8> {
+        let params = ethabi::encode(&[
+            ethabi::Token::FixedBytes(salt.as_bytes().to_vec()),
+            ethabi::Token::FixedBytes(contract_hash.as_bytes().to_vec()),
+            ethabi::Token::Bytes(constructor_input),
+        ]);
+        CREATE2_SIGNATURE.iter().copied().chain(params).collect()
+    }
+
+    /// Decodes the calldata of a call to the `create2` or `create2Account` deployer method, returning the salt,
+    /// the bytecode hash and the constructor input. Returns `None` if the calldata is not such a call; the call target
+    /// is not checked.
+    pub fn decode_deploy_params_create2(&self) -> Option<(H256, H256, Vec<u8>)> {
+        static CREATE2_ACCOUNT_SIGNATURE: Lazy<[u8; 4]> =
+            Lazy::new(|| ethabi::short_signature("create2Account", &CREATE2_ACCOUNT_PARAMS));
+
+        if self.calldata.len() < 4 {
+            return None;
+        }
+        let (signature, params) = self.calldata.split_at(4);
+        let tokens = if signature == *CREATE2_SIGNATURE {
+            ethabi::decode(&CREATE2_ACCOUNT_PARAMS[..3], params).ok()?
+        } else if signature == *CREATE2_ACCOUNT_SIGNATURE {
+            ethabi::decode(&CREATE2_ACCOUNT_PARAMS, params).ok()?
+        } else {
+            return None;
+        };
+        let mut tokens = tokens.into_iter();
+        let salt = tokens.next()?.into_fixed_bytes()?;
+        let contract_hash = tokens.next()?.into_fixed_bytes()?;
+        let constructor_input = tokens.next()?.into_bytes()?;
+        Some((
+            H256::from_slice(&salt),
+            H256::from_slice(&contract_hash),
+            constructor_input,
+        ))
+    }
+
     /// Number of new factory dependencies in this transaction
     pub fn factory_deps_length(&self) -> usize {
         self.factory_deps
@@ -77,6 +77,27 @@ pub fn deployed_address_create(sender: Address, deploy_nonce: U256) -> Address {
     Address::from_slice(&keccak256(&bytes)[12..])
 }
 
+/// Pre-calculates the address of the to-be-deployed contract via CREATE2.
+pub fn deployed_address_create2(
+    sender: Address,
+    salt: H256,
+    bytecode_hash: H256,
+    constructor_input: &[u8],
+) -> Address {
+    let prefix_bytes = keccak256("zksyncCreate2".as_bytes());
+    let address_bytes = address_to_h256(&sender);
+    let input_hash = keccak256(constructor_input);
+
+    let mut bytes = [0u8; 160];
+    bytes[..32].copy_from_slice(&prefix_bytes);
+    bytes[32..64].copy_from_slice(address_bytes.as_bytes());
+    bytes[64..96].copy_from_slice(salt.as_bytes());
+    bytes[96..128].copy_from_slice(bytecode_hash.as_bytes());
+    bytes[128..].copy_from_slice(&input_hash);
+
+    Address::from_slice(&keccak256(&bytes)[12..])
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
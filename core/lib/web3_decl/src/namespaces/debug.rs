@@ -1,11 +1,11 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, SubmissionAudit, TracerConfig},
     debug_flat_call::DebugCallFlat,
     transaction_request::CallRequest,
 };
 
-use crate::types::H256;
+use crate::types::{Bytes, H256};
 
 #[cfg_attr(
     all(feature = "client", feature = "server"),
@@ -51,4 +51,10 @@ pub trait DebugNamespace {
         tx_hash: H256,
         options: Option<TracerConfig>,
     ) -> RpcResult<Option<DebugCall>>;
+    #[method(name = "auditSubmission")]
+    async fn audit_submission(
+        &self,
+        tx_bytes: Bytes,
+        block: Option<BlockId>,
+    ) -> RpcResult<SubmissionAudit>;
 }
@@ -6,7 +6,10 @@
 //!
 //! This module is intended to be blocking.
 
-use std::time::{Duration, Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
 use multivm::{
@@ -29,16 +32,52 @@ use zksync_types::{
     get_nonce_key,
     utils::{decompose_full_nonce, nonces_to_full_nonce, storage_key_for_eth_balance},
     AccountTreeId, L1BatchNumber, MiniblockNumber, Nonce, ProtocolVersionId, StorageKey,
-    Transaction, H256, U256,
+    StorageValue, Transaction, H256, U256,
 };
 use zksync_utils::{h256_to_u256, time::seconds_since_epoch, u256_to_h256};
 
 use super::{
     vm_metrics::{self, SandboxStage, SANDBOX_METRICS},
-    BlockArgs, TxExecutionArgs, TxSharedArgs, VmPermit,
+    BlockArgs, PrecedingTxsState, TxExecutionArgs, TxSharedArgs, VmPermit,
 };
 
-type BoxedVm<'a> = Box<VmInstance<StorageView<PostgresStorage<'a>>, HistoryDisabled>>;
+type BoxedVm<'a> = Box<VmInstance<StorageView<StorageWithOverrides<'a>>, HistoryDisabled>>;
+
+/// Postgres storage with state changes made by preceding transactions applied on top of it.
+#[derive(Debug)]
+struct StorageWithOverrides<'a> {
+    inner: PostgresStorage<'a>,
+    preceding_state: Option<Arc<PrecedingTxsState>>,
+}
+
+impl ReadStorage for StorageWithOverrides<'_> {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        let overridden_value = self
+            .preceding_state
+            .as_ref()
+            .and_then(|state| state.storage_value(key));
+        overridden_value.unwrap_or_else(|| self.inner.read_value(key))
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        self.inner.is_write_initial(key)
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        let overridden_dep = self
+            .preceding_state
+            .as_ref()
+            .and_then(|state| state.factory_dep(hash));
+        match overridden_dep {
+            Some(bytecode) => Some(bytecode.to_vec()),
+            None => self.inner.load_factory_dep(hash),
+        }
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        self.inner.get_enumeration_index(key)
+    }
+}
 
 #[derive(Debug)]
 struct Sandbox<'a> {
@@ -46,7 +85,7 @@ struct Sandbox<'a> {
     l1_batch_env: L1BatchEnv,
     execution_args: &'a TxExecutionArgs,
     l2_block_info_to_reset: Option<StoredL2BlockInfo>,
-    storage_view: StorageView<PostgresStorage<'a>>,
+    storage_view: StorageView<StorageWithOverrides<'a>>,
 }
 
 impl<'a> Sandbox<'a> {
@@ -89,6 +128,10 @@ impl<'a> Sandbox<'a> {
         .await
         .context("cannot create `PostgresStorage`")?
         .with_caches(shared_args.caches.clone());
+        let storage = StorageWithOverrides {
+            inner: storage,
+            preceding_state: execution_args.preceding_state.clone(),
+        };
 
         let storage_view = StorageView::new(storage);
         let (system_env, l1_batch_env) = Self::prepare_env(
@@ -176,6 +219,14 @@ impl<'a> Sandbox<'a> {
                 .set_value(nonce_key, u256_to_h256(enforced_full_nonce));
         }
 
+        // The operator balance is overridden before adding balance to the payer, so that the latter is preserved
+        // if the operator pays for the transaction.
+        if let Some(operator_balance) = self.execution_args.operator_balance {
+            let operator_balance_key = storage_key_for_eth_balance(&self.l1_batch_env.fee_account);
+            self.storage_view
+                .set_value(operator_balance_key, u256_to_h256(operator_balance));
+        }
+
         let payer = tx.payer();
         let balance_key = storage_key_for_eth_balance(&payer);
         let mut current_balance = h256_to_u256(self.storage_view.read_value(&balance_key));
@@ -259,7 +310,10 @@ impl<'a> Sandbox<'a> {
         mut self,
         tx: &Transaction,
         adjust_pubdata_price: bool,
-    ) -> (BoxedVm<'a>, StoragePtr<StorageView<PostgresStorage<'a>>>) {
+    ) -> (
+        BoxedVm<'a>,
+        StoragePtr<StorageView<StorageWithOverrides<'a>>>,
+    ) {
         self.setup_storage_view(tx);
         let protocol_version = self.system_env.version;
         if adjust_pubdata_price {
@@ -295,7 +349,7 @@ pub(super) fn apply_vm_in_sandbox<T>(
     tx: Transaction,
     block_args: BlockArgs,
     apply: impl FnOnce(
-        &mut VmInstance<StorageView<PostgresStorage<'_>>, HistoryDisabled>,
+        &mut VmInstance<StorageView<StorageWithOverrides<'_>>, HistoryDisabled>,
         Transaction,
     ) -> T,
 ) -> anyhow::Result<T> {
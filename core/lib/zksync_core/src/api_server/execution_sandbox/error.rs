@@ -26,6 +26,8 @@ pub(crate) enum SandboxExecutionError {
         that caused this error. Error description: {0}"
     )]
     UnexpectedVMBehavior(String),
+    #[error("Execution deadline exceeded")]
+    ExecutionTimedOut,
 }
 
 impl From<Halt> for SandboxExecutionError {
@@ -67,6 +69,7 @@ impl From<Halt> for SandboxExecutionError {
             Halt::FailedToPublishCompressedBytecodes => {
                 Self::UnexpectedVMBehavior("Failed to publish compressed bytecodes".to_string())
             }
+            Halt::ExecutionDeadlineExceeded => Self::ExecutionTimedOut,
         }
     }
 }
@@ -1,18 +1,22 @@
 //! Implementation of "executing" methods, e.g. `eth_call`.
 
+use std::{collections::HashMap, sync::Arc};
+
 use anyhow::Context as _;
 use multivm::{
-    interface::{TxExecutionMode, VmExecutionResultAndLogs, VmInterface},
-    tracers::StorageInvocations,
+    interface::{BytecodeCompressionError, TxExecutionMode, VmExecutionResultAndLogs, VmInterface},
+    tracers::{ExecutionDeadline, StorageInvocations},
     vm_latest::constants::ETH_CALL_GAS_LIMIT,
     MultiVMTracer,
 };
 use tracing::{span, Level};
 use zksync_dal::{ConnectionPool, Core};
+use zksync_system_constants::SYSTEM_CONTEXT_ADDRESS;
 use zksync_types::{
     fee::TransactionExecutionMetrics, l2::L2Tx, ExecuteTransactionCommon, Nonce,
-    PackedEthSignature, Transaction, U256,
+    PackedEthSignature, StorageKey, StorageLog, StorageValue, Transaction, H256, U256,
 };
+use zksync_utils::bytecode::hash_bytecode;
 
 #[cfg(test)]
 use super::testonly::MockTransactionExecutor;
@@ -23,8 +27,12 @@ pub(crate) struct TxExecutionArgs {
     pub execution_mode: TxExecutionMode,
     pub enforced_nonce: Option<Nonce>,
     pub added_balance: U256,
+    /// Balance of the operator (fee) account to execute with instead of the one in the storage.
+    pub operator_balance: Option<U256>,
     pub enforced_base_fee: Option<u64>,
     pub missed_storage_invocation_limit: usize,
+    /// State changes made by transactions preceding the main one, applied on top of the storage.
+    pub preceding_state: Option<Arc<PrecedingTxsState>>,
 }
 
 impl TxExecutionArgs {
@@ -33,8 +41,10 @@ impl TxExecutionArgs {
             execution_mode: TxExecutionMode::VerifyExecute,
             enforced_nonce: Some(tx.nonce()),
             added_balance: U256::zero(),
+            operator_balance: None,
             enforced_base_fee: Some(tx.common_data.fee.max_fee_per_gas.as_u64()),
             missed_storage_invocation_limit: usize::MAX,
+            preceding_state: None,
         }
     }
 
@@ -47,8 +57,10 @@ impl TxExecutionArgs {
             execution_mode: TxExecutionMode::EthCall,
             enforced_nonce: None,
             added_balance: U256::zero(),
+            operator_balance: None,
             enforced_base_fee: Some(enforced_base_fee),
             missed_storage_invocation_limit,
+            preceding_state: None,
         }
     }
 
@@ -71,8 +83,46 @@ impl TxExecutionArgs {
             missed_storage_invocation_limit,
             enforced_nonce: tx.nonce(),
             added_balance,
+            operator_balance: None,
             enforced_base_fee: Some(base_fee),
+            preceding_state: None,
+        }
+    }
+}
+
+/// State changes made by transactions executed before the main one (e.g., preceding transactions in a bundle).
+/// The state is captured once, so that preceding transactions don't need to be re-executed on each execution
+/// of the main transaction.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PrecedingTxsState {
+    storage_writes: HashMap<StorageKey, StorageValue>,
+    factory_deps: HashMap<H256, Vec<u8>>,
+}
+
+impl PrecedingTxsState {
+    /// Records changes made by `tx` according to its execution `output`. Writes to the system context
+    /// are skipped since it's initialized anew for each execution.
+    pub fn apply_tx(&mut self, tx: &Transaction, output: &VmExecutionResultAndLogs) {
+        let writes =
+            output.logs.storage_logs.iter().filter(|log| {
+                log.log_query.rw_flag && log.log_query.address != SYSTEM_CONTEXT_ADDRESS
+            });
+        for log in writes {
+            let log = StorageLog::from_log_query(log);
+            self.storage_writes.insert(log.key, log.value);
         }
+        for bytecode in tx.execute.factory_deps.iter().flatten() {
+            self.factory_deps
+                .insert(hash_bytecode(bytecode), bytecode.clone());
+        }
+    }
+
+    pub fn storage_value(&self, key: &StorageKey) -> Option<StorageValue> {
+        self.storage_writes.get(key).copied()
+    }
+
+    pub fn factory_dep(&self, hash: H256) -> Option<&[u8]> {
+        self.factory_deps.get(&hash).map(Vec::as_slice)
     }
 }
 
@@ -82,8 +132,8 @@ pub(crate) struct TransactionExecutionOutput {
     pub vm: VmExecutionResultAndLogs,
     /// Execution metrics.
     pub metrics: TransactionExecutionMetrics,
-    /// Were published bytecodes OK?
-    pub are_published_bytecodes_ok: bool,
+    /// Result of publishing compressed bytecodes. On failure, contains the cause (e.g., the offending bytecode hash).
+    pub published_bytecodes: Result<(), BytecodeCompressionError>,
 }
 
 /// Executor of transactions.
@@ -113,9 +163,17 @@ impl TransactionExecutor {
         block_args: BlockArgs,
         custom_tracers: Vec<ApiTracer>,
     ) -> anyhow::Result<TransactionExecutionOutput> {
+        let deadline = shared_args.execution_deadline();
         #[cfg(test)]
         if let Self::Mock(mock_executor) = self {
-            return mock_executor.execute_tx(&tx, &block_args);
+            return mock_executor.execute_tx(
+                &tx,
+                &shared_args,
+                &execution_args,
+                &block_args,
+                deadline,
+                &custom_tracers,
+            );
         }
 
         let total_factory_deps = tx
@@ -137,10 +195,13 @@ impl TransactionExecutor {
                 |vm, tx| {
                     let storage_invocation_tracer =
                         StorageInvocations::new(execution_args.missed_storage_invocation_limit);
+                    let deadline_tracer = deadline
+                        .map(|deadline| ExecutionDeadline::new(deadline).into_tracer_pointer());
                     let custom_tracers: Vec<_> = custom_tracers
                         .into_iter()
                         .map(|tracer| tracer.into_boxed())
                         .chain(vec![storage_invocation_tracer.into_tracer_pointer()])
+                        .chain(deadline_tracer)
                         .collect();
                     vm.inspect_transaction_with_bytecode_compression(
                         custom_tracers.into(),
@@ -160,7 +221,7 @@ impl TransactionExecutor {
         Ok(TransactionExecutionOutput {
             vm: execution_result,
             metrics,
-            are_published_bytecodes_ok: published_bytecodes.is_ok(),
+            published_bytecodes,
         })
     }
 
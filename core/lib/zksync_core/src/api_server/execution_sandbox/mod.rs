@@ -1,4 +1,7 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
 use tokio::runtime::Handle;
@@ -13,10 +16,10 @@ use zksync_utils::bytecode::{compress_bytecode, hash_bytecode};
 use self::vm_metrics::SandboxStage;
 pub(super) use self::{
     error::SandboxExecutionError,
-    execute::{TransactionExecutor, TxExecutionArgs},
+    execute::{PrecedingTxsState, TransactionExecutor, TxExecutionArgs},
     tracers::ApiTracer,
     validate::ValidationError,
-    vm_metrics::{SubmitTxStage, SANDBOX_METRICS},
+    vm_metrics::{EstimateGasPhase, SandboxCallType, SubmitTxStage, SANDBOX_METRICS},
 };
 use super::tx_sender::MultiVMBaseSystemContracts;
 
@@ -32,6 +35,27 @@ mod tracers;
 mod validate;
 mod vm_metrics;
 
+/// Error returned if a DB connection cannot be acquired within the configured timeout.
+#[derive(Debug, thiserror::Error)]
+#[error("DB pool exhausted: no connection after {0:?}")]
+pub(super) struct ConnectionAcquireTimeout(pub Duration);
+
+/// Acquires an API connection from `connection_pool`. If `timeout` is set, fails with [`ConnectionAcquireTimeout`]
+/// if the connection isn't acquired in time.
+pub(super) async fn acquire_connection(
+    connection_pool: &ConnectionPool<Core>,
+    timeout: Option<Duration>,
+) -> anyhow::Result<Connection<'_, Core>> {
+    let connection = connection_pool.connection_tagged("api");
+    let connection = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, connection)
+            .await
+            .map_err(|_| ConnectionAcquireTimeout(timeout))?,
+        None => connection.await,
+    };
+    connection.context("failed acquiring DB connection")
+}
+
 /// Permit to invoke VM code.
 ///
 /// Any publicly-facing method that invokes VM is expected to accept a reference to this structure,
@@ -123,6 +147,11 @@ impl VmConcurrencyLimiter {
         (this, barrier)
     }
 
+    /// Returns the number of VM executions that can currently start without waiting for a permit.
+    pub fn available_permits(&self) -> usize {
+        self.limiter.available_permits()
+    }
+
     /// Waits until there is a free slot in the concurrency limiter.
     /// Returns a permit that should be dropped when the VM execution is finished.
     pub async fn acquire(&self) -> Option<VmPermit> {
@@ -161,11 +190,15 @@ async fn get_pending_state(
     Ok((block_id, resolved_block_number))
 }
 
-/// Returns the number of the pubdata that the transaction will spend on factory deps.
+/// Returns the number of the pubdata that the transaction will spend on factory deps. Bytecodes already known
+/// to the network (i.e., marked in the known codes storage as of the pending block) are not published again,
+/// so they don't contribute to the returned value. If `compress_bytecodes` is not set, bytecodes are assumed
+/// to be published uncompressed.
 pub(super) async fn get_pubdata_for_factory_deps(
     _vm_permit: &VmPermit,
     connection_pool: &ConnectionPool<Core>,
     factory_deps: &[Vec<u8>],
+    compress_bytecodes: bool,
     storage_caches: PostgresStorageCaches,
 ) -> anyhow::Result<u32> {
     if factory_deps.is_empty() {
@@ -195,10 +228,9 @@ pub(super) async fn get_pubdata_for_factory_deps(
                 return 0;
             }
 
-            let length = if let Ok(compressed) = compress_bytecode(bytecode) {
-                compressed.len()
-            } else {
-                bytecode.len()
+            let length = match compress_bytecodes.then(|| compress_bytecode(bytecode)) {
+                Some(Ok(compressed)) => compressed.len(),
+                _ => bytecode.len(),
             };
             length as u32 + PUBLISH_BYTECODE_OVERHEAD
         });
@@ -217,9 +249,18 @@ pub(crate) struct TxSharedArgs {
     pub caches: PostgresStorageCaches,
     pub validation_computational_gas_limit: u32,
     pub chain_id: L2ChainId,
+    /// Wall-clock time limit for a single VM execution. If exceeded, execution is aborted
+    /// with [`Halt::ExecutionDeadlineExceeded`](multivm::interface::Halt::ExecutionDeadlineExceeded).
+    pub execution_timeout: Option<Duration>,
 }
 
 impl TxSharedArgs {
+    /// Returns the deadline for a VM execution starting now.
+    pub fn execution_deadline(&self) -> Option<Instant> {
+        self.execution_timeout
+            .map(|timeout| Instant::now() + timeout)
+    }
+
     #[cfg(test)]
     pub fn mock(base_system_contracts: MultiVMBaseSystemContracts) -> Self {
         Self {
@@ -229,6 +270,7 @@ impl TxSharedArgs {
             caches: PostgresStorageCaches::new(1, 1),
             validation_computational_gas_limit: u32::MAX,
             chain_id: L2ChainId::default(),
+            execution_timeout: None,
         }
     }
 }
@@ -351,10 +393,19 @@ impl BlockArgs {
         })
     }
 
+    pub fn block_id(&self) -> api::BlockId {
+        self.block_id
+    }
+
     pub fn resolved_block_number(&self) -> MiniblockNumber {
         self.resolved_block_number
     }
 
+    /// Returns the timestamp of the L1 batch containing the block, or `None` for the pending block.
+    pub fn l1_batch_timestamp_s(&self) -> Option<u64> {
+        self.l1_batch_timestamp_s
+    }
+
     pub fn resolves_to_latest_sealed_miniblock(&self) -> bool {
         matches!(
             self.block_id,
@@ -1,21 +1,46 @@
-use std::fmt;
+use std::{fmt, time::Instant};
 
-use multivm::interface::{ExecutionResult, VmExecutionResultAndLogs};
+use multivm::interface::{
+    BytecodeCompressionError, ExecutionResult, Halt, VmExecutionLogs, VmExecutionResultAndLogs,
+    VmExecutionStatistics,
+};
+use once_cell::sync::Lazy;
+use zksync_contracts::BaseSystemContracts;
 use zksync_types::{
-    fee::TransactionExecutionMetrics, l2::L2Tx, ExecuteTransactionCommon, Transaction,
+    fee::TransactionExecutionMetrics, l2::L2Tx, vm_trace::Call, ExecuteTransactionCommon,
+    StorageLogQuery, Transaction, H256,
 };
+use zksync_utils::bytecode::{compress_bytecode, CompressedBytecodeInfo};
 
 use super::{
     execute::{TransactionExecutionOutput, TransactionExecutor},
     validate::ValidationError,
-    BlockArgs,
+    ApiTracer, BlockArgs, TxExecutionArgs, TxSharedArgs,
 };
 
+/// Hash of the latest playground bootloader, used to distinguish execution with the `eth_call` contracts.
+static PLAYGROUND_BOOTLOADER_HASH: Lazy<H256> =
+    Lazy::new(|| BaseSystemContracts::playground_post_1_4_2().bootloader.hash);
+
 type TxResponseFn = dyn Fn(&Transaction, &BlockArgs) -> ExecutionResult + Send + Sync;
+type TxWithArgsResponseFn = dyn Fn(&Transaction, &TxExecutionArgs) -> ExecutionResult + Send + Sync;
+type ValidationResponseFn = dyn Fn(&L2Tx, &BlockArgs) -> Result<(), ValidationError> + Send + Sync;
+type PublishedBytecodesResponseFn = dyn Fn(&Transaction) -> bool + Send + Sync;
+type TxMetricsResponseFn = dyn Fn(&Transaction) -> TransactionExecutionMetrics + Send + Sync;
+type StorageLogsResponseFn = dyn Fn(&Transaction) -> Vec<StorageLogQuery> + Send + Sync;
 
 pub(crate) struct MockTransactionExecutor {
     call_responses: Box<TxResponseFn>,
     tx_responses: Box<TxResponseFn>,
+    /// If not set, transactions executed with the playground contracts use `tx_responses`.
+    playground_tx_responses: Option<Box<TxResponseFn>>,
+    /// If set, takes precedence over `tx_responses` for transactions executed in the sandbox (i.e., not validated).
+    tx_with_args_responses: Option<Box<TxWithArgsResponseFn>>,
+    /// If not set, validation succeeds iff the transaction response is successful.
+    validation_responses: Option<Box<ValidationResponseFn>>,
+    published_bytecodes_responses: Box<PublishedBytecodesResponseFn>,
+    tx_metrics_responses: Box<TxMetricsResponseFn>,
+    storage_logs_responses: Box<StorageLogsResponseFn>,
 }
 
 impl fmt::Debug for MockTransactionExecutor {
@@ -38,6 +63,12 @@ impl Default for MockTransactionExecutor {
             tx_responses: Box::new(|tx, _| {
                 panic!("Unexpect transaction call: {tx:?}");
             }),
+            playground_tx_responses: None,
+            tx_with_args_responses: None,
+            validation_responses: None,
+            published_bytecodes_responses: Box::new(|_| true),
+            tx_metrics_responses: Box::new(|_| TransactionExecutionMetrics::default()),
+            storage_logs_responses: Box::new(|_| vec![]),
         }
     }
 }
@@ -57,42 +88,192 @@ impl MockTransactionExecutor {
         self.tx_responses = Box::new(responses);
     }
 
-    pub fn validate_tx(&self, tx: L2Tx, block_args: &BlockArgs) -> Result<(), ValidationError> {
-        let result = (self.tx_responses)(&tx.into(), block_args);
-        match result {
-            ExecutionResult::Success { .. } => Ok(()),
-            other => Err(ValidationError::Internal(anyhow::anyhow!(
-                "transaction validation failed: {other:?}"
-            ))),
+    /// Sets responses for transactions executed with the playground (`eth_call`) contracts rather than
+    /// the gas estimation ones.
+    pub fn set_playground_tx_responses<F>(&mut self, responses: F)
+    where
+        F: Fn(&Transaction, &BlockArgs) -> ExecutionResult + 'static + Send + Sync,
+    {
+        self.playground_tx_responses = Some(Box::new(responses));
+    }
+
+    /// Sets responses for transactions executed in the sandbox depending on the execution args, e.g. to emulate
+    /// transactions sensitive to state overrides.
+    pub fn set_tx_with_args_responses<F>(&mut self, responses: F)
+    where
+        F: Fn(&Transaction, &TxExecutionArgs) -> ExecutionResult + 'static + Send + Sync,
+    {
+        self.tx_with_args_responses = Some(Box::new(responses));
+    }
+
+    pub fn set_validation_responses<F>(&mut self, responses: F)
+    where
+        F: Fn(&L2Tx, &BlockArgs) -> Result<(), ValidationError> + 'static + Send + Sync,
+    {
+        self.validation_responses = Some(Box::new(responses));
+    }
+
+    /// Sets whether compressed bytecodes of a transaction can be published. By default, this is always the case.
+    /// If publishing fails, the first factory dependency of the transaction is reported as the unpublished bytecode.
+    pub fn set_published_bytecodes_responses<F>(&mut self, responses: F)
+    where
+        F: Fn(&Transaction) -> bool + 'static + Send + Sync,
+    {
+        self.published_bytecodes_responses = Box::new(responses);
+    }
+
+    /// Sets execution metrics returned for transactions. By default, metrics are [default](Default).
+    pub fn set_tx_metrics_responses<F>(&mut self, responses: F)
+    where
+        F: Fn(&Transaction) -> TransactionExecutionMetrics + 'static + Send + Sync,
+    {
+        self.tx_metrics_responses = Box::new(responses);
+    }
+
+    /// Sets storage logs returned for executed transactions and calls. By default, no logs are returned.
+    pub fn set_storage_logs_responses<F>(&mut self, responses: F)
+    where
+        F: Fn(&Transaction) -> Vec<StorageLogQuery> + 'static + Send + Sync,
+    {
+        self.storage_logs_responses = Box::new(responses);
+    }
+
+    pub fn execute_and_validate_tx(
+        &self,
+        tx: &L2Tx,
+        block_args: &BlockArgs,
+        deadline: Option<Instant>,
+    ) -> (
+        TransactionExecutionOutput,
+        Option<Result<(), ValidationError>>,
+    ) {
+        let transaction = tx.clone().into();
+        let result = (self.tx_responses)(&transaction, block_args);
+        let result = Self::apply_deadline(result, deadline);
+        let output = self.execution_output(&transaction, result);
+        if output.published_bytecodes.is_err() {
+            return (output, None);
         }
+
+        let validation_result = if let Some(validation_responses) = &self.validation_responses {
+            validation_responses(tx, block_args)
+        } else {
+            match &output.vm.result {
+                ExecutionResult::Success { .. } => Ok(()),
+                other => Err(ValidationError::Internal(anyhow::anyhow!(
+                    "transaction validation failed: {other:?}"
+                ))),
+            }
+        };
+        (output, Some(validation_result))
     }
 
     pub fn execute_tx(
         &self,
         tx: &Transaction,
+        shared_args: &TxSharedArgs,
+        execution_args: &TxExecutionArgs,
         block_args: &BlockArgs,
+        deadline: Option<Instant>,
+        custom_tracers: &[ApiTracer],
     ) -> anyhow::Result<TransactionExecutionOutput> {
-        let result = self.get_execution_result(tx, block_args);
-        let output = TransactionExecutionOutput {
+        let result = self.get_execution_result(tx, shared_args, execution_args, block_args);
+        let result = Self::apply_deadline(result, deadline);
+        Self::emulate_tracers(tx, custom_tracers);
+        Ok(self.execution_output(tx, result))
+    }
+
+    /// Emulates the call tracer by reporting a single call from the transaction initiator to its target.
+    fn emulate_tracers(tx: &Transaction, custom_tracers: &[ApiTracer]) {
+        for tracer in custom_tracers {
+            match tracer {
+                ApiTracer::CallTracer(calls) => {
+                    let call = Call {
+                        from: tx.initiator_account(),
+                        to: tx.execute.contract_address,
+                        gas: tx.gas_limit().as_u32(),
+                        ..Call::default()
+                    };
+                    calls.set(vec![call]).ok();
+                }
+            }
+        }
+    }
+
+    /// Emulates the execution deadline tracer: if the (potentially slow) response took longer than allowed,
+    /// the response is replaced with a halt.
+    fn apply_deadline(result: ExecutionResult, deadline: Option<Instant>) -> ExecutionResult {
+        match deadline {
+            Some(deadline) if Instant::now() >= deadline => ExecutionResult::Halt {
+                reason: Halt::ExecutionDeadlineExceeded,
+            },
+            _ => result,
+        }
+    }
+
+    fn execution_output(
+        &self,
+        tx: &Transaction,
+        result: ExecutionResult,
+    ) -> TransactionExecutionOutput {
+        let metrics = (self.tx_metrics_responses)(tx);
+        // Keep VM statistics consistent with the returned metrics, which are derived from them in the real executor.
+        let statistics = VmExecutionStatistics {
+            gas_used: metrics.gas_used as u32,
+            gas_remaining: metrics.gas_remaining,
+            ..VmExecutionStatistics::default()
+        };
+        TransactionExecutionOutput {
             vm: VmExecutionResultAndLogs {
                 result,
-                logs: Default::default(),
-                statistics: Default::default(),
+                logs: VmExecutionLogs {
+                    storage_logs: (self.storage_logs_responses)(tx),
+                    ..VmExecutionLogs::default()
+                },
+                statistics,
                 refunds: Default::default(),
             },
-            metrics: TransactionExecutionMetrics::default(),
-            are_published_bytecodes_ok: true,
-        };
-        Ok(output)
+            metrics,
+            published_bytecodes: self.published_bytecodes(tx),
+        }
+    }
+
+    fn published_bytecodes(&self, tx: &Transaction) -> Result<(), BytecodeCompressionError> {
+        if (self.published_bytecodes_responses)(tx) {
+            return Ok(());
+        }
+        let first_factory_dep = tx.execute.factory_deps.as_deref().and_then(<[_]>::first);
+        Err(match first_factory_dep {
+            Some(bytecode) => BytecodeCompressionError::not_published(&CompressedBytecodeInfo {
+                original: bytecode.clone(),
+                compressed: compress_bytecode(bytecode).unwrap_or_default(),
+            }),
+            None => BytecodeCompressionError::BytecodeCompressionFailed,
+        })
     }
 
-    fn get_execution_result(&self, tx: &Transaction, block_args: &BlockArgs) -> ExecutionResult {
+    fn get_execution_result(
+        &self,
+        tx: &Transaction,
+        shared_args: &TxSharedArgs,
+        execution_args: &TxExecutionArgs,
+        block_args: &BlockArgs,
+    ) -> ExecutionResult {
         if let ExecuteTransactionCommon::L2(data) = &tx.common_data {
             if data.input.is_none() {
                 return (self.call_responses)(tx, block_args);
             }
         }
-        (self.tx_responses)(tx, block_args)
+        if let Some(responses) = &self.tx_with_args_responses {
+            return responses(tx, execution_args);
+        }
+        let uses_playground_contracts =
+            shared_args.base_system_contracts.post_1_4_2.bootloader.hash
+                == *PLAYGROUND_BOOTLOADER_HASH;
+        match &self.playground_tx_responses {
+            Some(responses) if uses_playground_contracts => responses(tx, block_args),
+            _ => (self.tx_responses)(tx, block_args),
+        }
     }
 }
 
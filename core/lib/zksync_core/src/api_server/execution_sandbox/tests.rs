@@ -196,3 +196,16 @@ async fn test_instantiating_vm(pool: ConnectionPool<Core>, block_args: BlockArgs
     .expect("VM instantiation panicked")
     .expect("VM instantiation errored");
 }
+
+#[tokio::test]
+async fn loading_validation_params_with_exhausted_pool() {
+    let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let _held_connection = pool.connection().await.unwrap();
+
+    let tx = create_l2_transaction(10, 100);
+    let err =
+        validate::load_validation_params(&pool, &tx, u32::MAX, Some(Duration::from_millis(50)))
+            .await
+            .unwrap_err();
+    assert!(err.is::<ConnectionAcquireTimeout>(), "{err:#}");
+}
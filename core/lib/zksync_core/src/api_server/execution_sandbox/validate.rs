@@ -1,11 +1,11 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
 
 use anyhow::Context as _;
 use multivm::{
-    interface::{ExecutionResult, VmExecutionMode, VmInterface},
+    interface::{ExecutionResult, VmInterface},
     tracers::{
         validator::{self, ValidationTracer, ValidationTracerParams},
-        StorageInvocations,
+        ExecutionDeadline, StorageInvocations,
     },
     vm_latest::HistoryDisabled,
     MultiVMTracer,
@@ -14,9 +14,9 @@ use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
 use zksync_types::{l2::L2Tx, Transaction, TRUSTED_ADDRESS_SLOTS, TRUSTED_TOKEN_SLOTS};
 
 use super::{
-    apply,
-    execute::TransactionExecutor,
-    vm_metrics::{SandboxStage, EXECUTION_METRICS, SANDBOX_METRICS},
+    acquire_connection, apply,
+    execute::{TransactionExecutionOutput, TransactionExecutor},
+    vm_metrics::{self, SandboxStage, SubmitTxStage, EXECUTION_METRICS, SANDBOX_METRICS},
     BlockArgs, TxExecutionArgs, TxSharedArgs, VmPermit,
 };
 
@@ -31,7 +31,11 @@ pub(crate) enum ValidationError {
 }
 
 impl TransactionExecutor {
-    pub(crate) async fn validate_tx_in_sandbox(
+    /// Executes the transaction in the sandbox and validates it in a single VM run. The validation result is
+    /// discarded (i.e., `None` is returned) if compressed bytecodes of the transaction cannot be published,
+    /// since such a transaction will be rejected anyway.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn execute_and_validate_tx_in_sandbox(
         &self,
         connection_pool: ConnectionPool<Core>,
         vm_permit: VmPermit,
@@ -39,80 +43,130 @@ impl TransactionExecutor {
         shared_args: TxSharedArgs,
         block_args: BlockArgs,
         computational_gas_limit: u32,
-    ) -> Result<(), ValidationError> {
+        connection_acquire_timeout: Option<Duration>,
+    ) -> anyhow::Result<(
+        TransactionExecutionOutput,
+        Option<Result<(), ValidationError>>,
+    )> {
+        let deadline = shared_args.execution_deadline();
         #[cfg(test)]
         if let Self::Mock(mock) = self {
-            return mock.validate_tx(tx, &block_args);
+            return Ok(mock.execute_and_validate_tx(&tx, &block_args, deadline));
         }
 
-        let stage_latency = SANDBOX_METRICS.sandbox[&SandboxStage::ValidateInSandbox].start();
-        let mut connection = connection_pool
-            .connection_tagged("api")
-            .await
-            .context("failed acquiring DB connection")?;
-        let validation_params =
-            get_validation_params(&mut connection, &tx, computational_gas_limit)
-                .await
-                .context("failed getting validation params")?;
-        drop(connection);
+        let validation_params = load_validation_params(
+            &connection_pool,
+            &tx,
+            computational_gas_limit,
+            connection_acquire_timeout,
+        )
+        .await?;
 
+        let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::DryRun].start();
+        let sandbox_latency = SANDBOX_METRICS.sandbox[&SandboxStage::ValidateInSandbox].start();
         let execution_args = TxExecutionArgs::for_validation(&tx);
         let tx: Transaction = tx.into();
-
-        let validation_result = tokio::task::spawn_blocking(move || {
-            let span = tracing::debug_span!("validate_in_sandbox").entered();
-            let result = apply::apply_vm_in_sandbox(
-                vm_permit,
-                shared_args,
-                true,
-                &execution_args,
-                &connection_pool,
-                tx,
-                block_args,
-                |vm, tx| {
-                    let stage_latency = SANDBOX_METRICS.sandbox[&SandboxStage::Validation].start();
-                    let span = tracing::debug_span!("validation").entered();
-                    vm.push_transaction(tx);
-
-                    let (tracer, validation_result) =
-                        ValidationTracer::<HistoryDisabled>::new(validation_params);
-
-                    let result = vm.inspect(
-                        vec![
+        let total_factory_deps = tx
+            .execute
+            .factory_deps
+            .as_ref()
+            .map_or(0, |deps| deps.len() as u16);
+
+        let (published_bytecodes, execution_result, validation_result) =
+            tokio::task::spawn_blocking(move || {
+                let span = tracing::debug_span!("validate_in_sandbox").entered();
+                let result = apply::apply_vm_in_sandbox(
+                    vm_permit,
+                    shared_args,
+                    true,
+                    &execution_args,
+                    &connection_pool,
+                    tx,
+                    block_args,
+                    |vm, tx| {
+                        let stage_latency =
+                            SANDBOX_METRICS.sandbox[&SandboxStage::Validation].start();
+                        let span = tracing::debug_span!("validation").entered();
+
+                        let (tracer, violated_rule, validation_ended) =
+                            ValidationTracer::<HistoryDisabled>::new_for_execution(
+                                validation_params,
+                            );
+                        let deadline_tracer = deadline
+                            .map(|deadline| ExecutionDeadline::new(deadline).into_tracer_pointer());
+                        let tracers: Vec<_> = [
                             tracer.into_tracer_pointer(),
                             StorageInvocations::new(execution_args.missed_storage_invocation_limit)
                                 .into_tracer_pointer(),
                         ]
-                        .into(),
-                        VmExecutionMode::OneTx,
-                    );
-
-                    let result = match (result.result, validation_result.get()) {
-                        (_, Some(err)) => {
-                            Err(validator::ValidationError::ViolatedRule(err.clone()))
-                        }
-                        (ExecutionResult::Halt { reason }, _) => {
-                            Err(validator::ValidationError::FailedTx(reason))
-                        }
-                        (_, None) => Ok(()),
-                    };
-
-                    stage_latency.observe();
-                    span.exit();
-                    result
-                },
-            );
-            span.exit();
-            result
-        })
-        .await
-        .context("transaction validation panicked")??;
-
-        stage_latency.observe();
-        validation_result.map_err(ValidationError::Vm)
+                        .into_iter()
+                        .chain(deadline_tracer)
+                        .collect();
+
+                        let (published_bytecodes, result) = vm
+                            .inspect_transaction_with_bytecode_compression(
+                                tracers.into(),
+                                tx,
+                                true,
+                            );
+
+                        // A halt only fails validation if it happened before the validation step has ended;
+                        // otherwise, the transaction is valid, but its execution has failed.
+                        let validation_result = match (&result.result, violated_rule.get()) {
+                            (_, Some(err)) => {
+                                Err(validator::ValidationError::ViolatedRule(err.clone()))
+                            }
+                            (ExecutionResult::Halt { reason }, None)
+                                if validation_ended.get().is_none() =>
+                            {
+                                Err(validator::ValidationError::FailedTx(reason.clone()))
+                            }
+                            (_, None) => Ok(()),
+                        };
+
+                        stage_latency.observe();
+                        span.exit();
+                        (published_bytecodes, result, validation_result)
+                    },
+                );
+                span.exit();
+                result
+            })
+            .await
+            .context("transaction validation panicked")??;
+        sandbox_latency.observe();
+        // The single VM run both executes and validates the transaction, so it's reported for both stages.
+        let stage_latency = stage_latency.observe();
+        SANDBOX_METRICS.submit_tx[&SubmitTxStage::VerifyExecute].observe(stage_latency);
+
+        let metrics =
+            vm_metrics::collect_tx_execution_metrics(total_factory_deps, &execution_result);
+        let validation_result = published_bytecodes
+            .is_ok()
+            .then(|| validation_result.map_err(ValidationError::Vm));
+        let execution_output = TransactionExecutionOutput {
+            vm: execution_result,
+            metrics,
+            published_bytecodes,
+        };
+        Ok((execution_output, validation_result))
     }
 }
 
+/// Loads validation params for `tx`. Acquiring a DB connection is limited by `connection_acquire_timeout`,
+/// so that submission doesn't hang if the pool is exhausted.
+pub(super) async fn load_validation_params(
+    connection_pool: &ConnectionPool<Core>,
+    tx: &L2Tx,
+    computational_gas_limit: u32,
+    connection_acquire_timeout: Option<Duration>,
+) -> anyhow::Result<ValidationTracerParams> {
+    let mut connection = acquire_connection(connection_pool, connection_acquire_timeout).await?;
+    get_validation_params(&mut connection, tx, computational_gas_limit)
+        .await
+        .context("failed getting validation params")
+}
+
 /// Some slots can be marked as "trusted". That is needed for slots which can not be
 /// trusted to change between validation and execution in general case, but
 /// sometimes we can safely rely on them to not change often.
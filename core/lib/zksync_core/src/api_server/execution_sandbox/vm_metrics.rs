@@ -1,7 +1,10 @@
 use std::time::Duration;
 
 use multivm::interface::{VmExecutionResultAndLogs, VmMemoryMetrics};
-use vise::{Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
+use vise::{
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LabeledFamily,
+    Metrics,
+};
 use zksync_state::StorageViewMetrics;
 use zksync_types::{
     event::{extract_long_l2_to_l1_messages, extract_published_bytecodes},
@@ -86,6 +89,8 @@ pub(super) enum SandboxStage {
 pub(in crate::api_server) enum SubmitTxStage {
     #[metrics(name = "1_validate")]
     Validate,
+    /// Transaction execution in the sandbox. Since execution and validation are performed in a single VM run,
+    /// it has the same latency as [`Self::VerifyExecute`].
     #[metrics(name = "2_dry_run")]
     DryRun,
     #[metrics(name = "3_verify_execute")]
@@ -96,6 +101,26 @@ pub(in crate::api_server) enum SubmitTxStage {
     DbInsert,
 }
 
+/// Phase of gas estimation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "phase", rename_all = "snake_case")]
+pub(in crate::api_server) enum EstimateGasPhase {
+    /// Everything before the binary search: fetching the protocol version and fee input, balance checks,
+    /// computing gas for publishing bytecodes etc.
+    Preparation,
+    /// Binary search for the gas limit.
+    Search,
+}
+
+/// Type of a `TxSender` operation used to attribute storage cache usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "call_type", rename_all = "snake_case")]
+pub(in crate::api_server) enum SandboxCallType {
+    EstimateGas,
+    EthCall,
+    SubmitTx,
+}
+
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "api_web3")]
 pub(in crate::api_server) struct SandboxMetrics {
@@ -107,6 +132,21 @@ pub(in crate::api_server) struct SandboxMetrics {
     pub submit_tx: Family<SubmitTxStage, Histogram<Duration>>,
     #[metrics(buckets = Buckets::linear(0.0..=30.0, 3.0))]
     pub estimate_gas_binary_search_iterations: Histogram<usize>,
+    /// Latency of a specific phase of gas estimation.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub estimate_gas_phase_latency: Family<EstimateGasPhase, Histogram<Duration>>,
+    /// Number of VM storage cache hits attributed to a certain call type. Since caches are shared,
+    /// this is approximate if calls are executed concurrently.
+    pub storage_cache_hits: Family<SandboxCallType, Counter>,
+    /// Number of VM storage cache misses attributed to a certain call type. Since caches are shared,
+    /// this is approximate if calls are executed concurrently.
+    pub storage_cache_misses: Family<SandboxCallType, Counter>,
+    /// Number of failed gas estimations for a specific cause (the error code of the returned `SubmitTxError`).
+    #[metrics(labels = ["cause"])]
+    pub estimate_gas_failures: LabeledFamily<&'static str, Counter>,
+    /// Number of gas estimations performed for a specific protocol version (the version of the pending block).
+    #[metrics(labels = ["protocol_version"])]
+    pub estimate_gas_protocol_versions: LabeledFamily<u16, Counter>,
 }
 
 #[vise::register]
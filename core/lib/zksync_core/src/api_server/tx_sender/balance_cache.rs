@@ -0,0 +1,63 @@
+//! Short-lived cache for the fee account balance.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use zksync_types::{Address, MiniblockNumber, U256};
+
+#[derive(Debug, Clone, Copy)]
+struct CachedBalance {
+    balance: U256,
+    /// Latest miniblock observed when the balance was fetched.
+    miniblock_number: MiniblockNumber,
+    fetched_at: Instant,
+}
+
+/// Cache for the balance of the fee account, which is read on the hot path of transaction submission and gas estimation.
+///
+/// A cached balance is invalidated after a TTL, or once a miniblock newer than the one observed when fetching
+/// the balance is passed to [`Self::get()`], whichever comes first.
+#[derive(Debug)]
+pub(super) struct FeeAccountBalanceCache {
+    fee_account: Address,
+    ttl: Duration,
+    entry: Mutex<Option<CachedBalance>>,
+}
+
+impl FeeAccountBalanceCache {
+    pub fn new(fee_account: Address, ttl: Duration) -> Self {
+        Self {
+            fee_account,
+            ttl,
+            entry: Mutex::new(None),
+        }
+    }
+
+    /// Returns the cached balance for `address`, or `None` if `address` is not the fee account or the cached value
+    /// is absent or outdated, i.e. was fetched before `latest_miniblock_number` was observed.
+    pub fn get(&self, address: Address, latest_miniblock_number: MiniblockNumber) -> Option<U256> {
+        if address != self.fee_account {
+            return None;
+        }
+        let entry = (*self.entry.lock().expect("balance cache is poisoned"))?;
+        let is_fresh = entry.fetched_at.elapsed() < self.ttl
+            && entry.miniblock_number >= latest_miniblock_number;
+        is_fresh.then_some(entry.balance)
+    }
+
+    /// Caches the balance for `address` if it's the fee account. `miniblock_number` should be the latest miniblock
+    /// observed *before* fetching the balance, so that a miniblock observed concurrently with the fetch invalidates
+    /// the cached value.
+    pub fn insert(&self, address: Address, balance: U256, miniblock_number: MiniblockNumber) {
+        if address != self.fee_account {
+            return;
+        }
+        *self.entry.lock().expect("balance cache is poisoned") = Some(CachedBalance {
+            balance,
+            miniblock_number,
+            fetched_at: Instant::now(),
+        });
+    }
+}
@@ -0,0 +1,149 @@
+//! Capturing sandbox inputs for offline debugging.
+
+use std::collections::{hash_map, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use zksync_types::{
+    api, l2::L2Tx, AccountTreeId, Address, L2ChainId, MiniblockNumber, StorageKey, StorageLog,
+    StorageLogQuery, StorageLogQueryType,
+};
+use zksync_utils::u256_to_h256;
+
+use super::{result::ApiCallResult, SubmitTxError, TxSender};
+use crate::api_server::execution_sandbox::BlockArgs;
+
+/// Inputs of an `eth_call`-like VM run in the API sandbox together with its outcome. Contains enough information
+/// to replay the run deterministically, and can be serialized to JSON (e.g., to be attached to a bug report).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionCapture {
+    /// Executed transaction.
+    pub tx: L2Tx,
+    /// Block ID the transaction was executed against, as requested by the caller.
+    pub block_id: api::BlockId,
+    /// Miniblock that `block_id` was resolved to.
+    pub resolved_block_number: MiniblockNumber,
+    /// Timestamp of the L1 batch used by the VM; `None` for the pending block.
+    pub l1_batch_timestamp_s: Option<u64>,
+    pub fee_input: CapturedFeeInput,
+    pub operator_account: Address,
+    pub chain_id: L2ChainId,
+    /// Storage slots touched by the VM, with values they had before the execution.
+    pub storage_slots: Vec<StorageLog>,
+    /// Storage writes attempted by the VM, with the last value written to each slot. Includes writes that were
+    /// rolled back, e.g. because the call has reverted, which shows how far the execution progressed before the revert.
+    #[serde(default)]
+    pub attempted_writes: Vec<StorageLog>,
+    /// Call output or the error message if the execution has failed.
+    pub output: Result<Vec<u8>, String>,
+}
+
+/// Fee input provided to the VM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedFeeInput {
+    pub l1_gas_price: u64,
+    pub fair_l2_gas_price: u64,
+    pub fair_pubdata_price: u64,
+}
+
+impl ExecutionCapture {
+    /// Collects initial values of all storage slots touched by the VM. For each slot, the first log query
+    /// touching it holds the value the slot had before the execution.
+    fn storage_slots(storage_logs: &[StorageLogQuery]) -> Vec<StorageLog> {
+        let mut seen_keys = HashSet::new();
+        storage_logs
+            .iter()
+            .filter_map(|log| {
+                let log_query = &log.log_query;
+                let key = StorageKey::new(
+                    AccountTreeId::new(log_query.address),
+                    u256_to_h256(log_query.key),
+                );
+                seen_keys
+                    .insert(key)
+                    .then(|| StorageLog::new_read_log(key, u256_to_h256(log_query.read_value)))
+            })
+            .collect()
+    }
+
+    /// Collects writes attempted by the VM in the order of first occurrence. Reverted writes are still present
+    /// in the storage logs, followed by rollback queries mirroring them; the latter are skipped.
+    fn attempted_writes(storage_logs: &[StorageLogQuery]) -> Vec<StorageLog> {
+        let mut writes = vec![];
+        let mut write_indices = HashMap::new();
+        for log in storage_logs {
+            let log_query = &log.log_query;
+            if matches!(log.log_type, StorageLogQueryType::Read) || log_query.rollback {
+                continue;
+            }
+            let key = StorageKey::new(
+                AccountTreeId::new(log_query.address),
+                u256_to_h256(log_query.key),
+            );
+            let write = StorageLog::new_write_log(key, u256_to_h256(log_query.written_value));
+            match write_indices.entry(key) {
+                hash_map::Entry::Occupied(entry) => writes[*entry.get()] = write,
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(writes.len());
+                    writes.push(write);
+                }
+            }
+        }
+        writes
+    }
+}
+
+impl TxSender {
+    /// Executes `tx` in the same way as `eth_call` and captures the sandbox inputs, without enforcing
+    /// the `eth_call` target allowlist.
+    pub(crate) async fn capture_execution(
+        &self,
+        tx: L2Tx,
+        block_args: BlockArgs,
+    ) -> Result<ExecutionCapture, SubmitTxError> {
+        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
+        let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
+
+        let shared_args = self.shared_args().await;
+        let fee_input = CapturedFeeInput {
+            l1_gas_price: shared_args.fee_input.l1_gas_price(),
+            fair_l2_gas_price: shared_args.fee_input.fair_l2_gas_price(),
+            fair_pubdata_price: shared_args.fee_input.fair_pubdata_price(),
+        };
+        let operator_account = *shared_args.operator_account.address();
+        let chain_id = shared_args.chain_id;
+
+        let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
+        let execution_result = self
+            .0
+            .executor
+            .execute_tx_eth_call(
+                vm_permit,
+                shared_args,
+                self.0.replica_connection_pool.clone(),
+                tx.clone(),
+                block_args,
+                vm_execution_cache_misses_limit,
+                vec![],
+            )
+            .await?;
+        let storage_slots = ExecutionCapture::storage_slots(&execution_result.logs.storage_logs);
+        let attempted_writes =
+            ExecutionCapture::attempted_writes(&execution_result.logs.storage_logs);
+        let output = execution_result
+            .into_api_call_result()
+            .map_err(|err| err.to_string());
+
+        Ok(ExecutionCapture {
+            tx,
+            block_id: block_args.block_id(),
+            resolved_block_number: block_args.resolved_block_number(),
+            l1_batch_timestamp_s: block_args.l1_batch_timestamp_s(),
+            fee_input,
+            operator_account,
+            chain_id,
+            storage_slots,
+            attempted_writes,
+            output,
+        })
+    }
+}
@@ -0,0 +1,67 @@
+//! Short-lived cache for the gas price returned by `eth_gasPrice`.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use zksync_types::{fee_model::BatchFeeInput, MiniblockNumber};
+
+#[derive(Debug, Clone, Copy)]
+struct CachedGasPrice {
+    base_fee: u64,
+    fee_input: BatchFeeInput,
+    /// Latest miniblock observed when the pending protocol version was fetched.
+    miniblock_number: MiniblockNumber,
+    fetched_at: Instant,
+}
+
+/// Cache for the base fee returned by [`TxSender::gas_price()`](super::TxSender::gas_price()). The base fee is derived
+/// from the batch fee input and the pending protocol version; the latter requires a DB read, which is skipped on cache hits.
+///
+/// A cached base fee is only used if the fee input is unchanged. It is invalidated after a TTL, or once a miniblock newer
+/// than the one it was fetched at is observed, whichever comes first.
+#[derive(Debug)]
+pub(super) struct GasPriceCache {
+    ttl: Duration,
+    entry: Mutex<Option<CachedGasPrice>>,
+}
+
+impl GasPriceCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entry: Mutex::new(None),
+        }
+    }
+
+    /// Returns the cached base fee, or `None` if it's absent, was computed for another `fee_input`
+    /// or is outdated compared to `latest_miniblock_number`.
+    pub fn get(
+        &self,
+        fee_input: BatchFeeInput,
+        latest_miniblock_number: MiniblockNumber,
+    ) -> Option<u64> {
+        let entry = (*self.entry.lock().expect("gas price cache is poisoned"))?;
+        let is_fresh = entry.fee_input == fee_input
+            && entry.fetched_at.elapsed() < self.ttl
+            && entry.miniblock_number >= latest_miniblock_number;
+        is_fresh.then_some(entry.base_fee)
+    }
+
+    /// Caches the base fee. `miniblock_number` should be the latest observed miniblock recorded *before* fetching
+    /// the pending protocol version, so that a miniblock observed concurrently with the fetch invalidates the cached value.
+    pub fn insert(
+        &self,
+        base_fee: u64,
+        fee_input: BatchFeeInput,
+        miniblock_number: MiniblockNumber,
+    ) {
+        *self.entry.lock().expect("gas price cache is poisoned") = Some(CachedGasPrice {
+            base_fee,
+            fee_input,
+            miniblock_number,
+            fetched_at: Instant::now(),
+        });
+    }
+}
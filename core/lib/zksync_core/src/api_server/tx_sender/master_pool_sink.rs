@@ -1,8 +1,15 @@
-use std::collections::hash_map::{Entry, HashMap};
+use std::{
+    collections::hash_map::{Entry, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
-use tokio::sync::Mutex;
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
 use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool, Core, CoreDal};
-use zksync_types::{fee::TransactionExecutionMetrics, l2::L2Tx, Address, Nonce, H256};
+use zksync_types::{
+    fee::TransactionExecutionMetrics, l2::L2Tx, Address, MiniblockNumber, Nonce, H256,
+};
 
 use super::{tx_sink::TxSink, SubmitTxError};
 use crate::{
@@ -10,18 +17,34 @@ use crate::{
     metrics::{TxStage, APP_METRICS},
 };
 
+type InflightRequests = Mutex<HashMap<(Address, Nonce), H256>>;
+
+/// Latest sealed miniblock in the master DB together with the time it was fetched.
+#[derive(Debug, Clone, Copy)]
+struct CachedMiniblockNumber {
+    number: Option<MiniblockNumber>,
+    fetched_at: Instant,
+}
+
 /// Wrapper for the master DB pool that allows to submit transactions to the mempool.
 #[derive(Debug)]
 pub struct MasterPoolSink {
     master_pool: ConnectionPool<Core>,
-    inflight_requests: Mutex<HashMap<(Address, Nonce), H256>>,
+    inflight_requests: InflightRequests,
+    latest_sealed_miniblock: tokio::sync::Mutex<Option<CachedMiniblockNumber>>,
 }
 
 impl MasterPoolSink {
+    /// Time for which the latest sealed miniblock in the master DB is cached, so that checking the pending block
+    /// doesn't require a master DB connection per request. Since miniblocks are normally sealed once per second,
+    /// this delays detecting a stale pending block by at most a miniblock.
+    const LATEST_MINIBLOCK_CACHE_TTL: Duration = Duration::from_secs(1);
+
     pub fn new(master_pool: ConnectionPool<Core>) -> Self {
         Self {
             master_pool,
             inflight_requests: Mutex::new(HashMap::new()),
+            latest_sealed_miniblock: tokio::sync::Mutex::new(None),
         }
     }
 }
@@ -32,31 +55,43 @@ impl TxSink for MasterPoolSink {
         &self,
         tx: L2Tx,
         execution_metrics: TransactionExecutionMetrics,
+        valid_until: Option<DateTime<Utc>>,
     ) -> Result<L2TxSubmissionResult, SubmitTxError> {
         let address_and_nonce = (tx.initiator_account(), tx.nonce());
 
-        let mut lock = self.inflight_requests.lock().await;
-        match lock.entry(address_and_nonce) {
-            Entry::Occupied(entry) => {
-                let submission_res_handle = if entry.get() == &tx.hash() {
-                    L2TxSubmissionResult::Duplicate
-                } else {
-                    L2TxSubmissionResult::InsertionInProgress
-                };
-                APP_METRICS.processed_txs[&TxStage::Mempool(submission_res_handle)].inc();
-                return Ok(submission_res_handle);
-            }
-            Entry::Vacant(entry) => {
-                entry.insert(tx.hash());
-                API_METRICS.inflight_tx_submissions.inc_by(1);
+        let tx_hash = tx.hash();
+        {
+            let mut lock = self
+                .inflight_requests
+                .lock()
+                .expect("in-flight requests are poisoned");
+            match lock.entry(address_and_nonce) {
+                Entry::Occupied(entry) => {
+                    let inflight_tx_hash = *entry.get();
+                    if inflight_tx_hash == tx_hash {
+                        let submission_res_handle = L2TxSubmissionResult::InsertionInProgress;
+                        APP_METRICS.processed_txs[&TxStage::Mempool(submission_res_handle)].inc();
+                        return Ok(submission_res_handle);
+                    }
+                    APP_METRICS.processed_txs[&TxStage::ConcurrentNonceConflict].inc();
+                    return Err(SubmitTxError::ConcurrentNonceConflict(inflight_tx_hash));
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(tx_hash);
+                    API_METRICS.inflight_tx_submissions.inc_by(1);
+                }
             }
+        }
+        // Ensures that the in-flight request is removed even if the submission future is dropped midway.
+        let _guard = InflightRequestGuard {
+            inflight_requests: &self.inflight_requests,
+            address_and_nonce,
         };
-        drop(lock);
 
-        let result = match self.master_pool.connection_tagged("api").await {
+        match self.master_pool.connection_tagged("api").await {
             Ok(mut connection) => connection
                 .transactions_dal()
-                .insert_transaction_l2(tx, execution_metrics)
+                .insert_transaction_l2_with_deadline(tx, execution_metrics, valid_until)
                 .await
                 .map(|submission_res_handle| {
                     APP_METRICS.processed_txs[&TxStage::Mempool(submission_res_handle)].inc();
@@ -64,14 +99,47 @@ impl TxSink for MasterPoolSink {
                 })
                 .map_err(|err| anyhow::format_err!(err).into()),
             Err(err) => Err(err.into()),
-        };
+        }
+    }
+
+    async fn lookup_latest_sealed_miniblock(
+        &self,
+    ) -> Result<Option<MiniblockNumber>, SubmitTxError> {
+        // The lock is held while fetching, so that concurrent lookups wait for a single DB query.
+        let mut cached = self.latest_sealed_miniblock.lock().await;
+        if let Some(cached) = *cached {
+            if cached.fetched_at.elapsed() < Self::LATEST_MINIBLOCK_CACHE_TTL {
+                return Ok(cached.number);
+            }
+        }
+
+        let mut connection = self.master_pool.connection_tagged("api").await?;
+        let miniblock_number = connection
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("failed getting sealed miniblock number from master DB")?;
+        *cached = Some(CachedMiniblockNumber {
+            number: miniblock_number,
+            fetched_at: Instant::now(),
+        });
+        Ok(miniblock_number)
+    }
+}
+
+/// Removes an in-flight submission request on drop.
+#[derive(Debug)]
+struct InflightRequestGuard<'a> {
+    inflight_requests: &'a InflightRequests,
+    address_and_nonce: (Address, Nonce),
+}
 
+impl Drop for InflightRequestGuard<'_> {
+    fn drop(&mut self) {
         self.inflight_requests
             .lock()
-            .await
-            .remove(&address_and_nonce);
+            .expect("in-flight requests are poisoned")
+            .remove(&self.address_and_nonce);
         API_METRICS.inflight_tx_submissions.dec_by(1);
-
-        result
     }
 }
@@ -1,50 +1,85 @@
 //! Helper module to submit transactions into the zkSync Network.
 
-use std::{cmp, sync::Arc, time::Instant};
+use std::{
+    cmp,
+    collections::{hash_map, HashMap, HashSet},
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use futures::{channel::mpsc, future, stream, Stream, StreamExt};
 use multivm::{
-    interface::VmExecutionResultAndLogs,
-    utils::{adjust_pubdata_price_for_tx, derive_base_fee_and_gas_per_pubdata, derive_overhead},
+    interface::{ExecutionResult, Halt, VmExecutionResultAndLogs, VmRevertReason},
+    utils::{
+        adjust_pubdata_price_for_tx, derive_base_fee_and_gas_per_pubdata, derive_overhead,
+        get_intrinsic_constants, get_max_gas_per_pubdata_byte,
+    },
     vm_latest::constants::BLOCK_GAS_LIMIT,
+    zk_evm_latest::zkevm_opcode_defs::system_params::MAX_PUBDATA_PER_BLOCK,
 };
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tokio::sync::Semaphore;
 use zksync_config::configs::{api::Web3JsonRpcConfig, chain::StateKeeperConfig};
 use zksync_contracts::BaseSystemContracts;
 use zksync_dal::{
-    transactions_dal::L2TxSubmissionResult, Connection, ConnectionPool, Core, CoreDal,
+    transactions_dal::L2TxSubmissionResult, Connection, ConnectionPool, Core, CoreDal, SqlxError,
 };
 use zksync_state::PostgresStorageCaches;
 use zksync_types::{
+    api,
     fee::{Fee, TransactionExecutionMetrics},
     fee_model::BatchFeeInput,
-    get_code_key, get_intrinsic_constants,
-    l1::is_l1_tx_type,
-    l2::{error::TxCheckError::TxDuplication, L2Tx},
-    utils::storage_key_for_eth_balance,
+    get_code_key, get_nonce_key,
+    l1::{is_l1_tx_type, L1TxCommonData},
+    l2::{error::TxCheckError::TxDuplication, L2Tx, TransactionType},
+    transaction_request::PaymasterParams,
+    utils::{deployed_address_create2, storage_key_for_eth_balance},
+    vm_trace::Call,
     AccountTreeId, Address, ExecuteTransactionCommon, L2ChainId, MiniblockNumber, Nonce,
-    PackedEthSignature, ProtocolVersionId, Transaction, VmVersion, H160, H256, MAX_L2_TX_GAS_LIMIT,
-    MAX_NEW_FACTORY_DEPS, U256,
+    PackedEthSignature, ProtocolVersionId, Transaction, VmVersion, CONTRACT_DEPLOYER_ADDRESS, H160,
+    H256, MAX_L2_TX_GAS_LIMIT, MAX_NEW_FACTORY_DEPS, U256,
 };
 use zksync_utils::h256_to_u256;
 
+pub use self::capture::{CapturedFeeInput, ExecutionCapture};
 pub(super) use self::result::SubmitTxError;
-use self::tx_sink::TxSink;
+use self::{
+    balance_cache::FeeAccountBalanceCache,
+    gas_price_cache::GasPriceCache,
+    rejected_tx_sink::{NoopRejectedTxSink, RejectedTxSink},
+    tx_sink::TxSink,
+};
 use crate::{
     api_server::{
         execution_sandbox::{
-            get_pubdata_for_factory_deps, BlockArgs, BlockStartInfo, SubmitTxStage,
-            TransactionExecutor, TxExecutionArgs, TxSharedArgs, VmConcurrencyLimiter, VmPermit,
-            SANDBOX_METRICS,
+            acquire_connection, get_pubdata_for_factory_deps, ApiTracer, BlockArgs, BlockStartInfo,
+            ConnectionAcquireTimeout, EstimateGasPhase, PrecedingTxsState, SandboxCallType,
+            SubmitTxStage, TransactionExecutor, TxExecutionArgs, TxSharedArgs,
+            VmConcurrencyBarrier, VmConcurrencyLimiter, VmPermit, SANDBOX_METRICS,
         },
         tx_sender::result::ApiCallResult,
     },
     fee_model::BatchFeeModelInputProvider,
-    state_keeper::seal_criteria::{ConditionalSealer, NoopSealer, SealData},
+    state_keeper::seal_criteria::{
+        ConditionalSealer, NoopSealer, SealData, UnexecutableCriterion, UnexecutableReason,
+    },
     utils::pending_protocol_version,
 };
 
+mod balance_cache;
+mod capture;
+mod gas_price_cache;
 pub mod master_pool_sink;
 pub mod proxy;
+pub mod rejected_tx_sink;
 mod result;
 #[cfg(test)]
 pub(crate) mod tests;
@@ -154,6 +189,12 @@ pub struct TxSenderBuilder {
     tx_sink: Arc<dyn TxSink>,
     /// Batch sealer used to check whether transaction can be executed by the sequencer.
     sealer: Option<Arc<dyn ConditionalSealer>>,
+    /// Sink recording rejected transactions.
+    rejected_tx_sink: Option<Arc<dyn RejectedTxSink>>,
+    /// Limiter for VM executions, if it was configured via [`Self::with_vm_concurrency()`].
+    vm_concurrency_limiter: Option<Arc<VmConcurrencyLimiter>>,
+    /// Whether [`Self::build()`] should fail if no sealer is set instead of falling back to a no-op one.
+    require_sealer: bool,
 }
 
 impl TxSenderBuilder {
@@ -167,6 +208,9 @@ impl TxSenderBuilder {
             replica_connection_pool,
             tx_sink,
             sealer: None,
+            rejected_tx_sink: None,
+            vm_concurrency_limiter: None,
+            require_sealer: false,
         }
     }
 
@@ -175,27 +219,85 @@ impl TxSenderBuilder {
         self
     }
 
+    /// Requires a sealer to be set via [`Self::with_sealer()`]. By default, a no-op sealer is used if no sealer is set,
+    /// which considers all transactions executable. This is convenient for tests, but unsafe for the main node.
+    pub fn require_sealer(mut self) -> Self {
+        self.require_sealer = true;
+        self
+    }
+
+    /// Sets a sink recording transactions rejected on submission. By default, rejected transactions are not recorded.
+    pub fn with_rejected_tx_sink(mut self, sink: Arc<dyn RejectedTxSink>) -> Self {
+        self.rejected_tx_sink = Some(sink);
+        self
+    }
+
+    /// Creates a VM concurrency limiter with the specified max concurrency. This is a shortcut for passing
+    /// a limiter to [`Self::build()`]. The returned barrier should be closed and awaited on shutdown
+    /// to wait for the VM tasks spawned by the built `TxSender`.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn with_vm_concurrency(mut self, limit: usize) -> (Self, VmConcurrencyBarrier) {
+        let (limiter, barrier) = VmConcurrencyLimiter::new(limit);
+        self.vm_concurrency_limiter = Some(Arc::new(limiter));
+        (self, barrier)
+    }
+
+    /// Builds a `TxSender`. If `vm_concurrency_limiter` is `None`, the limiter configured
+    /// via [`Self::with_vm_concurrency()`] is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the VM concurrency limiter is neither provided nor configured, or if a sealer is
+    /// [required](Self::require_sealer()), but not set.
     pub async fn build(
         self,
         batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
-        vm_concurrency_limiter: Arc<VmConcurrencyLimiter>,
+        vm_concurrency_limiter: Option<Arc<VmConcurrencyLimiter>>,
         api_contracts: ApiContracts,
         storage_caches: PostgresStorageCaches,
-    ) -> TxSender {
-        // Use noop sealer if no sealer was explicitly provided.
-        let sealer = self.sealer.unwrap_or_else(|| Arc::new(NoopSealer));
+    ) -> anyhow::Result<TxSender> {
+        // Use noop sealer if no sealer was explicitly provided, unless a sealer is required.
+        let sealer = match self.sealer {
+            Some(sealer) => sealer,
+            None => {
+                anyhow::ensure!(
+                    !self.require_sealer,
+                    "sealer is required for `TxSender`, but was not set via `with_sealer()`"
+                );
+                Arc::new(NoopSealer)
+            }
+        };
+        let rejected_tx_sink = self
+            .rejected_tx_sink
+            .unwrap_or_else(|| Arc::new(NoopRejectedTxSink));
+        let vm_concurrency_limiter = vm_concurrency_limiter
+            .or(self.vm_concurrency_limiter)
+            .context("VM concurrency limiter is neither provided nor configured for `TxSender`")?;
+        let fee_account_balance_cache = FeeAccountBalanceCache::new(
+            self.config.fee_account_addr,
+            self.config.fee_account_balance_cache_ttl,
+        );
+        let estimation_limiter = self.config.max_concurrent_estimations.map(Semaphore::new);
+
+        let gas_price_cache = GasPriceCache::new(self.config.gas_price_cache_ttl);
 
-        TxSender(Arc::new(TxSenderInner {
+        Ok(TxSender(Arc::new(TxSenderInner {
             sender_config: self.config,
             tx_sink: self.tx_sink,
             replica_connection_pool: self.replica_connection_pool,
             batch_fee_input_provider,
             api_contracts,
             vm_concurrency_limiter,
+            estimation_limiter,
             storage_caches,
             sealer,
+            rejected_tx_sink,
             executor: TransactionExecutor::Real,
-        }))
+            fee_account_balance_cache,
+            gas_price_cache,
+            last_observed_miniblock: AtomicU32::new(0),
+        })))
     }
 }
 
@@ -214,9 +316,181 @@ pub struct TxSenderConfig {
     pub l1_to_l2_transactions_compatibility_mode: bool,
     pub chain_id: L2ChainId,
     pub max_pubdata_per_batch: u64,
+    /// Maximum pubdata a single transaction may publish (including its factory deps); checked both during gas estimation
+    /// and on submission. May be set lower than `max_pubdata_per_batch` to prevent a single transaction from taking up
+    /// most of a batch. Transactions exceeding the limit are rejected with [`SubmitTxError::TxPubdataLimitExceeded`].
+    pub max_pubdata_per_tx: u64,
+    /// If set, `eth_call` is only allowed to target contracts from this set. `None` allows all targets.
+    pub eth_call_target_allowlist: Option<HashSet<Address>>,
+    /// Maximum size of data returned by `eth_call` (either the call output or the revert data). Calls returning
+    /// more data are rejected with [`SubmitTxError::ResponseTooLarge`]. If not set, the response size is not limited.
+    pub max_eth_call_response_bytes: Option<usize>,
+    /// Timeout for acquiring a connection to the replica DB. If not set, the acquisition is only limited
+    /// by the connection pool itself.
+    pub replica_connection_acquire_timeout: Option<Duration>,
+    /// Number of times the final gas estimation step is retried with a bumped gas limit if it runs out of gas.
+    /// Reverts and other failures not related to gas aren't retried. Running out of gas may be caused by gas refunds
+    /// making execution slightly non-monotonic in the gas limit.
+    pub estimate_gas_final_step_retries: u32,
+    /// Relative gas limit increment applied on each retry of the final gas estimation step (e.g., 0.05 for +5%).
+    pub estimate_gas_retry_bump: f64,
+    /// Number of times reading the expected account nonce is retried on transient DB errors (e.g., a replica
+    /// connection timeout).
+    pub nonce_read_retries: u32,
+    /// Interval between retries of reading the expected account nonce.
+    pub nonce_read_retry_interval: Duration,
+    /// Minimum allowed scale factor for estimated gas limits; smaller factors are clamped to it. Factors below 1.0
+    /// are not recommended since they make gas estimates too low for transactions to be executed.
+    pub min_estimate_gas_scale_factor: f64,
+    /// Maximum allowed scale factor for estimated gas limits; larger factors are clamped to it.
+    pub max_estimate_gas_scale_factor: f64,
+    /// Wall-clock time limit for a single VM execution during gas estimation, `eth_call` or transaction submission.
+    /// If not set, VM executions are only limited by gas and storage invocations.
+    pub vm_execution_timeout: Option<Duration>,
+    /// Lower bound for `gas_per_pubdata` derived from the batch fee input during fee estimation. Can be used to prevent
+    /// unsafely low values on chains with very cheap L1 gas. If not set, the derived value is used as is.
+    pub min_gas_per_pubdata: Option<u64>,
+    /// Maximum gas limit for the transaction body considered during gas estimation. Should only differ
+    /// from [`MAX_L2_TX_GAS_LIMIT`] on networks experimenting with higher limits. Independent of
+    /// [`Self::max_allowed_l2_tx_gas_limit`], which is checked for submitted transactions.
+    pub max_gas_limit_for_estimation: u32,
+    /// Number of nonces beyond [`Self::max_nonce_ahead`] that are still accepted, provided that all preceding nonces
+    /// of the account are already pending in the mempool. Smooths over timing issues for sequential submitters.
+    pub max_nonce_ahead_grace: u32,
+    /// If set, a transaction reverting in the final gas estimation step is re-executed with the `eth_call`
+    /// (playground) contracts to surface a more human-readable revert reason. The binary search and the returned
+    /// execution metrics are still based on the gas estimation contracts.
+    pub estimate_gas_playground_revert_reasons: bool,
+    /// If set, gas estimation executes the transaction with the maximum gas limit before the binary search. If
+    /// the transaction reverts with the maximum gas limit, the revert is returned right away since more gas cannot help,
+    /// which saves a binary search for deterministically reverting transactions. Other failures (e.g., running out of gas)
+    /// don't abort the estimation.
+    pub estimate_gas_probe_max_gas_limit: bool,
+    /// **Experimental.** If set, gas estimation executes the transaction with the maximum gas limit and takes
+    /// the reported gas used as the estimate, confirming it with another execution. If the confirmation fails
+    /// (e.g., because gas consumption of the transaction depends on the gas limit), estimation falls back
+    /// to the binary search.
+    pub estimate_gas_by_extrapolation: bool,
+    /// If set, a summary of each gas estimation (preparation time, number of binary search iterations
+    /// and the final search bounds) is logged at the debug level. Unlike per-iteration logs, which are emitted
+    /// at the trace level, the summary is lightweight enough to be logged for every request.
+    pub log_estimation_summary: bool,
+    /// If set, it is checked that signatures of submitted transactions recover to their initiators before any
+    /// DB reads or VM executions, so that transactions with invalid signatures are rejected cheaply. Custom accounts,
+    /// which can use arbitrary signature schemes, are exempt from the check.
+    pub early_signature_check: bool,
+    /// Targets (i.e., recipient addresses) that submitted transactions are not allowed to have. Transactions
+    /// targeting these addresses are rejected before execution. Can be used to protect public endpoints
+    /// against transactions that are known to fail.
+    pub forbidden_tx_targets: HashSet<Address>,
+    /// Maximum number of miniblocks the state at the resolved pending block may lag behind the latest sealed miniblock
+    /// at the transaction destination (the master DB on the main node, or the main node for the external node;
+    /// see [`TxSink::lookup_latest_sealed_miniblock()`]). Pending blocks lagging further (e.g., if the replica DB
+    /// falls behind) are rejected as stale instead of being used for execution. If not set, the pending block
+    /// is never considered stale.
+    pub max_pending_block_lag: Option<u32>,
+    /// Maximum number of concurrent gas estimations. Unlike other sandbox calls, a gas estimation holds a VM permit
+    /// for the whole binary search, so limiting estimations separately prevents them from starving other traffic.
+    /// If not set, estimations are only limited by the VM concurrency limiter.
+    pub max_concurrent_estimations: Option<usize>,
+    /// Policy for computing the expected nonce of submitted transactions, which the nonce window
+    /// (see [`Self::max_nonce_ahead`]) starts from.
+    pub expected_nonce_policy: ExpectedNoncePolicy,
+    /// Treatment of submitted transactions with `gas_per_pubdata_limit` exceeding the maximum supported by the VM.
+    pub excessive_gas_per_pubdata_policy: ExcessiveGasPerPubdataPolicy,
+    /// Treatment of submitted transactions initiated by the fee account (see [`FeeAccountTxPolicy`]).
+    pub fee_account_tx_policy: FeeAccountTxPolicy,
+    /// Time-to-live for the base fee cached by [`TxSender::gas_price()`].
+    pub gas_price_cache_ttl: Duration,
+    /// Time-to-live for the fee account balance cached by [`TxSender`].
+    pub fee_account_balance_cache_ttl: Duration,
+}
+
+/// Policy for computing the expected nonce of submitted transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExpectedNoncePolicy {
+    /// Expected nonce is the account nonce at the latest sealed miniblock.
+    #[default]
+    Sealed,
+    /// Expected nonce accounts for the account's transactions pending in the mempool, i.e., it's the first nonce
+    /// starting from the sealed one that has no pending transaction. Note that with this policy, pending transactions
+    /// cannot be replaced by resubmitting a transaction with the same nonce.
+    Pending,
+}
+
+impl ExpectedNoncePolicy {
+    /// Creates a policy from the corresponding config flag.
+    pub fn new(use_pending_nonces: bool) -> Self {
+        if use_pending_nonces {
+            Self::Pending
+        } else {
+            Self::Sealed
+        }
+    }
+}
+
+/// Treatment of submitted transactions with `gas_per_pubdata_limit` exceeding the maximum supported by the VM.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExcessiveGasPerPubdataPolicy {
+    /// Transactions are accepted with a warning. The limit is effectively clamped to the maximum by the fee logic.
+    #[default]
+    Clamp,
+    /// Transactions are rejected with [`SubmitTxError::GasPerPubdataLimitTooHigh`].
+    Reject,
+}
+
+impl ExcessiveGasPerPubdataPolicy {
+    /// Creates a policy from the corresponding config flag.
+    pub fn new(reject: bool) -> Self {
+        if reject {
+            Self::Reject
+        } else {
+            Self::Clamp
+        }
+    }
+}
+
+/// Treatment of submitted transactions initiated by the fee account, i.e. the operator paying fees to itself.
+///
+/// The fee account doesn't receive fees at the time a transaction is executed; the bootloader collects fees
+/// and transfers them to the operator once the batch is sealed. Thus, fees of a transaction initiated
+/// by the fee account are never netted against the fees it receives: its balance must cover the maximum fee
+/// and the transferred value, like for any other account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeAccountTxPolicy {
+    /// Transactions are validated like transactions from any other account. The fee account balance may be served
+    /// from the fee account balance cache, which is invalidated once a newer miniblock is observed.
+    #[default]
+    Validate,
+    /// Transactions are rejected with [`SubmitTxError::InitiatorIsFeeAccount`], e.g. if the fee account
+    /// is only expected to receive fees.
+    Reject,
+}
+
+impl FeeAccountTxPolicy {
+    /// Creates a policy from the corresponding config flag.
+    pub fn new(reject: bool) -> Self {
+        if reject {
+            Self::Reject
+        } else {
+            Self::Validate
+        }
+    }
 }
 
 impl TxSenderConfig {
+    pub const DEFAULT_ESTIMATE_GAS_FINAL_STEP_RETRIES: u32 = 2;
+    pub const DEFAULT_ESTIMATE_GAS_RETRY_BUMP: f64 = 0.05;
+    pub const DEFAULT_NONCE_READ_RETRIES: u32 = 2;
+    pub const DEFAULT_NONCE_READ_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+    pub const DEFAULT_MAX_PUBDATA_PER_TX: u64 = MAX_PUBDATA_PER_BLOCK as u64;
+    pub const DEFAULT_MIN_ESTIMATE_GAS_SCALE_FACTOR: f64 = 1.0;
+    pub const DEFAULT_MAX_ESTIMATE_GAS_SCALE_FACTOR: f64 = 10.0;
+    pub const DEFAULT_MAX_GAS_LIMIT_FOR_ESTIMATION: u32 = MAX_L2_TX_GAS_LIMIT as u32;
+    pub const DEFAULT_MAX_NONCE_AHEAD_GRACE: u32 = 0;
+    pub const DEFAULT_GAS_PRICE_CACHE_TTL: Duration = Duration::from_secs(1);
+    pub const DEFAULT_FEE_ACCOUNT_BALANCE_CACHE_TTL: Duration = Duration::from_secs(1);
+
     pub fn new(
         state_keeper_config: &StateKeeperConfig,
         web3_json_config: &Web3JsonRpcConfig,
@@ -234,10 +508,176 @@ impl TxSenderConfig {
                 .l1_to_l2_transactions_compatibility_mode,
             chain_id,
             max_pubdata_per_batch: state_keeper_config.max_pubdata_per_batch,
+            max_pubdata_per_tx: web3_json_config
+                .max_pubdata_per_tx
+                .unwrap_or(Self::DEFAULT_MAX_PUBDATA_PER_TX),
+            eth_call_target_allowlist: web3_json_config
+                .eth_call_target_allowlist
+                .as_ref()
+                .map(|targets| targets.iter().copied().collect()),
+            max_eth_call_response_bytes: web3_json_config.max_eth_call_response_bytes,
+            replica_connection_acquire_timeout: web3_json_config
+                .replica_connection_acquire_timeout(),
+            estimate_gas_final_step_retries: web3_json_config
+                .estimate_gas_final_step_retries
+                .unwrap_or(Self::DEFAULT_ESTIMATE_GAS_FINAL_STEP_RETRIES),
+            estimate_gas_retry_bump: web3_json_config
+                .estimate_gas_retry_bump
+                .unwrap_or(Self::DEFAULT_ESTIMATE_GAS_RETRY_BUMP),
+            nonce_read_retries: web3_json_config
+                .nonce_read_retries
+                .unwrap_or(Self::DEFAULT_NONCE_READ_RETRIES),
+            nonce_read_retry_interval: web3_json_config
+                .nonce_read_retry_interval()
+                .unwrap_or(Self::DEFAULT_NONCE_READ_RETRY_INTERVAL),
+            min_estimate_gas_scale_factor: web3_json_config
+                .min_estimate_gas_scale_factor
+                .unwrap_or(Self::DEFAULT_MIN_ESTIMATE_GAS_SCALE_FACTOR),
+            max_estimate_gas_scale_factor: web3_json_config
+                .max_estimate_gas_scale_factor
+                .unwrap_or(Self::DEFAULT_MAX_ESTIMATE_GAS_SCALE_FACTOR),
+            vm_execution_timeout: web3_json_config.vm_execution_timeout(),
+            min_gas_per_pubdata: web3_json_config.min_gas_per_pubdata,
+            max_gas_limit_for_estimation: web3_json_config
+                .max_gas_limit_for_estimation
+                .unwrap_or(Self::DEFAULT_MAX_GAS_LIMIT_FOR_ESTIMATION),
+            max_nonce_ahead_grace: web3_json_config
+                .max_nonce_ahead_grace
+                .unwrap_or(Self::DEFAULT_MAX_NONCE_AHEAD_GRACE),
+            estimate_gas_playground_revert_reasons: web3_json_config
+                .estimate_gas_playground_revert_reasons,
+            estimate_gas_probe_max_gas_limit: web3_json_config.estimate_gas_probe_max_gas_limit,
+            estimate_gas_by_extrapolation: web3_json_config.estimate_gas_by_extrapolation,
+            log_estimation_summary: web3_json_config.log_estimation_summary,
+            early_signature_check: web3_json_config.early_signature_check,
+            forbidden_tx_targets: web3_json_config
+                .forbidden_tx_targets()
+                .into_iter()
+                .collect(),
+            max_pending_block_lag: web3_json_config.max_pending_block_lag,
+            max_concurrent_estimations: web3_json_config.max_concurrent_estimations,
+            expected_nonce_policy: ExpectedNoncePolicy::new(web3_json_config.use_pending_nonces),
+            excessive_gas_per_pubdata_policy: ExcessiveGasPerPubdataPolicy::new(
+                web3_json_config.reject_excessive_gas_per_pubdata,
+            ),
+            fee_account_tx_policy: FeeAccountTxPolicy::new(web3_json_config.reject_fee_account_txs),
+            gas_price_cache_ttl: web3_json_config
+                .gas_price_cache_ttl()
+                .unwrap_or(Self::DEFAULT_GAS_PRICE_CACHE_TTL),
+            fee_account_balance_cache_ttl: web3_json_config
+                .fee_account_balance_cache_ttl()
+                .unwrap_or(Self::DEFAULT_FEE_ACCOUNT_BALANCE_CACHE_TTL),
+        }
+    }
+
+    /// Checks the consistency of configuration parameters. This should be called before using the config
+    /// to build a [`TxSender`].
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let (min, max) = (
+            self.min_estimate_gas_scale_factor,
+            self.max_estimate_gas_scale_factor,
+        );
+        anyhow::ensure!(
+            min.is_finite() && max.is_finite(),
+            "estimated gas scale factor bounds [{min}, {max}] must be finite"
+        );
+        anyhow::ensure!(
+            min > 0.0 && min <= max,
+            "estimated gas scale factor bounds [{min}, {max}] must be positive and ordered"
+        );
+        anyhow::ensure!(
+            self.max_concurrent_estimations != Some(0),
+            "max concurrent estimations must be positive if set"
+        );
+        let retry_bump = self.estimate_gas_retry_bump;
+        anyhow::ensure!(
+            retry_bump.is_finite() && retry_bump >= 0.0,
+            "estimate gas retry bump {retry_bump} must be finite and non-negative"
+        );
+        Ok(())
+    }
+}
+
+/// Serializable snapshot of the [`TxSenderConfig`] parameters affecting fees and transaction acceptance,
+/// as returned by [`TxSender::effective_config()`]. Large collections (e.g., target allowlists) are omitted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxSenderConfigSnapshot {
+    pub fee_account_addr: Address,
+    pub chain_id: L2ChainId,
+    pub gas_price_scale_factor: f64,
+    pub min_estimate_gas_scale_factor: f64,
+    pub max_estimate_gas_scale_factor: f64,
+    pub min_gas_per_pubdata: Option<u64>,
+    pub max_nonce_ahead: u32,
+    pub max_nonce_ahead_grace: u32,
+    pub max_allowed_l2_tx_gas_limit: u32,
+    pub max_gas_limit_for_estimation: u32,
+    pub validation_computational_gas_limit: u32,
+    pub max_pubdata_per_batch: u64,
+    pub max_pubdata_per_tx: u64,
+    pub vm_execution_timeout_ms: Option<u64>,
+    pub max_concurrent_estimations: Option<usize>,
+}
+
+impl From<&TxSenderConfig> for TxSenderConfigSnapshot {
+    fn from(config: &TxSenderConfig) -> Self {
+        Self {
+            fee_account_addr: config.fee_account_addr,
+            chain_id: config.chain_id,
+            gas_price_scale_factor: config.gas_price_scale_factor,
+            min_estimate_gas_scale_factor: config.min_estimate_gas_scale_factor,
+            max_estimate_gas_scale_factor: config.max_estimate_gas_scale_factor,
+            min_gas_per_pubdata: config.min_gas_per_pubdata,
+            max_nonce_ahead: config.max_nonce_ahead,
+            max_nonce_ahead_grace: config.max_nonce_ahead_grace,
+            max_allowed_l2_tx_gas_limit: config.max_allowed_l2_tx_gas_limit,
+            max_gas_limit_for_estimation: config.max_gas_limit_for_estimation,
+            validation_computational_gas_limit: config.validation_computational_gas_limit,
+            max_pubdata_per_batch: config.max_pubdata_per_batch,
+            max_pubdata_per_tx: config.max_pubdata_per_tx,
+            vm_execution_timeout_ms: config
+                .vm_execution_timeout
+                .map(|timeout| timeout.as_millis() as u64),
+            max_concurrent_estimations: config.max_concurrent_estimations,
         }
     }
 }
 
+/// Checks whether a DB error is expected to go away on its own, so that the failed operation can be retried.
+fn is_transient_db_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|err| {
+        err.is::<ConnectionAcquireTimeout>()
+            || matches!(
+                err.downcast_ref::<SqlxError>(),
+                Some(SqlxError::Io(_) | SqlxError::PoolTimedOut)
+            )
+    })
+}
+
+/// Checks whether VM execution failed because of an insufficient gas limit, as opposed to deterministic failures
+/// (e.g., reverts) that wouldn't be fixed by increasing the gas limit.
+fn is_out_of_gas(result: &ExecutionResult) -> bool {
+    matches!(
+        result,
+        ExecutionResult::Halt {
+            reason: Halt::BootloaderOutOfGas
+                | Halt::ValidationOutOfGas
+                | Halt::NotEnoughGasProvided
+        }
+    )
+}
+
+/// Checks whether VM execution was aborted because of the execution timeout.
+fn is_execution_timed_out(result: &ExecutionResult) -> bool {
+    matches!(
+        result,
+        ExecutionResult::Halt {
+            reason: Halt::ExecutionDeadlineExceeded
+        }
+    )
+}
+
 pub struct TxSenderInner {
     pub(super) sender_config: TxSenderConfig,
     /// Sink to be used to persist transactions.
@@ -248,13 +688,186 @@ pub struct TxSenderInner {
     pub(super) api_contracts: ApiContracts,
     /// Used to limit the amount of VMs that can be executed simultaneously.
     pub(super) vm_concurrency_limiter: Arc<VmConcurrencyLimiter>,
+    /// Limits the number of concurrent gas estimations, if configured.
+    pub(super) estimation_limiter: Option<Semaphore>,
     // Caches used in VM execution.
     storage_caches: PostgresStorageCaches,
     /// Batch sealer used to check whether transaction can be executed by the sequencer.
     sealer: Arc<dyn ConditionalSealer>,
+    /// Sink recording transactions rejected on submission.
+    pub(super) rejected_tx_sink: Arc<dyn RejectedTxSink>,
     pub(super) executor: TransactionExecutor,
+    /// Cache for the fee account balance, which is read on the hot path.
+    fee_account_balance_cache: FeeAccountBalanceCache,
+    /// Cache for the base fee returned by [`TxSender::gas_price()`].
+    gas_price_cache: GasPriceCache,
+    /// Newest pending miniblock observed by the sender; used to invalidate [`Self::gas_price_cache`].
+    last_observed_miniblock: AtomicU32,
+}
+
+/// Result of gas estimation for a transaction.
+#[derive(Debug, Clone)]
+pub struct GasEstimate {
+    /// Suggested fee for the transaction. Its gas limit includes all gas components below, plus the transaction overhead.
+    pub fee: Fee,
+    /// Gas required to publish on L1 the transaction's factory deps not yet known to the network.
+    pub gas_for_bytecodes_pubdata: u32,
+    /// Gas required to execute the transaction body on L2 (scaled by the estimated fee scale factor).
+    pub tx_body_gas_limit: u32,
+    /// Whether `tx_body_gas_limit` was capped by the maximum gas limit for estimation (by default,
+    /// [`MAX_L2_TX_GAS_LIMIT`]), i.e. the limit scaled by the fee scale factor would be higher. Can be used to warn
+    /// users that the transaction is close to the gas ceiling.
+    pub gas_limit_capped: bool,
+    /// Minimal gas limit under which the transaction succeeded during the binary search, i.e., not scaled
+    /// by the estimated fee scale factor. Includes the same components as `fee.gas_limit`, so it can be compared
+    /// with the suggested limit for custom risk modeling. Unless the scale factor is below 1, it doesn't exceed
+    /// `fee.gas_limit`.
+    pub min_viable_gas_limit: u32,
+    /// Miniblock the estimate was computed against. This is the pending miniblock at the time of the estimation.
+    pub block_number: MiniblockNumber,
+    /// Protocol version the estimate was computed for, i.e. the version of the pending miniblock.
+    pub protocol_version: ProtocolVersionId,
+    /// Number of VM executions performed by the binary search for the gas limit (not including the final step).
+    pub binary_search_iterations: usize,
+    /// For transactions sponsored by a paymaster, gas spent on the paymaster validation and the post-transaction call.
+    /// This gas is included into `fee.gas_limit`; the paymaster must cover the entire fee, i.e. `fee.gas_limit`
+    /// multiplied by `fee.max_fee_per_gas`. `None` unless [`GasEstimationOptions::estimate_paymaster_gas`] is set,
+    /// if the transaction has no paymaster, or if the paymaster gas cannot be determined because the transaction fails
+    /// without the paymaster.
+    pub paymaster_gas: Option<u32>,
+    /// Adjustment of the fee input applied so that the gas per pubdata byte required by the block doesn't exceed
+    /// the transaction `gas_per_pubdata_limit`. `None` if the fee input wasn't adjusted.
+    pub pubdata_price_adjustment: Option<PubdataPriceAdjustment>,
+    /// Address of the contract deployed by the transaction via CREATE2, i.e. by calling `create2()` or
+    /// `create2Account()` on the contract deployer. The address is checked not to be occupied before the estimation;
+    /// otherwise, [`SubmitTxError::DeploymentAddressOccupied`] is returned. `None` if the transaction is not
+    /// a CREATE2 deployment.
+    pub create2_address: Option<Address>,
+}
+
+/// Adjustment of the batch fee input applied during gas estimation, expressed in terms of the derived fee parameters.
+/// The adjustment lowers the gas per pubdata byte, which may change the base fee and thus substantially change
+/// the quoted fee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PubdataPriceAdjustment {
+    /// Base fee derived from the unadjusted fee input.
+    pub original_base_fee: u64,
+    /// Gas per pubdata byte derived from the unadjusted fee input.
+    pub original_gas_per_pubdata: u64,
+    /// Base fee derived from the adjusted fee input.
+    pub adjusted_base_fee: u64,
+    /// Gas per pubdata byte derived from the adjusted fee input.
+    pub adjusted_gas_per_pubdata: u64,
+}
+
+impl PubdataPriceAdjustment {
+    fn new(
+        original_input: BatchFeeInput,
+        adjusted_input: BatchFeeInput,
+        vm_version: VmVersion,
+    ) -> Option<Self> {
+        if original_input == adjusted_input {
+            return None;
+        }
+        let (original_base_fee, original_gas_per_pubdata) =
+            derive_base_fee_and_gas_per_pubdata(original_input, vm_version);
+        let (adjusted_base_fee, adjusted_gas_per_pubdata) =
+            derive_base_fee_and_gas_per_pubdata(adjusted_input, vm_version);
+        Some(Self {
+            original_base_fee,
+            original_gas_per_pubdata,
+            adjusted_base_fee,
+            adjusted_gas_per_pubdata,
+        })
+    }
+}
+
+/// Outcome of [`TxSender::extrapolate_gas_limit()`].
+#[derive(Debug)]
+enum GasExtrapolation {
+    /// Extrapolated gas limit confirmed by execution.
+    Confirmed(u32),
+    /// Extrapolated gas limit that turned out to be insufficient.
+    Insufficient(u32),
+    /// The transaction fails even with the maximum gas limit.
+    Failed,
+}
+
+/// Optional parameters for gas estimation.
+#[derive(Debug, Clone, Default)]
+pub struct GasEstimationOptions {
+    /// L1 gas price (in wei) to estimate with instead of the one provided by the batch fee input provider.
+    /// Can be used to forecast transaction costs under hypothetical L1 conditions. The pubdata price is adjusted
+    /// proportionally to the overridden price.
+    pub l1_gas_price_override: Option<u64>,
+    /// Nonce to estimate an L2 transaction with instead of the nonce embedded in the transaction. Can be used to estimate
+    /// a future transaction, e.g. if its execution depends on the nonce via CREATE address derivation.
+    /// Ignored for L1 transactions.
+    pub nonce_override: Option<Nonce>,
+    /// Signature to estimate an L2 transaction with instead of the signature embedded in the transaction. For custom
+    /// accounts, signature length and contents can affect the validation cost, so the placeholder signature used
+    /// for unsigned transactions may lead to an inaccurate estimate. Ignored for L1 transactions.
+    pub signature_override: Option<Vec<u8>>,
+    /// Acceptable overestimation of the gas limit as a percentage (e.g., 1.0 for 1%) of the current upper bound
+    /// of the binary search. Allows tighter precision for cheap transactions and coarser precision for expensive ones.
+    /// If set together with the absolute `acceptable_overestimation`, the larger of the two tolerances is used
+    /// on each binary search iteration.
+    pub acceptable_overestimation_pct: Option<f64>,
+    /// If set, gas for publishing factory deps is computed based on their uncompressed sizes rather than assuming
+    /// that bytecodes are compressed. Provides a conservative upper bound, e.g. for chains with compression disabled.
+    pub uncompressed_bytecodes: bool,
+    /// Balance of the operator (fee) account to estimate with instead of its current balance. Can be used to model
+    /// transactions sensitive to the operator state, e.g. ones relying on operator refunds.
+    pub operator_balance_override: Option<U256>,
+    /// If set, the fee input is not adjusted to the transaction `gas_per_pubdata_limit`, i.e. the estimate uses
+    /// the gas per pubdata byte derived from the batch fee input as is. Intended for diagnostic estimates;
+    /// transactions with the returned fee may not be executable if their `gas_per_pubdata_limit` is lower.
+    pub skip_pubdata_price_adjustment: bool,
+    /// If set, the estimated transaction is not checked against the sequencer seal criteria (e.g., circuit or
+    /// batch tip gas limits), so the returned fee is based on gas only. Intended for callers estimating transactions
+    /// to be submitted to another environment; such transactions may be rejected as unexecutable on this node.
+    pub skip_seal_check: bool,
+    /// If set, gas spent by the paymaster is reported in [`GasEstimate::paymaster_gas`] for paymaster-sponsored
+    /// transactions. This requires an additional VM execution of the transaction without the paymaster.
+    pub estimate_paymaster_gas: bool,
+}
+
+/// Outcome of replaying the transaction submission checks, as returned by [`TxSender::audit_submission()`].
+#[derive(Debug)]
+pub struct SubmissionAudit {
+    /// Miniblock the checks were replayed at.
+    pub block_number: MiniblockNumber,
+    /// Metrics of the transaction execution in the sandbox. `None` if the transaction was rejected before execution
+    /// or if the execution failed.
+    pub execution_metrics: Option<TransactionExecutionMetrics>,
+    /// Error the transaction submission would be rejected with, or `None` if the transaction would be accepted.
+    pub rejection: Option<SubmitTxError>,
+}
+
+impl SubmissionAudit {
+    /// Checks whether the transaction would be accepted.
+    pub fn is_accepted(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// Step of the gas estimation reported by [`TxSender::estimate_gas_steps()`].
+#[derive(Debug)]
+pub enum EstimationStep {
+    /// Execution of the transaction during the binary search for the gas limit.
+    Iteration {
+        /// Gas limit the transaction was executed with, including gas for publishing bytecodes, but not including
+        /// the transaction overhead.
+        gas_limit: u32,
+        /// Execution result with this gas limit.
+        result: ExecutionResult,
+    },
+    /// Result of the estimation. This is always the last reported step.
+    Finished(Result<Fee, SubmitTxError>),
 }
 
+type EstimationStepsSender = mpsc::UnboundedSender<EstimationStep>;
+
 #[derive(Clone)]
 pub struct TxSender(pub(super) Arc<TxSenderInner>);
 
@@ -269,79 +882,164 @@ impl TxSender {
         Arc::clone(&self.0.vm_concurrency_limiter)
     }
 
+    /// Returns a snapshot of the configuration this sender operates with, e.g. to be reported by a debug endpoint.
+    pub fn effective_config(&self) -> TxSenderConfigSnapshot {
+        (&self.0.sender_config).into()
+    }
+
+    /// Returns the number of currently free VM permits, i.e. how many VM executions (gas estimations, calls
+    /// or transaction submissions) can start right now without waiting. Can be used for backpressure decisions.
+    pub fn available_vm_permits(&self) -> usize {
+        self.0.vm_concurrency_limiter.available_permits()
+    }
+
     pub(crate) fn storage_caches(&self) -> PostgresStorageCaches {
         self.0.storage_caches.clone()
     }
 
     async fn acquire_replica_connection(&self) -> anyhow::Result<Connection<'_, Core>> {
-        self.0
-            .replica_connection_pool
-            .connection_tagged("api")
+        let timeout = self.0.sender_config.replica_connection_acquire_timeout;
+        acquire_connection(&self.0.replica_connection_pool, timeout)
             .await
             .context("failed acquiring connection to replica DB")
     }
 
+    /// Runs `action` and attributes storage cache hits / misses recorded while it was running to `call_type`.
+    async fn with_cache_stats<T>(
+        &self,
+        call_type: SandboxCallType,
+        action: impl Future<Output = T>,
+    ) -> T {
+        let stats_before = self.0.storage_caches.stats();
+        let output = action.await;
+        let stats = self.0.storage_caches.stats().since(stats_before);
+        SANDBOX_METRICS.storage_cache_hits[&call_type].inc_by(stats.hits);
+        SANDBOX_METRICS.storage_cache_misses[&call_type].inc_by(stats.misses);
+        output
+    }
+
+    /// Resolves arguments for the pending block. Callers making several sandbox calls per request can resolve
+    /// the pending block once and pass the returned args to [`Self::eth_call()`] and similar methods.
+    pub(crate) async fn pending_block_args(&self) -> Result<BlockArgs, SubmitTxError> {
+        let mut connection = self.acquire_replica_connection().await?;
+        let block_args = BlockArgs::pending(&mut connection).await?;
+        drop(connection);
+        self.observe_pending_block(&block_args).await?;
+        Ok(block_args)
+    }
+
+    /// Checks that the resolved pending block is not stale compared to the latest sealed miniblock
+    /// at the transaction destination and records it as observed.
+    async fn observe_pending_block(&self, block_args: &BlockArgs) -> Result<(), SubmitTxError> {
+        let pending_block_number = block_args.resolved_block_number();
+        if let Some(max_lag) = self.0.sender_config.max_pending_block_lag {
+            let latest_block_number = self.0.tx_sink.lookup_latest_sealed_miniblock().await?;
+            if let Some(latest_block_number) = latest_block_number {
+                // The pending block follows the latest miniblock sealed in the replica DB, so the replica lags
+                // by `latest_block_number + 1 - pending_block_number` miniblocks.
+                let lag = (latest_block_number.0 + 1).saturating_sub(pending_block_number.0);
+                if lag > max_lag {
+                    return Err(SubmitTxError::StaleNodeState {
+                        pending_block_number,
+                        latest_block_number,
+                    });
+                }
+            }
+        }
+        self.observe_miniblock(pending_block_number);
+        Ok(())
+    }
+
+    /// Records that the specified miniblock is sealed or pending, invalidating caches populated for older miniblocks.
+    fn observe_miniblock(&self, miniblock_number: MiniblockNumber) {
+        self.0
+            .last_observed_miniblock
+            .fetch_max(miniblock_number.0, Ordering::Relaxed);
+    }
+
+    /// Returns the newest miniblock observed via [`Self::observe_miniblock()`].
+    fn last_observed_miniblock(&self) -> MiniblockNumber {
+        MiniblockNumber(self.0.last_observed_miniblock.load(Ordering::Relaxed))
+    }
+
+    /// Same as [`Self::pending_block_args()`], but also resolves the protocol version for the pending block.
+    /// If there are no sealed miniblocks (e.g., right after snapshot recovery), the version is taken from
+    /// the snapshot recovery info; if there's no such info either, i.e. the storage is not initialized, an error
+    /// is returned.
+    async fn pending_block_args_and_protocol_version(
+        &self,
+    ) -> Result<(BlockArgs, ProtocolVersionId), SubmitTxError> {
+        let mut connection = self.acquire_replica_connection().await?;
+        let block_args = BlockArgs::pending(&mut connection).await?;
+        let protocol_version = pending_protocol_version(&mut connection)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed getting protocol version for pending miniblock #{}",
+                    block_args.resolved_block_number()
+                )
+            })?;
+        drop(connection);
+        self.observe_pending_block(&block_args).await?;
+        Ok((block_args, protocol_version))
+    }
+
     #[tracing::instrument(skip(self, tx))]
     pub async fn submit_tx(&self, tx: L2Tx) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        self.submit_tx_with_deadline(tx, None).await
+    }
+
+    /// Same as [`Self::submit_tx()`], but allows to specify the deadline for the transaction inclusion.
+    /// The deadline is persisted together with the transaction; the state keeper doesn't pick up transactions
+    /// past their deadline. Transactions that are already past their deadline are rejected.
+    pub async fn submit_tx_with_deadline(
+        &self,
+        tx: L2Tx,
+        valid_until: Option<DateTime<Utc>>,
+    ) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        let tx_hash = tx.hash();
+        let result = self
+            .with_cache_stats(
+                SandboxCallType::SubmitTx,
+                self.submit_tx_inner(tx, valid_until),
+            )
+            .await;
+        if let Err(err) = &result {
+            self.0.rejected_tx_sink.record(tx_hash, err);
+        }
+        result
+    }
+
+    async fn submit_tx_inner(
+        &self,
+        tx: L2Tx,
+        valid_until: Option<DateTime<Utc>>,
+    ) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        if let Some(valid_until) = valid_until {
+            if valid_until <= Utc::now() {
+                return Err(SubmitTxError::TransactionExpired(valid_until));
+            }
+        }
+
         let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::Validate].start();
         self.validate_tx(&tx).await?;
         stage_latency.observe();
 
-        let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::DryRun].start();
         let shared_args = self.shared_args().await;
         let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
         let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
-        let mut connection = self.acquire_replica_connection().await?;
-        let block_args = BlockArgs::pending(&mut connection).await?;
-        drop(connection);
-
-        let execution_output = self
-            .0
-            .executor
-            .execute_tx_in_sandbox(
-                vm_permit.clone(),
-                shared_args.clone(),
-                true,
-                TxExecutionArgs::for_validation(&tx),
-                self.0.replica_connection_pool.clone(),
-                tx.clone().into(),
-                block_args,
-                vec![],
-            )
+        let block_args = self.pending_block_args().await?;
+        let execution_metrics = self
+            .execute_and_validate_tx(&tx, shared_args, vm_permit, block_args)
             .await?;
-
         tracing::info!(
-            "Submit tx {:?} with execution metrics {:?}",
-            tx.hash(),
-            execution_output.metrics
+            "Submit tx {:?} with execution metrics {execution_metrics:?}",
+            tx.hash()
         );
-        stage_latency.observe();
-
-        let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::VerifyExecute].start();
-        let computational_gas_limit = self.0.sender_config.validation_computational_gas_limit;
-        let validation_result = self
-            .0
-            .executor
-            .validate_tx_in_sandbox(
-                self.0.replica_connection_pool.clone(),
-                vm_permit,
-                tx.clone(),
-                shared_args,
-                block_args,
-                computational_gas_limit,
-            )
-            .await;
-        stage_latency.observe();
-
-        if let Err(err) = validation_result {
-            return Err(err.into());
-        }
-        if !execution_output.are_published_bytecodes_ok {
-            return Err(SubmitTxError::FailedToPublishCompressedBytecodes);
-        }
 
         let stage_started_at = Instant::now();
-        self.ensure_tx_executable(tx.clone().into(), &execution_output.metrics, true)?;
+        self.ensure_tx_pubdata_within_limit(execution_metrics.pubdata_published.into())?;
+        self.ensure_tx_executable(tx.clone().into(), &execution_metrics, true)?;
 
         let nonce = tx.common_data.nonce.0;
         let hash = tx.hash();
@@ -349,12 +1047,14 @@ impl TxSender {
         let submission_res_handle = self
             .0
             .tx_sink
-            .submit_tx(tx, execution_output.metrics)
+            .submit_tx(tx, execution_metrics, valid_until)
             .await?;
 
+        // The match is intentionally exhaustive, so that a new submission result variant needs to be classified
+        // explicitly rather than being treated as success by default.
         match submission_res_handle {
             L2TxSubmissionResult::AlreadyExecuted => {
-                let Nonce(expected_nonce) = self
+                let (Nonce(expected_nonce), nonce_block_number) = self
                     .get_expected_nonce(initiator_account)
                     .await
                     .with_context(|| {
@@ -364,16 +1064,18 @@ impl TxSender {
                     expected_nonce,
                     expected_nonce + self.0.sender_config.max_nonce_ahead,
                     nonce,
+                    nonce_block_number,
                 ))
             }
-            L2TxSubmissionResult::Duplicate => Err(SubmitTxError::IncorrectTx(TxDuplication(hash))),
+            L2TxSubmissionResult::Duplicate => Err(self.duplicate_tx_error(hash).await?),
+            // Returned by `MasterPoolSink` if the same transaction is being submitted by a concurrent request.
             L2TxSubmissionResult::InsertionInProgress => Err(SubmitTxError::InsertionInProgress),
             L2TxSubmissionResult::Proxied => {
                 SANDBOX_METRICS.submit_tx[&SubmitTxStage::TxProxy]
                     .observe(stage_started_at.elapsed());
                 Ok(submission_res_handle)
             }
-            _ => {
+            L2TxSubmissionResult::Added | L2TxSubmissionResult::Replaced => {
                 SANDBOX_METRICS.submit_tx[&SubmitTxStage::DbInsert]
                     .observe(stage_started_at.elapsed());
                 Ok(submission_res_handle)
@@ -381,6 +1083,101 @@ impl TxSender {
         }
     }
 
+    /// Executes and validates a transaction in the sandbox, converting execution failures to submission errors.
+    async fn execute_and_validate_tx(
+        &self,
+        tx: &L2Tx,
+        shared_args: TxSharedArgs,
+        vm_permit: VmPermit,
+        block_args: BlockArgs,
+    ) -> Result<TransactionExecutionMetrics, SubmitTxError> {
+        let computational_gas_limit = self.0.sender_config.validation_computational_gas_limit;
+        let (execution_output, validation_result) = self
+            .0
+            .executor
+            .execute_and_validate_tx_in_sandbox(
+                self.0.replica_connection_pool.clone(),
+                vm_permit,
+                tx.clone(),
+                shared_args,
+                block_args,
+                computational_gas_limit,
+                self.0.sender_config.replica_connection_acquire_timeout,
+            )
+            .await?;
+
+        if is_execution_timed_out(&execution_output.vm.result) {
+            return Err(SubmitTxError::ExecutionTimedOut);
+        }
+        if let Err(err) = execution_output.published_bytecodes {
+            return Err(SubmitTxError::FailedToPublishCompressedBytecodes(err));
+        }
+        if let Some(Err(err)) = validation_result {
+            return Err(err.into());
+        }
+        Ok(execution_output.metrics)
+    }
+
+    /// Replays the checks performed on transaction submission (validation, sandbox execution and the executability
+    /// check) against the chain state at `at_block`, without submitting the transaction. Can be used to audit why
+    /// a historical transaction was accepted or rejected.
+    ///
+    /// For a historical block, the nonce, balance and protocol version are taken from the state after the block.
+    /// Checks depending on the mempool (e.g., nonce gaps) or the fee model (e.g., the minimum fee per gas) use
+    /// the current state since it's not persisted historically. For the pending block, the checks are equivalent
+    /// to the ones performed by [`Self::submit_tx()`].
+    pub(super) async fn audit_submission(&self, tx: L2Tx, at_block: BlockArgs) -> SubmissionAudit {
+        let block_number = at_block.resolved_block_number();
+        let (execution_metrics, rejection) = match self.audit_execution(&tx, at_block).await {
+            Ok(metrics) => {
+                let rejection = self
+                    .ensure_tx_pubdata_within_limit(metrics.pubdata_published.into())
+                    .and_then(|()| self.ensure_tx_executable(tx.into(), &metrics, false))
+                    .err();
+                (Some(metrics), rejection)
+            }
+            Err(err) => (None, Some(err)),
+        };
+        SubmissionAudit {
+            block_number,
+            execution_metrics,
+            rejection,
+        }
+    }
+
+    async fn audit_execution(
+        &self,
+        tx: &L2Tx,
+        at_block: BlockArgs,
+    ) -> Result<TransactionExecutionMetrics, SubmitTxError> {
+        let is_pending = at_block.block_id() == api::BlockId::Number(api::BlockNumber::Pending);
+        let state_block_number = (!is_pending).then(|| at_block.resolved_block_number());
+        self.validate_tx_at(tx, state_block_number).await?;
+
+        let shared_args = self.shared_args().await;
+        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
+        let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
+        self.execute_and_validate_tx(tx, shared_args, vm_permit, at_block)
+            .await
+    }
+
+    /// Distinguishes a transaction resubmitted while it's still in the mempool (which clients can treat as success)
+    /// from other duplicates, e.g. of a transaction that was already rejected.
+    async fn duplicate_tx_error(&self, tx_hash: H256) -> anyhow::Result<SubmitTxError> {
+        let mut connection = self.acquire_replica_connection().await?;
+        let existing_tx = connection
+            .transactions_web3_dal()
+            .get_transaction_details(tx_hash)
+            .await
+            .with_context(|| format!("failed getting details for transaction {tx_hash:?}"))?;
+        Ok(match existing_tx {
+            Some(details) if matches!(details.status, api::TransactionStatus::Pending) => {
+                SubmitTxError::AlreadyInMempool(tx_hash)
+            }
+            _ => SubmitTxError::IncorrectTx(TxDuplication(tx_hash)),
+        })
+    }
+
     async fn shared_args(&self) -> TxSharedArgs {
         TxSharedArgs {
             operator_account: AccountTreeId::new(self.0.sender_config.fee_account_addr),
@@ -392,18 +1189,43 @@ impl TxSender {
                 .sender_config
                 .validation_computational_gas_limit,
             chain_id: self.0.sender_config.chain_id,
+            execution_timeout: self.0.sender_config.vm_execution_timeout,
         }
     }
 
     async fn validate_tx(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
-        let max_gas = U256::from(u32::MAX);
-        if tx.common_data.fee.gas_limit > max_gas
-            || tx.common_data.fee.gas_per_pubdata_limit > max_gas
+        self.validate_tx_at(tx, None).await
+    }
+
+    /// Validates a transaction against the state after `state_block_number`, or against the latest state
+    /// if it's not specified.
+    async fn validate_tx_at(
+        &self,
+        tx: &L2Tx,
+        state_block_number: Option<MiniblockNumber>,
+    ) -> Result<(), SubmitTxError> {
+        // `L2Tx` can technically hold a priority operation or a protocol upgrade type. Such transactions
+        // are never supposed to be submitted via the API; they are processed when received from L1.
+        let tx_type = tx.common_data.transaction_type;
+        if matches!(
+            tx_type,
+            TransactionType::PriorityOpTransaction | TransactionType::ProtocolUpgradeTransaction
+        ) {
+            return Err(SubmitTxError::UnsupportedTxType(tx_type));
+        }
+        let target = tx.recipient_account();
+        if self.0.sender_config.forbidden_tx_targets.contains(&target) {
+            return Err(SubmitTxError::ForbiddenTarget(target));
+        }
+        let initiator = tx.initiator_account();
+        if initiator == self.0.sender_config.fee_account_addr
+            && self.0.sender_config.fee_account_tx_policy == FeeAccountTxPolicy::Reject
         {
-            return Err(SubmitTxError::GasLimitIsTooBig);
+            return Err(SubmitTxError::InitiatorIsFeeAccount(initiator));
         }
 
-        let fee_input = self.0.batch_fee_input_provider.get_batch_fee_input().await;
+        Self::narrow_gas_value(tx.common_data.fee.gas_limit)?;
+        Self::narrow_gas_value(tx.common_data.fee.gas_per_pubdata_limit)?;
 
         // TODO (SMA-1715): do not subsidize the overhead for the transaction
 
@@ -415,13 +1237,17 @@ impl TxSender {
             );
             return Err(SubmitTxError::GasLimitIsTooBig);
         }
-        if tx.common_data.fee.max_fee_per_gas < fee_input.fair_l2_gas_price().into() {
+        let min_max_fee_per_gas = self.min_max_fee_per_gas().await;
+        if tx.common_data.fee.max_fee_per_gas < min_max_fee_per_gas.into() {
             tracing::info!(
                 "Submitted Tx is Unexecutable {:?} because of MaxFeePerGasTooLow {}",
                 tx.hash(),
                 tx.common_data.fee.max_fee_per_gas
             );
-            return Err(SubmitTxError::MaxFeePerGasTooLow);
+            return Err(SubmitTxError::MaxFeePerGasTooLow {
+                provided: tx.common_data.fee.max_fee_per_gas,
+                required: min_max_fee_per_gas,
+            });
         }
         if tx.common_data.fee.max_fee_per_gas < tx.common_data.fee.max_priority_fee_per_gas {
             tracing::info!(
@@ -437,8 +1263,15 @@ impl TxSender {
                 MAX_NEW_FACTORY_DEPS,
             ));
         }
+        Self::ensure_unique_factory_deps(tx)?;
+        if self.0.sender_config.early_signature_check {
+            self.ensure_valid_eoa_signature(tx).await?;
+        }
 
-        let intrinsic_consts = get_intrinsic_constants();
+        // Intrinsic constants depend on the bootloader, so they're taken for the version that will execute the tx.
+        let protocol_version = self.protocol_version_at(state_block_number).await?;
+        self.check_gas_per_pubdata_limit(tx, protocol_version)?;
+        let intrinsic_consts = get_intrinsic_constants(protocol_version.into());
         assert!(
             intrinsic_consts.l2_tx_intrinsic_pubdata == 0,
             "Currently we assume that the L2 transactions do not have any intrinsic pubdata"
@@ -450,86 +1283,380 @@ impl TxSender {
 
         // We still double-check the nonce manually
         // to make sure that only the correct nonce is submitted and the transaction's hashes never repeat
-        self.validate_account_nonce(tx).await?;
+        self.validate_account_nonce(tx, state_block_number).await?;
         // Even though without enough balance the tx will not pass anyway
         // we check the user for enough balance explicitly here for better DevEx.
-        self.validate_enough_balance(tx).await?;
+        self.validate_enough_balance(tx, state_block_number).await?;
         Ok(())
     }
 
-    async fn validate_account_nonce(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
-        let Nonce(expected_nonce) = self
-            .get_expected_nonce(tx.initiator_account())
+    /// Returns the protocol version of the miniblock with the specified number, or the pending protocol version
+    /// if the number is not specified.
+    async fn protocol_version_at(
+        &self,
+        block_number: Option<MiniblockNumber>,
+    ) -> anyhow::Result<ProtocolVersionId> {
+        let mut connection = self.acquire_replica_connection().await?;
+        let Some(block_number) = block_number else {
+            return pending_protocol_version(&mut connection)
+                .await
+                .context("failed getting pending protocol version");
+        };
+        let header = connection
+            .blocks_dal()
+            .get_miniblock_header(block_number)
+            .await
+            .with_context(|| format!("failed getting header of miniblock #{block_number}"))?
+            .with_context(|| format!("miniblock #{block_number} is not in storage"))?;
+        // Blocks without version specified are considered to be of `Version9`, same as in the sandbox.
+        Ok(header
+            .protocol_version
+            .unwrap_or(ProtocolVersionId::last_potentially_undefined()))
+    }
+
+    /// Checks that the transaction signature recovers to the transaction initiator, unless the initiator
+    /// is a custom account.
+    async fn ensure_valid_eoa_signature(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
+        let signed_message = tx.get_signed_bytes(self.0.sender_config.chain_id);
+        let recovered_signer = PackedEthSignature::deserialize_packed(&tx.common_data.signature)
+            .ok()
+            .and_then(|signature| signature.signature_recover_signer(&signed_message).ok());
+        if recovered_signer == Some(tx.initiator_account()) {
+            return Ok(());
+        }
+
+        // The account code is only read on a mismatch, so that valid transactions don't incur additional DB reads.
+        let initiator_account = tx.initiator_account();
+        let account_code_hash = self
+            .acquire_replica_connection()
+            .await?
+            .storage_web3_dal()
+            .get_value(&get_code_key(&initiator_account))
             .await
             .with_context(|| {
-                format!(
-                    "failed getting expected nonce for {:?}",
-                    tx.initiator_account()
-                )
+                format!("failed getting code hash for account {initiator_account:?}")
             })?;
-
-        if tx.common_data.nonce.0 < expected_nonce {
-            Err(SubmitTxError::NonceIsTooLow(
-                expected_nonce,
+        if account_code_hash != H256::zero() {
+            // Custom accounts define their own signature semantics, which are checked during validation.
+            return Ok(());
+        }
+        tracing::info!(
+            "Submitted Tx is Unexecutable {:?} because of InvalidSignature",
+            tx.hash()
+        );
+        Err(SubmitTxError::InvalidSignature)
+    }
+
+    /// Applies [`TxSenderConfig::excessive_gas_per_pubdata_policy`] if the transaction `gas_per_pubdata_limit`
+    /// exceeds the maximum supported by the VM.
+    fn check_gas_per_pubdata_limit(
+        &self,
+        tx: &L2Tx,
+        protocol_version: ProtocolVersionId,
+    ) -> Result<(), SubmitTxError> {
+        let gas_per_pubdata_limit = tx.common_data.fee.gas_per_pubdata_limit;
+        let max_gas_per_pubdata = get_max_gas_per_pubdata_byte(protocol_version.into());
+        if gas_per_pubdata_limit <= max_gas_per_pubdata.into() {
+            return Ok(());
+        }
+
+        match self.0.sender_config.excessive_gas_per_pubdata_policy {
+            ExcessiveGasPerPubdataPolicy::Clamp => {
+                tracing::warn!(
+                    "Tx {:?} has gas per pubdata limit {gas_per_pubdata_limit} exceeding the maximum {max_gas_per_pubdata}; \
+                     it will be clamped",
+                    tx.hash()
+                );
+                Ok(())
+            }
+            ExcessiveGasPerPubdataPolicy::Reject => Err(SubmitTxError::GasPerPubdataLimitTooHigh(
+                gas_per_pubdata_limit,
+                max_gas_per_pubdata,
+            )),
+        }
+    }
+
+    /// Rejects transactions repeating the same bytecode in factory deps. Duplicates would inflate pubdata estimation
+    /// and count towards [`MAX_NEW_FACTORY_DEPS`] without any effect on execution.
+    fn ensure_unique_factory_deps(tx: &L2Tx) -> Result<(), SubmitTxError> {
+        let Some(factory_deps) = &tx.execute.factory_deps else {
+            return Ok(());
+        };
+        let mut unique_deps = HashSet::with_capacity(factory_deps.len());
+        for (i, dep) in factory_deps.iter().enumerate() {
+            if !unique_deps.insert(dep.as_slice()) {
+                return Err(SubmitTxError::DuplicateFactoryDep(i));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the amount minted by an L1 transaction covers its maximum cost. Unlike L2 transactions,
+    /// L1 ones don't draw funds from the sender's L2 balance, so a malformed L1 transaction would only fail in the VM.
+    fn validate_l1_tx_mint(common_data: &L1TxCommonData, value: U256) -> Result<(), SubmitTxError> {
+        let required_funds = common_data
+            .gas_limit
+            .checked_mul(common_data.max_fee_per_gas)
+            .and_then(|fee| fee.checked_add(value));
+        match required_funds {
+            Some(required_funds) if common_data.to_mint >= required_funds => Ok(()),
+            _ => Err(SubmitTxError::InsufficientMintForL1Tx(
+                common_data.to_mint,
+                required_funds.unwrap_or(U256::MAX),
+            )),
+        }
+    }
+
+    async fn validate_account_nonce(
+        &self,
+        tx: &L2Tx,
+        state_block_number: Option<MiniblockNumber>,
+    ) -> Result<(), SubmitTxError> {
+        let sealed_nonce = match state_block_number {
+            Some(block_number) => {
+                let mut storage = self.acquire_replica_connection().await?;
+                Self::get_nonce_at(&mut storage, tx.initiator_account(), block_number)
+                    .await
+                    .map(|nonce| (nonce, block_number))
+            }
+            None => self.get_expected_nonce(tx.initiator_account()).await,
+        };
+        let (sealed_nonce, nonce_block_number) = sealed_nonce.with_context(|| {
+            format!(
+                "failed getting expected nonce for {:?}",
+                tx.initiator_account()
+            )
+        })?;
+        let Nonce(expected_nonce) = match self.0.sender_config.expected_nonce_policy {
+            ExpectedNoncePolicy::Sealed => sealed_nonce,
+            ExpectedNoncePolicy::Pending => {
+                self.get_pending_nonce(tx.initiator_account(), sealed_nonce)
+                    .await?
+            }
+        };
+
+        if tx.common_data.nonce.0 < expected_nonce {
+            Err(SubmitTxError::NonceIsTooLow(
+                expected_nonce,
                 expected_nonce + self.0.sender_config.max_nonce_ahead,
                 tx.nonce().0,
+                nonce_block_number,
             ))
         } else {
             let max_nonce = expected_nonce + self.0.sender_config.max_nonce_ahead;
-            if !(expected_nonce..=max_nonce).contains(&tx.common_data.nonce.0) {
-                Err(SubmitTxError::NonceIsTooHigh(
+            if (expected_nonce..=max_nonce).contains(&tx.common_data.nonce.0) {
+                return Ok(());
+            }
+
+            let Nonce(pending_nonce) = self
+                .get_pending_nonce(tx.initiator_account(), Nonce(expected_nonce))
+                .await?;
+            let grace_max_nonce =
+                max_nonce.saturating_add(self.0.sender_config.max_nonce_ahead_grace);
+            if tx.common_data.nonce.0 <= grace_max_nonce && pending_nonce >= tx.common_data.nonce.0
+            {
+                // All preceding nonces are pending, so the transaction can be executed right after them.
+                return Ok(());
+            }
+
+            if pending_nonce <= max_nonce {
+                // The user can fill the gap by submitting transactions starting from `pending_nonce`.
+                Err(SubmitTxError::NonceGap(
                     expected_nonce,
                     max_nonce,
                     tx.nonce().0,
+                    pending_nonce,
                 ))
             } else {
-                Ok(())
+                Err(SubmitTxError::NonceIsTooHigh(
+                    expected_nonce,
+                    max_nonce,
+                    tx.nonce().0,
+                    nonce_block_number,
+                ))
             }
         }
     }
 
-    async fn get_expected_nonce(&self, initiator_account: Address) -> anyhow::Result<Nonce> {
+    /// Returns the first nonce of `initiator_account` starting from `committed_nonce` that has no
+    /// corresponding transaction in the mempool (or the sink-specific storage).
+    async fn get_pending_nonce(
+        &self,
+        initiator_account: Address,
+        committed_nonce: Nonce,
+    ) -> anyhow::Result<Nonce> {
+        let sink_nonce = self
+            .0
+            .tx_sink
+            .lookup_pending_nonce(initiator_account, committed_nonce.0)
+            .await
+            .context("failed looking up pending nonce in tx sink")?;
+        if let Some(nonce) = sink_nonce {
+            return Ok(nonce);
+        }
+
+        let mut storage = self.acquire_replica_connection().await?;
+        let pending_nonce = storage
+            .transactions_web3_dal()
+            .next_nonce_by_initiator_account(initiator_account, committed_nonce.0.into())
+            .await
+            .with_context(|| format!("failed getting pending nonce for {initiator_account:?}"))?;
+        Ok(Nonce(pending_nonce.as_u32()))
+    }
+
+    /// Reads the expected nonce for the account, retrying on transient DB errors as configured
+    /// in [`TxSenderConfig`]. Returns the nonce together with the miniblock it was read at.
+    async fn get_expected_nonce(
+        &self,
+        initiator_account: Address,
+    ) -> anyhow::Result<(Nonce, MiniblockNumber)> {
+        self.with_nonce_read_retries(&[initiator_account], || {
+            self.get_expected_nonce_once(initiator_account)
+        })
+        .await
+    }
+
+    async fn get_expected_nonce_once(
+        &self,
+        initiator_account: Address,
+    ) -> anyhow::Result<(Nonce, MiniblockNumber)> {
+        let mut storage = self.acquire_replica_connection().await?;
+        let latest_block_number = Self::latest_block_for_nonces(&mut storage).await?;
+        let nonce =
+            Self::get_nonce_at(&mut storage, initiator_account, latest_block_number).await?;
+        Ok((nonce, latest_block_number))
+    }
+
+    /// Same as [`Self::get_expected_nonce()`], but reads nonces for multiple accounts in a single DB query.
+    /// The returned map contains all `accounts`.
+    pub async fn get_expected_nonces(
+        &self,
+        accounts: &[Address],
+    ) -> anyhow::Result<HashMap<Address, Nonce>> {
+        self.with_nonce_read_retries(accounts, || self.get_expected_nonces_once(accounts))
+            .await
+    }
+
+    async fn get_expected_nonces_once(
+        &self,
+        accounts: &[Address],
+    ) -> anyhow::Result<HashMap<Address, Nonce>> {
         let mut storage = self.acquire_replica_connection().await?;
+        let latest_block_number = Self::latest_block_for_nonces(&mut storage).await?;
+        storage
+            .storage_web3_dal()
+            .get_historical_nonces_for_addresses(accounts, latest_block_number)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed getting nonces for {} addresses at miniblock #{latest_block_number}",
+                    accounts.len()
+                )
+            })
+    }
+
+    /// Retries `read` on transient DB errors as configured in [`TxSenderConfig`].
+    async fn with_nonce_read_retries<T, F, Fut>(
+        &self,
+        accounts: &[Address],
+        read: F,
+    ) -> anyhow::Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let config = &self.0.sender_config;
+        let mut retries_left = config.nonce_read_retries;
+        loop {
+            match read().await {
+                Err(err) if retries_left > 0 && is_transient_db_error(&err) => {
+                    retries_left -= 1;
+                    tracing::warn!(
+                        "Transient DB error reading nonces for addresses {accounts:?}, retrying in {:?} \
+                         ({retries_left} retries left): {err:#}",
+                        config.nonce_read_retry_interval
+                    );
+                    tokio::time::sleep(config.nonce_read_retry_interval).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns the miniblock expected nonces are read at, i.e. the latest sealed miniblock.
+    async fn latest_block_for_nonces(
+        storage: &mut Connection<'_, Core>,
+    ) -> anyhow::Result<MiniblockNumber> {
         let latest_block_number = storage
             .blocks_dal()
             .get_sealed_miniblock_number()
             .await
             .context("failed getting sealed miniblock number")?;
-        let latest_block_number = match latest_block_number {
+        Ok(match latest_block_number {
             Some(number) => number,
             None => {
                 // We don't have miniblocks in the storage yet. Use the snapshot miniblock number instead.
-                let start = BlockStartInfo::new(&mut storage).await?;
+                let start = BlockStartInfo::new(storage).await?;
                 MiniblockNumber(start.first_miniblock.saturating_sub(1))
             }
-        };
+        })
+    }
 
+    async fn get_nonce_at(
+        storage: &mut Connection<'_, Core>,
+        initiator_account: Address,
+        block_number: MiniblockNumber,
+    ) -> anyhow::Result<Nonce> {
         let nonce = storage
             .storage_web3_dal()
-            .get_address_historical_nonce(initiator_account, latest_block_number)
+            .get_address_historical_nonce(initiator_account, block_number)
             .await
             .with_context(|| {
-                format!("failed getting nonce for address {initiator_account:?} at miniblock #{latest_block_number}")
+                format!("failed getting nonce for address {initiator_account:?} at miniblock #{block_number}")
             })?;
         let nonce = u32::try_from(nonce)
             .map_err(|err| anyhow::anyhow!("failed converting nonce to u32: {err}"))?;
         Ok(Nonce(nonce))
     }
 
-    async fn validate_enough_balance(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
+    /// Narrows a gas value (e.g., a gas limit) to `u32`, which is used for gas computations in the VM.
+    fn narrow_gas_value<T: TryInto<u32>>(value: T) -> Result<u32, SubmitTxError> {
+        value
+            .try_into()
+            .map_err(|_| SubmitTxError::GasLimitIsTooBig)
+    }
+
+    async fn validate_enough_balance(
+        &self,
+        tx: &L2Tx,
+        state_block_number: Option<MiniblockNumber>,
+    ) -> Result<(), SubmitTxError> {
         let paymaster = tx.common_data.paymaster_params.paymaster;
         // The paymaster is expected to pay for the tx; whatever balance the user has, we don't care.
         if paymaster != Address::default() {
             return Ok(());
         }
 
-        let balance = self.get_balance(&tx.common_data.initiator_address).await?;
+        let initiator_address = &tx.common_data.initiator_address;
+        let balance = match state_block_number {
+            Some(block_number) => {
+                self.get_historical_balance(initiator_address, block_number)
+                    .await?
+            }
+            None => self.get_balance(initiator_address).await?,
+        };
         // Estimate the minimum fee price user will agree to.
         let gas_price = tx.common_data.fee.max_fee_per_gas;
-        let max_fee = tx.common_data.fee.gas_limit * gas_price;
-        let max_fee_and_value = max_fee + tx.execute.value;
-
-        if balance < max_fee_and_value {
+        let max_fee = tx
+            .common_data
+            .fee
+            .gas_limit
+            .checked_mul(gas_price)
+            .ok_or(SubmitTxError::GasLimitIsTooBig)?;
+        // If the sum overflows, no balance can cover it.
+        let max_fee_and_value = max_fee.checked_add(tx.execute.value);
+
+        if max_fee_and_value.map_or(true, |max_fee_and_value| balance < max_fee_and_value) {
             Err(SubmitTxError::NotEnoughBalanceForFeeValue(
                 balance,
                 max_fee,
@@ -541,6 +1668,17 @@ impl TxSender {
     }
 
     async fn get_balance(&self, initiator_address: &H160) -> anyhow::Result<U256> {
+        let balance_cache = &self.0.fee_account_balance_cache;
+        let miniblock_number = self.last_observed_miniblock();
+        if let Some(balance) = balance_cache.get(*initiator_address, miniblock_number) {
+            return Ok(balance);
+        }
+        let balance = self.get_balance_uncached(initiator_address).await?;
+        balance_cache.insert(*initiator_address, balance, miniblock_number);
+        Ok(balance)
+    }
+
+    async fn get_balance_uncached(&self, initiator_address: &H160) -> anyhow::Result<U256> {
         let eth_balance_key = storage_key_for_eth_balance(initiator_address);
         let balance = self
             .acquire_replica_connection()
@@ -551,6 +1689,41 @@ impl TxSender {
         Ok(h256_to_u256(balance))
     }
 
+    /// Returns the initiator balance used by the VM during gas estimation. If the initiator is the fee account,
+    /// its balance is affected by [`GasEstimationOptions::operator_balance_override`].
+    async fn initiator_balance_for_estimate(
+        &self,
+        tx: &Transaction,
+        options: &GasEstimationOptions,
+    ) -> anyhow::Result<U256> {
+        let initiator = tx.initiator_account();
+        match options.operator_balance_override {
+            Some(balance) if initiator == self.0.sender_config.fee_account_addr => Ok(balance),
+            _ => self.get_balance(&initiator).await,
+        }
+    }
+
+    /// Reads the balance bypassing the cache, which only holds the latest balances.
+    async fn get_historical_balance(
+        &self,
+        initiator_address: &H160,
+        block_number: MiniblockNumber,
+    ) -> anyhow::Result<U256> {
+        let eth_balance_key = storage_key_for_eth_balance(initiator_address);
+        let balance = self
+            .acquire_replica_connection()
+            .await?
+            .storage_web3_dal()
+            .get_historical_value_unchecked(&eth_balance_key, block_number)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed getting balance for {initiator_address:?} at miniblock #{block_number}"
+                )
+            })?;
+        Ok(h256_to_u256(balance))
+    }
+
     /// Given the gas_limit to be used for the body of the transaction,
     /// returns the result for executing the transaction with such gas_limit
     #[allow(clippy::too_many_arguments)]
@@ -564,15 +1737,21 @@ impl TxSender {
         block_args: BlockArgs,
         base_fee: u64,
         vm_version: VmVersion,
+        preceding_state: Option<&Arc<PrecedingTxsState>>,
+        operator_balance_override: Option<U256>,
+        base_system_contracts: &MultiVMBaseSystemContracts,
+        custom_tracers: Vec<ApiTracer>,
     ) -> anyhow::Result<(VmExecutionResultAndLogs, TransactionExecutionMetrics)> {
-        let gas_limit_with_overhead = tx_gas_limit
-            + derive_overhead(
-                tx_gas_limit,
-                gas_price_per_pubdata,
-                tx.encoding_len(),
-                tx.tx_format() as u8,
-                vm_version,
-            );
+        let overhead = derive_overhead(
+            tx_gas_limit,
+            gas_price_per_pubdata,
+            tx.encoding_len(),
+            tx.tx_format() as u8,
+            vm_version,
+        );
+        let gas_limit_with_overhead = tx_gas_limit.checked_add(overhead).with_context(|| {
+            format!("gas limit {tx_gas_limit} with overhead {overhead} doesn't fit into u32")
+        })?;
 
         match &mut tx.common_data {
             ExecuteTransactionCommon::L1(l1_common_data) => {
@@ -594,10 +1773,17 @@ impl TxSender {
             }
         }
 
-        let shared_args = self.shared_args_for_gas_estimate(fee_model_params);
+        let shared_args =
+            self.shared_args_for_gas_estimate(fee_model_params, base_system_contracts.clone());
         let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
-        let execution_args =
+        let mut execution_args =
             TxExecutionArgs::for_gas_estimate(vm_execution_cache_misses_limit, &tx, base_fee);
+        execution_args.operator_balance = operator_balance_override;
+        if let Some(state) = preceding_state {
+            // Nonces are updated by the preceding transactions, so they must not be overwritten.
+            execution_args.enforced_nonce = None;
+            execution_args.preceding_state = Some(state.clone());
+        }
         let execution_output = self
             .0
             .executor
@@ -609,13 +1795,73 @@ impl TxSender {
                 self.0.replica_connection_pool.clone(),
                 tx.clone(),
                 block_args,
-                vec![],
+                custom_tracers,
             )
             .await?;
         Ok((execution_output.vm, execution_output.metrics))
     }
 
-    fn shared_args_for_gas_estimate(&self, fee_input: BatchFeeInput) -> TxSharedArgs {
+    /// Executes transactions preceding the estimated one and captures the resulting state. The state is reused
+    /// on all estimation steps, so that preceding transactions aren't re-executed on each step.
+    ///
+    /// Each preceding transaction is executed in the same way as the final step of its own gas estimation.
+    async fn execute_preceding_txs(
+        &self,
+        vm_permit: &VmPermit,
+        preceding_txs: &[Transaction],
+        fee_input: BatchFeeInput,
+        block_args: BlockArgs,
+        base_fee: u64,
+        operator_balance_override: Option<U256>,
+    ) -> Result<Arc<PrecedingTxsState>, SubmitTxError> {
+        let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
+        let mut state = Arc::new(PrecedingTxsState::default());
+        for (i, tx) in preceding_txs.iter().enumerate() {
+            let shared_args = self
+                .shared_args_for_gas_estimate(fee_input, self.0.api_contracts.estimate_gas.clone());
+            let mut execution_args =
+                TxExecutionArgs::for_gas_estimate(vm_execution_cache_misses_limit, tx, base_fee);
+            // Unlike the estimated transaction, preceding transactions must pay fees from the actual balance
+            // of the payer; otherwise, the minted balance would leak into the state observed by subsequent transactions.
+            execution_args.added_balance = U256::zero();
+            execution_args.operator_balance = operator_balance_override;
+            execution_args.preceding_state = Some(state.clone());
+            let output = self
+                .0
+                .executor
+                .execute_tx_in_sandbox(
+                    vm_permit.clone(),
+                    shared_args,
+                    true,
+                    execution_args,
+                    self.0.replica_connection_pool.clone(),
+                    tx.clone(),
+                    block_args,
+                    vec![],
+                )
+                .await
+                .with_context(|| format!("failed executing preceding transaction #{i}"))?;
+
+            let failure = match &output.vm.result {
+                ExecutionResult::Success { .. } => None,
+                ExecutionResult::Revert { output } => Some(output.to_user_friendly_string()),
+                ExecutionResult::Halt { reason } => Some(reason.to_string()),
+            };
+            if let Some(failure) = failure {
+                tracing::info!("Preceding transaction #{i} failed: {failure}");
+                return Err(SubmitTxError::PrecedingTxFailed(i, failure));
+            }
+            // The execution args holding a reference to the state are dropped at this point, so the state isn't cloned.
+            Arc::make_mut(&mut state).apply_tx(tx, &output.vm);
+        }
+        Ok(state)
+    }
+
+    fn shared_args_for_gas_estimate(
+        &self,
+        fee_input: BatchFeeInput,
+        base_system_contracts: MultiVMBaseSystemContracts,
+    ) -> TxSharedArgs {
         let config = &self.0.sender_config;
 
         TxSharedArgs {
@@ -623,39 +1869,287 @@ impl TxSender {
             fee_input,
             // We want to bypass the computation gas limit check for gas estimation
             validation_computational_gas_limit: BLOCK_GAS_LIMIT,
-            base_system_contracts: self.0.api_contracts.estimate_gas.clone(),
+            base_system_contracts,
             caches: self.storage_caches(),
             chain_id: config.chain_id,
+            execution_timeout: config.vm_execution_timeout,
         }
     }
 
     pub async fn get_txs_fee_in_wei(
         &self,
-        mut tx: Transaction,
+        tx: Transaction,
         estimated_fee_scale_factor: f64,
         acceptable_overestimation: u32,
+        options: &GasEstimationOptions,
     ) -> Result<Fee, SubmitTxError> {
-        let estimation_started_at = Instant::now();
+        let estimate = self
+            .estimate_gas(
+                tx,
+                estimated_fee_scale_factor,
+                acceptable_overestimation,
+                options,
+            )
+            .await?;
+        Ok(estimate.fee)
+    }
 
-        let mut connection = self.acquire_replica_connection().await?;
-        let block_args = BlockArgs::pending(&mut connection).await?;
-        let protocol_version = pending_protocol_version(&mut connection)
+    /// Same as [`Self::get_txs_fee_in_wei()`], but also returns the split of the estimated gas
+    /// between publishing bytecodes on L1 and executing the transaction on L2, and the unscaled gas limit
+    /// found by the binary search.
+    pub async fn estimate_gas(
+        &self,
+        tx: Transaction,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u32,
+        options: &GasEstimationOptions,
+    ) -> Result<GasEstimate, SubmitTxError> {
+        let estimation = self.estimate_gas_after_txs(
+            tx,
+            estimated_fee_scale_factor,
+            acceptable_overestimation,
+            options,
+            &[],
+            None,
+            vec![],
+        );
+        self.with_cache_stats(SandboxCallType::EstimateGas, estimation)
             .await
-            .context("failed getting pending protocol version")?;
-        drop(connection);
+    }
 
-        let fee_input = {
-            // For now, both L1 gas price and pubdata price are scaled with the same coefficient
-            let fee_input = self
-                .0
-                .batch_fee_input_provider
-                .get_batch_fee_input_scaled(
-                    self.0.sender_config.gas_price_scale_factor,
-                    self.0.sender_config.gas_price_scale_factor,
-                )
+    /// Same as [`Self::estimate_gas()`], but also returns the call trace of the transaction executed with
+    /// the estimated gas limit. Intended for diagnostics; the final estimation step is executed once more
+    /// with the call tracer attached, so this is more expensive than the untraced estimation.
+    pub async fn estimate_gas_with_call_trace(
+        &self,
+        tx: Transaction,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u32,
+        options: &GasEstimationOptions,
+    ) -> Result<(GasEstimate, Vec<Call>), SubmitTxError> {
+        let call_trace = Arc::new(OnceCell::default());
+        let estimation = self.estimate_gas_after_txs(
+            tx,
+            estimated_fee_scale_factor,
+            acceptable_overestimation,
+            options,
+            &[],
+            None,
+            vec![ApiTracer::CallTracer(call_trace.clone())],
+        );
+        let estimate = self
+            .with_cache_stats(SandboxCallType::EstimateGas, estimation)
+            .await?;
+        let call_trace = call_trace.get().cloned().unwrap_or_default();
+        Ok((estimate, call_trace))
+    }
+
+    /// Same as [`Self::get_txs_fee_in_wei()`], but reports each binary search iteration as it's performed,
+    /// e.g. to visualize convergence of the estimation in developer tooling. The last item of the returned stream
+    /// is always [`EstimationStep::Finished`] with the estimation result.
+    pub fn estimate_gas_steps(
+        &self,
+        tx: Transaction,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u32,
+        options: GasEstimationOptions,
+    ) -> impl Stream<Item = EstimationStep> + Send + 'static {
+        let (steps_sender, steps_receiver) = mpsc::unbounded();
+        let this = self.clone();
+        let estimation = async move {
+            let estimation = this.estimate_gas_after_txs(
+                tx,
+                estimated_fee_scale_factor,
+                acceptable_overestimation,
+                &options,
+                &[],
+                Some(&steps_sender),
+                vec![],
+            );
+            let result = this
+                .with_cache_stats(SandboxCallType::EstimateGas, estimation)
                 .await;
+            // The receiver may be dropped if the stream is not polled to completion, which is fine.
+            steps_sender
+                .unbounded_send(EstimationStep::Finished(
+                    result.map(|estimate| estimate.fee),
+                ))
+                .ok();
+            // `steps_sender` is dropped here, which terminates `steps_receiver` once all steps are consumed.
+        };
+        // The estimation future doesn't yield any items itself; it's polled together with the receiver
+        // so that the estimation progresses while the stream is consumed.
+        let estimation = stream::once(estimation).filter_map(|()| future::ready(None));
+        stream::select(estimation, steps_receiver)
+    }
+
+    /// Estimates fees for a bundle of L2 transactions (e.g., user operations from different senders) executed
+    /// sequentially in a single block. Each transaction is estimated in the state produced by the preceding
+    /// transactions in the bundle, with their gas limits set to estimated values.
+    ///
+    /// Unlike estimating transactions one by one, this checks that the nonce of each transaction is the next one
+    /// for its sender, taking into account preceding transactions in the bundle.
+    pub async fn estimate_bundle(
+        &self,
+        txs: Vec<Transaction>,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u32,
+        options: &GasEstimationOptions,
+    ) -> Result<Vec<Fee>, SubmitTxError> {
+        let mut expected_nonces = HashMap::new();
+        for tx in &txs {
+            let ExecuteTransactionCommon::L2(common_data) = &tx.common_data else {
+                return Err(SubmitTxError::UnsupportedTxType(tx.tx_format()));
+            };
+            let initiator = common_data.initiator_address;
+            let (expected_nonce, nonce_block_number) = match expected_nonces.entry(initiator) {
+                hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                hash_map::Entry::Vacant(entry) => {
+                    let (nonce, block_number) =
+                        self.get_expected_nonce(initiator).await.with_context(|| {
+                            format!("failed getting expected nonce for {initiator:?}")
+                        })?;
+                    entry.insert((nonce.0, block_number))
+                }
+            };
+
+            let nonce = common_data.nonce.0;
+            if nonce < *expected_nonce {
+                return Err(SubmitTxError::NonceIsTooLow(
+                    *expected_nonce,
+                    *expected_nonce,
+                    nonce,
+                    *nonce_block_number,
+                ));
+            } else if nonce > *expected_nonce {
+                return Err(SubmitTxError::NonceIsTooHigh(
+                    *expected_nonce,
+                    *expected_nonce,
+                    nonce,
+                    *nonce_block_number,
+                ));
+            }
+            *expected_nonce += 1;
+        }
+
+        let mut fees = Vec::with_capacity(txs.len());
+        let mut preceding_txs = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let estimation = self.estimate_gas_after_txs(
+                tx.clone(),
+                estimated_fee_scale_factor,
+                acceptable_overestimation,
+                options,
+                &preceding_txs,
+                None,
+                vec![],
+            );
+            let estimate = self
+                .with_cache_stats(SandboxCallType::EstimateGas, estimation)
+                .await?;
+            preceding_txs.push(Self::with_estimated_fee(tx, &estimate.fee));
+            fees.push(estimate.fee);
+        }
+        Ok(fees)
+    }
+
+    fn report_estimation_step(
+        steps_sender: Option<&EstimationStepsSender>,
+        gas_limit: u32,
+        result: &ExecutionResult,
+    ) {
+        if let Some(sender) = steps_sender {
+            let step = EstimationStep::Iteration {
+                gas_limit,
+                result: result.clone(),
+            };
+            // The stream consumer may have lost interest in steps; this doesn't influence the estimation.
+            sender.unbounded_send(step).ok();
+        }
+    }
+
+    fn with_estimated_fee(mut tx: Transaction, fee: &Fee) -> Transaction {
+        if let ExecuteTransactionCommon::L2(common_data) = &mut tx.common_data {
+            common_data.fee = fee.clone();
+            if common_data.signature.is_empty() {
+                common_data.signature = PackedEthSignature::default().serialize_packed().into();
+            }
+        }
+        tx
+    }
+
+    async fn estimate_gas_after_txs(
+        &self,
+        tx: Transaction,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u32,
+        options: &GasEstimationOptions,
+        preceding_txs: &[Transaction],
+        steps_sender: Option<&EstimationStepsSender>,
+        final_step_tracers: Vec<ApiTracer>,
+    ) -> Result<GasEstimate, SubmitTxError> {
+        let result = self
+            .estimate_gas_after_txs_inner(
+                tx,
+                estimated_fee_scale_factor,
+                acceptable_overestimation,
+                options,
+                preceding_txs,
+                steps_sender,
+                final_step_tracers,
+            )
+            .await;
+        if let Err(err) = &result {
+            SANDBOX_METRICS.estimate_gas_failures[&err.prom_error_code()].inc();
+        }
+        result
+    }
+
+    async fn estimate_gas_after_txs_inner(
+        &self,
+        mut tx: Transaction,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u32,
+        options: &GasEstimationOptions,
+        preceding_txs: &[Transaction],
+        steps_sender: Option<&EstimationStepsSender>,
+        final_step_tracers: Vec<ApiTracer>,
+    ) -> Result<GasEstimate, SubmitTxError> {
+        let estimation_started_at = Instant::now();
+        let estimated_fee_scale_factor =
+            self.clamp_estimated_fee_scale_factor(estimated_fee_scale_factor);
+        // `estimate_gas_step()` overwrites `to_mint` with the amount required for each tried gas limit,
+        // so the mint supplied by the user must be checked before any VM execution.
+        if let ExecuteTransactionCommon::L1(common_data) = &tx.common_data {
+            Self::validate_l1_tx_mint(common_data, tx.execute.value)?;
+        }
+        for preceding_tx in preceding_txs {
+            if let ExecuteTransactionCommon::L1(common_data) = &preceding_tx.common_data {
+                Self::validate_l1_tx_mint(common_data, preceding_tx.execute.value)?;
+            }
+        }
+
+        let (block_args, protocol_version) = self.pending_block_args_and_protocol_version().await?;
+        SANDBOX_METRICS.estimate_gas_protocol_versions[&(protocol_version as u16)].inc();
+
+        // For now, both L1 gas price and pubdata price are scaled with the same coefficient
+        let unadjusted_fee_input = self
+            .0
+            .batch_fee_input_provider
+            .get_batch_fee_input_scaled(
+                self.0.sender_config.gas_price_scale_factor,
+                self.0.sender_config.gas_price_scale_factor,
+            )
+            .await;
+        let unadjusted_fee_input = match options.l1_gas_price_override {
+            Some(l1_gas_price) => Self::override_l1_gas_price(unadjusted_fee_input, l1_gas_price),
+            None => unadjusted_fee_input,
+        };
+        let fee_input = if options.skip_pubdata_price_adjustment {
+            unadjusted_fee_input
+        } else {
             adjust_pubdata_price_for_tx(
-                fee_input,
+                unadjusted_fee_input,
                 tx.gas_per_pubdata_byte_limit(),
                 // We do not have to adjust the params to the `gasPrice` of the transaction, since
                 // its gas price will be amended later on to suit the `fee_input`
@@ -663,13 +2157,20 @@ impl TxSender {
                 protocol_version.into(),
             )
         };
+        let pubdata_price_adjustment =
+            PubdataPriceAdjustment::new(unadjusted_fee_input, fee_input, protocol_version.into());
 
         let (base_fee, gas_per_pubdata_byte) =
             derive_base_fee_and_gas_per_pubdata(fee_input, protocol_version.into());
+        let gas_per_pubdata_byte = self.apply_min_gas_per_pubdata(gas_per_pubdata_byte);
+        let gas_per_pubdata_byte = Self::narrow_gas_value(gas_per_pubdata_byte)?;
         match &mut tx.common_data {
             ExecuteTransactionCommon::L2(common_data) => {
                 common_data.fee.max_fee_per_gas = base_fee.into();
                 common_data.fee.max_priority_fee_per_gas = base_fee.into();
+                if let Some(nonce) = options.nonce_override {
+                    common_data.nonce = nonce;
+                }
             }
             ExecuteTransactionCommon::L1(common_data) => {
                 common_data.max_fee_per_gas = base_fee.into();
@@ -697,7 +2198,7 @@ impl TxSender {
 
         if !tx.is_l1()
             && account_code_hash == H256::zero()
-            && tx.execute.value > self.get_balance(&tx.initiator_account()).await?
+            && tx.execute.value > self.initiator_balance_for_estimate(&tx, options).await?
         {
             tracing::info!(
                 "fee estimation failed on validation step.
@@ -708,17 +2209,49 @@ impl TxSender {
             return Err(SubmitTxError::InsufficientFundsForTransfer);
         }
 
+        let create2_address = self.check_create2_deployment(&tx).await?;
+
         // For L2 transactions we need a properly formatted signature
         if let ExecuteTransactionCommon::L2(l2_common_data) = &mut tx.common_data {
+            if let Some(signature) = &options.signature_override {
+                l2_common_data.signature = signature.clone();
+            }
             if l2_common_data.signature.is_empty() {
                 l2_common_data.signature = PackedEthSignature::default().serialize_packed().into();
             }
         }
 
+        // The estimation permit is acquired before the VM one, so that estimations throttled by the estimation limiter
+        // don't occupy VM permits.
+        let _estimation_permit = match &self.0.estimation_limiter {
+            Some(limiter) => Some(
+                limiter
+                    .acquire()
+                    .await
+                    .map_err(|_| SubmitTxError::ServerShuttingDown)?,
+            ),
+            None => None,
+        };
         // Acquire the vm token for the whole duration of the binary search.
         let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
         let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
 
+        let preceding_state = if preceding_txs.is_empty() {
+            None
+        } else {
+            let state = self
+                .execute_preceding_txs(
+                    &vm_permit,
+                    preceding_txs,
+                    fee_input,
+                    block_args,
+                    base_fee,
+                    options.operator_balance_override,
+                )
+                .await?;
+            Some(state)
+        };
+
         // We already know how many gas is needed to cover for the publishing of the bytecodes.
         // For L1->L2 transactions all the bytecodes have been made available on L1, so no funds need to be
         // spent on re-publishing those.
@@ -729,55 +2262,205 @@ impl TxSender {
                 &vm_permit,
                 &self.0.replica_connection_pool,
                 tx.execute.factory_deps.as_deref().unwrap_or_default(),
+                !options.uncompressed_bytecodes,
                 self.storage_caches(),
             )
             .await?;
 
-            if pubdata_for_factory_deps as u64 > self.0.sender_config.max_pubdata_per_batch {
-                return Err(SubmitTxError::Unexecutable(
-                    "exceeds limit for published pubdata".to_string(),
+            let max_pubdata_per_batch = self.0.sender_config.max_pubdata_per_batch;
+            if pubdata_for_factory_deps as u64 > max_pubdata_per_batch {
+                return Err(SubmitTxError::PubdataLimitExceeded(
+                    pubdata_for_factory_deps.into(),
+                    max_pubdata_per_batch,
                 ));
             }
-            pubdata_for_factory_deps * (gas_per_pubdata_byte as u32)
+            self.ensure_tx_pubdata_within_limit(pubdata_for_factory_deps.into())?;
+            pubdata_for_factory_deps
+                .checked_mul(gas_per_pubdata_byte)
+                .ok_or(SubmitTxError::GasLimitIsTooBig)?
         };
+        Self::ensure_min_gas_fits_into_block(
+            &tx,
+            gas_for_bytecodes_pubdata,
+            gas_per_pubdata_byte,
+            protocol_version,
+        )?;
 
         // We are using binary search to find the minimal values of gas_limit under which
         // the transaction succeeds
         let mut lower_bound = 0;
-        let mut upper_bound = MAX_L2_TX_GAS_LIMIT as u32;
+        let max_gas_limit = self.0.sender_config.max_gas_limit_for_estimation;
+        let mut upper_bound = max_gas_limit;
         let tx_id = format!(
             "{:?}-{}",
             tx.initiator_account(),
             tx.nonce().unwrap_or(Nonce(0))
         );
+        let preparation_time = estimation_started_at.elapsed();
+        SANDBOX_METRICS.estimate_gas_phase_latency[&EstimateGasPhase::Preparation]
+            .observe(preparation_time);
         tracing::trace!(
             "fee estimation tx {:?}: preparation took {:?}, starting binary search",
             tx_id,
-            estimation_started_at.elapsed(),
+            preparation_time,
         );
 
         let mut number_of_iterations = 0usize;
-        while lower_bound + acceptable_overestimation < upper_bound {
+        if self
+            .is_call_to_non_contract(&tx, block_args, preceding_state.as_ref())
+            .await?
+        {
+            // Calling an address without code doesn't execute anything, so gas used by the transaction doesn't depend
+            // on the gas limit. Thus, a single execution with the maximum gas limit is enough to bound the gas limit;
+            // the bound is still checked by the final estimation step.
+            tracing::info!(
+                "fee estimation tx {tx_id:?}: target {:?} has no code, calldata will be ignored",
+                tx.execute.contract_address
+            );
+            let try_gas_limit = gas_for_bytecodes_pubdata
+                .checked_add(upper_bound)
+                .ok_or(SubmitTxError::GasLimitIsTooBig)?;
+            let (result, _) = self
+                .estimate_gas_step(
+                    vm_permit.clone(),
+                    tx.clone(),
+                    try_gas_limit,
+                    gas_per_pubdata_byte,
+                    fee_input,
+                    block_args,
+                    base_fee,
+                    protocol_version.into(),
+                    preceding_state.as_ref(),
+                    options.operator_balance_override,
+                    &self.0.api_contracts.estimate_gas,
+                    vec![],
+                )
+                .await
+                .context("estimate_gas step failed")?;
+            number_of_iterations += 1;
+            Self::report_estimation_step(steps_sender, try_gas_limit, &result.result);
+
+            if is_execution_timed_out(&result.result) {
+                return Err(SubmitTxError::ExecutionTimedOut);
+            }
+            // If the execution has failed, fall back to the binary search, which will report the failure.
+            if !result.result.is_failed() {
+                let gas_used = result
+                    .statistics
+                    .gas_used
+                    .saturating_sub(gas_for_bytecodes_pubdata);
+                upper_bound = upper_bound.min(gas_used);
+                lower_bound = upper_bound;
+            }
+        } else if self.0.sender_config.estimate_gas_probe_max_gas_limit {
+            let try_gas_limit = gas_for_bytecodes_pubdata
+                .checked_add(upper_bound)
+                .ok_or(SubmitTxError::GasLimitIsTooBig)?;
+            let (result, _) = self
+                .estimate_gas_step(
+                    vm_permit.clone(),
+                    tx.clone(),
+                    try_gas_limit,
+                    gas_per_pubdata_byte,
+                    fee_input,
+                    block_args,
+                    base_fee,
+                    protocol_version.into(),
+                    preceding_state.as_ref(),
+                    options.operator_balance_override,
+                    &self.0.api_contracts.estimate_gas,
+                    vec![],
+                )
+                .await
+                .context("estimate_gas step failed")?;
+            number_of_iterations += 1;
+            Self::report_estimation_step(steps_sender, try_gas_limit, &result.result);
+
+            if is_execution_timed_out(&result.result) {
+                return Err(SubmitTxError::ExecutionTimedOut);
+            }
+            if let ExecutionResult::Revert { output } = &result.result {
+                tracing::info!(
+                    "fee estimation tx {tx_id:?}: transaction reverted with the maximum gas limit {try_gas_limit}, \
+                     aborting estimation: {output}"
+                );
+                return Err(SubmitTxError::ExecutionReverted(
+                    output.to_user_friendly_string(),
+                    output.encoded_data(),
+                ));
+            }
+        } else if self.0.sender_config.estimate_gas_by_extrapolation {
+            let extrapolation = self
+                .extrapolate_gas_limit(
+                    &vm_permit,
+                    &tx,
+                    upper_bound,
+                    gas_for_bytecodes_pubdata,
+                    gas_per_pubdata_byte,
+                    fee_input,
+                    block_args,
+                    base_fee,
+                    protocol_version,
+                    preceding_state.as_ref(),
+                    options.operator_balance_override,
+                    steps_sender,
+                    &mut number_of_iterations,
+                )
+                .await?;
+            match extrapolation {
+                GasExtrapolation::Confirmed(gas_limit) => {
+                    lower_bound = gas_limit;
+                    upper_bound = gas_limit;
+                }
+                GasExtrapolation::Insufficient(failed_gas_limit) => {
+                    tracing::debug!(
+                        "fee estimation tx {tx_id:?}: extrapolated gas limit {failed_gas_limit} is insufficient, \
+                         falling back to binary search"
+                    );
+                    lower_bound = (failed_gas_limit + 1).min(upper_bound);
+                }
+                // The failure will be reported by the binary search.
+                GasExtrapolation::Failed => {}
+            }
+        }
+
+        while lower_bound.saturating_add(Self::acceptable_overestimation(
+            acceptable_overestimation,
+            options.acceptable_overestimation_pct,
+            upper_bound,
+        )) < upper_bound
+        {
             let mid = (lower_bound + upper_bound) / 2;
             // There is no way to distinct between errors due to out of gas
             // or normal execution errors, so we just hope that increasing the
             // gas limit will make the transaction successful
             let iteration_started_at = Instant::now();
-            let try_gas_limit = gas_for_bytecodes_pubdata + mid;
+            let try_gas_limit = gas_for_bytecodes_pubdata
+                .checked_add(mid)
+                .ok_or(SubmitTxError::GasLimitIsTooBig)?;
             let (result, _) = self
                 .estimate_gas_step(
                     vm_permit.clone(),
                     tx.clone(),
                     try_gas_limit,
-                    gas_per_pubdata_byte as u32,
+                    gas_per_pubdata_byte,
                     fee_input,
                     block_args,
                     base_fee,
                     protocol_version.into(),
+                    preceding_state.as_ref(),
+                    options.operator_balance_override,
+                    &self.0.api_contracts.estimate_gas,
+                    vec![],
                 )
                 .await
                 .context("estimate_gas step failed")?;
+            Self::report_estimation_step(steps_sender, try_gas_limit, &result.result);
 
+            // Unlike other failures, a timeout is not caused by an insufficient gas limit.
+            if is_execution_timed_out(&result.result) {
+                return Err(SubmitTxError::ExecutionTimedOut);
+            }
             if result.result.is_failed() {
                 lower_bound = mid + 1;
             } else {
@@ -794,32 +2477,209 @@ impl TxSender {
             );
             number_of_iterations += 1;
         }
+        let search_time = estimation_started_at.elapsed() - preparation_time;
         SANDBOX_METRICS
             .estimate_gas_binary_search_iterations
             .observe(number_of_iterations);
+        SANDBOX_METRICS.estimate_gas_phase_latency[&EstimateGasPhase::Search].observe(search_time);
+        if self.0.sender_config.log_estimation_summary {
+            tracing::debug!(
+                "fee estimation summary for tx {tx_id:?}: preparation took {preparation_time:?}, binary search took \
+                 {number_of_iterations} iterations and {search_time:?}; final bounds: [{lower_bound}, {upper_bound}]"
+            );
+        }
 
-        let tx_body_gas_limit = cmp::min(
-            MAX_L2_TX_GAS_LIMIT as u32,
-            ((upper_bound as f64) * estimated_fee_scale_factor) as u32,
-        );
+        let scaled_gas_limit = f64::from(upper_bound) * estimated_fee_scale_factor;
+        let gas_limit_capped = scaled_gas_limit > f64::from(max_gas_limit);
+        let mut tx_body_gas_limit = cmp::min(max_gas_limit, scaled_gas_limit as u32);
 
-        let suggested_gas_limit = tx_body_gas_limit + gas_for_bytecodes_pubdata;
-        let (result, tx_metrics) = self
-            .estimate_gas_step(
-                vm_permit,
+        let mut retries_left = self.0.sender_config.estimate_gas_final_step_retries;
+        let (suggested_gas_limit, result, tx_metrics) = loop {
+            let suggested_gas_limit = tx_body_gas_limit
+                .checked_add(gas_for_bytecodes_pubdata)
+                .ok_or(SubmitTxError::GasLimitIsTooBig)?;
+            let (result, tx_metrics) = self
+                .estimate_gas_step(
+                    vm_permit.clone(),
+                    tx.clone(),
+                    suggested_gas_limit,
+                    gas_per_pubdata_byte,
+                    fee_input,
+                    block_args,
+                    base_fee,
+                    protocol_version.into(),
+                    preceding_state.as_ref(),
+                    options.operator_balance_override,
+                    &self.0.api_contracts.estimate_gas,
+                    vec![],
+                )
+                .await
+                .context("final estimate_gas step failed")?;
+
+            if !is_out_of_gas(&result.result)
+                || retries_left == 0
+                || tx_body_gas_limit >= max_gas_limit
+            {
+                break (suggested_gas_limit, result, tx_metrics);
+            }
+            retries_left -= 1;
+            let bumped_gas_limit = Self::bump_gas_limit(
+                tx_body_gas_limit,
+                self.0.sender_config.estimate_gas_retry_bump,
+                max_gas_limit,
+            );
+            tracing::debug!(
+                "fee estimation tx {tx_id:?}: final step ran out of gas with gas limit {tx_body_gas_limit}, \
+                 retrying with {bumped_gas_limit}"
+            );
+            tx_body_gas_limit = bumped_gas_limit;
+        };
+
+        // The final step may be retried, so tracers are attached to a separate execution with the resulting gas limit
+        // rather than to each of the attempts.
+        if !final_step_tracers.is_empty() {
+            self.estimate_gas_step(
+                vm_permit.clone(),
                 tx.clone(),
                 suggested_gas_limit,
-                gas_per_pubdata_byte as u32,
+                gas_per_pubdata_byte,
                 fee_input,
                 block_args,
                 base_fee,
                 protocol_version.into(),
+                preceding_state.as_ref(),
+                options.operator_balance_override,
+                &self.0.api_contracts.estimate_gas,
+                final_step_tracers,
             )
             .await
-            .context("final estimate_gas step failed")?;
+            .context("traced final estimate_gas step failed")?;
+        }
 
+        // Pubdata produced at runtime (e.g., by deploying contracts) isn't covered by the factory deps check above.
+        // If it doesn't fit into a batch, the transaction fails regardless of the gas limit, which we want to
+        // report as such rather than as a generic revert.
+        let max_pubdata_per_batch = self.0.sender_config.max_pubdata_per_batch;
+        let pubdata_published = u64::from(tx_metrics.pubdata_published);
+        if pubdata_published > max_pubdata_per_batch {
+            tracing::info!(
+                "fee estimation tx {tx_id:?}: final step published {pubdata_published} bytes of pubdata, \
+                 exceeding the limit of {max_pubdata_per_batch} bytes"
+            );
+            return Err(SubmitTxError::PubdataLimitExceeded(
+                pubdata_published,
+                max_pubdata_per_batch,
+            ));
+        }
+        self.ensure_tx_pubdata_within_limit(pubdata_published)?;
+
+        if let ExecutionResult::Halt { reason } = &result.result {
+            // Unlike reverts, these halts depend on the gas limit, i.e. the binary search has failed
+            // to find a gas limit within bounds under which the transaction can be executed.
+            if matches!(
+                reason,
+                Halt::BootloaderOutOfGas
+                    | Halt::ValidationOutOfGas
+                    | Halt::NotEnoughGasProvided
+                    | Halt::TooBigGasLimit
+            ) {
+                tracing::info!(
+                    "fee estimation tx {tx_id:?}: binary search failed to converge \
+                     (upper bound: {upper_bound}); final step halted with: {reason}"
+                );
+                return Err(SubmitTxError::GasEstimationFailed(reason.to_string()));
+            }
+        }
+
+        // Playground contracts produce more human-readable revert reasons than the gas estimation ones.
+        let result = if matches!(result.result, ExecutionResult::Revert { .. })
+            && self.0.sender_config.estimate_gas_playground_revert_reasons
+        {
+            let (playground_result, _) = self
+                .estimate_gas_step(
+                    vm_permit.clone(),
+                    tx.clone(),
+                    suggested_gas_limit,
+                    gas_per_pubdata_byte,
+                    fee_input,
+                    block_args,
+                    base_fee,
+                    protocol_version.into(),
+                    preceding_state.as_ref(),
+                    options.operator_balance_override,
+                    &self.0.api_contracts.eth_call,
+                    vec![],
+                )
+                .await
+                .context("final estimate_gas step with playground contracts failed")?;
+            // Only replace the result if the playground execution confirms the revert.
+            if matches!(playground_result.result, ExecutionResult::Revert { .. }) {
+                playground_result
+            } else {
+                tracing::debug!(
+                    "fee estimation tx {tx_id:?}: transaction reverted with estimation contracts, \
+                     but not with playground ones: {:?}",
+                    playground_result.result
+                );
+                result
+            }
+        } else {
+            result
+        };
         result.into_api_call_result()?;
-        self.ensure_tx_executable(tx.clone(), &tx_metrics, false)?;
+        if options.skip_seal_check {
+            tracing::warn!(
+                "fee estimation tx {tx_id:?}: seal criteria check is skipped; the transaction may be unexecutable \
+                 by the sequencer"
+            );
+        } else {
+            self.ensure_tx_executable(tx.clone(), &tx_metrics, false)?;
+        }
+
+        // The binary search already accounts for the paymaster since the transaction is executed with it. To report
+        // the paymaster gas separately, the transaction is additionally executed without the paymaster if requested.
+        let paymaster_gas = match &tx.common_data {
+            ExecuteTransactionCommon::L2(common_data)
+                if options.estimate_paymaster_gas
+                    && common_data.paymaster_params.paymaster != Address::zero() =>
+            {
+                let mut unsponsored_tx = tx.clone();
+                if let ExecuteTransactionCommon::L2(common_data) = &mut unsponsored_tx.common_data {
+                    common_data.paymaster_params = PaymasterParams::default();
+                }
+                let (unsponsored_result, unsponsored_metrics) = self
+                    .estimate_gas_step(
+                        vm_permit.clone(),
+                        unsponsored_tx,
+                        suggested_gas_limit,
+                        gas_per_pubdata_byte,
+                        fee_input,
+                        block_args,
+                        base_fee,
+                        protocol_version.into(),
+                        preceding_state.as_ref(),
+                        options.operator_balance_override,
+                        &self.0.api_contracts.estimate_gas,
+                        vec![],
+                    )
+                    .await
+                    .context("estimate_gas step without paymaster failed")?;
+                if unsponsored_result.result.is_failed() {
+                    // E.g., the transaction may rely on side effects of the paymaster validation.
+                    tracing::debug!(
+                        "fee estimation tx {tx_id:?}: transaction failed without paymaster: {:?}",
+                        unsponsored_result.result
+                    );
+                    None
+                } else {
+                    let paymaster_gas = tx_metrics
+                        .gas_used
+                        .saturating_sub(unsponsored_metrics.gas_used);
+                    Some(u32::try_from(paymaster_gas).unwrap_or(u32::MAX))
+                }
+            }
+            _ => None,
+        };
 
         // Now, we need to calculate the final overhead for the transaction. We need to take into account the fact
         // that the migration of 1.4.1 may be still going on.
@@ -830,7 +2690,7 @@ impl TxSender {
         {
             derive_pessimistic_overhead(
                 suggested_gas_limit,
-                gas_per_pubdata_byte as u32,
+                gas_per_pubdata_byte,
                 tx.encoding_len(),
                 tx.tx_format() as u8,
                 protocol_version.into(),
@@ -838,42 +2698,326 @@ impl TxSender {
         } else {
             derive_overhead(
                 suggested_gas_limit,
-                gas_per_pubdata_byte as u32,
+                gas_per_pubdata_byte,
                 tx.encoding_len(),
                 tx.tx_format() as u8,
                 protocol_version.into(),
             )
         };
 
-        let full_gas_limit =
-            match tx_body_gas_limit.overflowing_add(gas_for_bytecodes_pubdata + overhead) {
-                (value, false) => value,
-                (_, true) => {
-                    return Err(SubmitTxError::ExecutionReverted(
-                        "exceeds block gas limit".to_string(),
-                        vec![],
-                    ));
-                }
-            };
+        let full_gas_limit = suggested_gas_limit
+            .checked_add(overhead)
+            .ok_or(SubmitTxError::GasLimitIsTooBig)?;
+        let min_viable_gas_limit = upper_bound
+            .saturating_add(gas_for_bytecodes_pubdata)
+            .saturating_add(overhead);
 
-        Ok(Fee {
+        let fee = Fee {
             max_fee_per_gas: base_fee.into(),
             max_priority_fee_per_gas: 0u32.into(),
             gas_limit: full_gas_limit.into(),
             gas_per_pubdata_limit: gas_per_pubdata_byte.into(),
+        };
+        Ok(GasEstimate {
+            fee,
+            gas_for_bytecodes_pubdata,
+            tx_body_gas_limit,
+            gas_limit_capped,
+            min_viable_gas_limit,
+            block_number: block_args.resolved_block_number(),
+            protocol_version,
+            binary_search_iterations: number_of_iterations,
+            paymaster_gas,
+            pubdata_price_adjustment,
+            create2_address,
         })
     }
 
+    /// Clamps the scale factor for estimated gas limits to the bounds from [`TxSenderConfig`].
+    fn clamp_estimated_fee_scale_factor(&self, scale_factor: f64) -> f64 {
+        let config = &self.0.sender_config;
+        let (min, max) = (
+            config.min_estimate_gas_scale_factor,
+            config.max_estimate_gas_scale_factor,
+        );
+        // NaN is not comparable, so it's clamped to the minimum explicitly. Unlike `f64::clamp()`, `max()` / `min()`
+        // don't panic on invalid bounds (which are rejected by `TxSenderConfig::validate()` anyway).
+        let clamped = if scale_factor.is_nan() {
+            min
+        } else {
+            scale_factor.max(min).min(max)
+        };
+        if clamped != scale_factor {
+            tracing::info!(
+                "Estimated fee scale factor {scale_factor} is outside allowed bounds [{min}, {max}]; clamped to {clamped}"
+            );
+        }
+        clamped
+    }
+
+    /// Increases `gas_limit` by the relative `bump`, but by at least 1 gas. The result is capped by `max_gas_limit`.
+    fn bump_gas_limit(gas_limit: u32, bump: f64, max_gas_limit: u32) -> u32 {
+        let bumped_gas_limit = ((gas_limit as f64) * (1.0 + bump)).ceil() as u32;
+        cmp::min(
+            max_gas_limit,
+            cmp::max(bumped_gas_limit, gas_limit.saturating_add(1)),
+        )
+    }
+
+    /// If the transaction deploys a contract via CREATE2, returns the address of the deployed contract after checking
+    /// that the address isn't occupied. The deployer would reject deployment to an occupied address anyway,
+    /// but the resulting revert doesn't explain the cause.
+    async fn check_create2_deployment(
+        &self,
+        tx: &Transaction,
+    ) -> Result<Option<Address>, SubmitTxError> {
+        if tx.execute.contract_address != CONTRACT_DEPLOYER_ADDRESS {
+            return Ok(None);
+        }
+        let Some((salt, bytecode_hash, constructor_input)) =
+            tx.execute.decode_deploy_params_create2()
+        else {
+            return Ok(None);
+        };
+        let address = deployed_address_create2(
+            tx.initiator_account(),
+            salt,
+            bytecode_hash,
+            &constructor_input,
+        );
+
+        // Mirrors the deployer check: the address must have neither code nor a non-zero (raw) nonce.
+        let hashed_keys = [
+            get_code_key(&address).hashed_key(),
+            get_nonce_key(&address).hashed_key(),
+        ];
+        let values = self
+            .acquire_replica_connection()
+            .await?
+            .storage_web3_dal()
+            .get_values(&hashed_keys)
+            .await
+            .with_context(|| format!("failed getting code hash and nonce for {address:?}"))?;
+        if values.values().any(|value| !value.is_zero()) {
+            return Err(SubmitTxError::DeploymentAddressOccupied(address));
+        }
+        Ok(Some(address))
+    }
+
+    /// Checks whether the transaction is an L2 call with non-empty calldata to an address without deployed code
+    /// (e.g., to an EOA). Such calls do nothing besides transferring value.
+    ///
+    /// The code is read from the same state the transaction is executed on, i.e. at `block_args`
+    /// with changes made by the preceding transactions (if any) applied on top.
+    async fn is_call_to_non_contract(
+        &self,
+        tx: &Transaction,
+        block_args: BlockArgs,
+        preceding_state: Option<&Arc<PrecedingTxsState>>,
+    ) -> anyhow::Result<bool> {
+        if tx.is_l1() || tx.execute.calldata().is_empty() {
+            return Ok(false);
+        }
+        let target = tx.execute.contract_address;
+        let code_key = get_code_key(&target);
+        if let Some(code_hash) = preceding_state.and_then(|state| state.storage_value(&code_key)) {
+            return Ok(code_hash == H256::zero());
+        }
+        let block_number = block_args.resolved_block_number();
+        let code_hash = self
+            .acquire_replica_connection()
+            .await?
+            .storage_web3_dal()
+            .get_historical_value_unchecked(&code_key, block_number)
+            .await
+            .with_context(|| {
+                format!("failed getting code hash for {target:?} at miniblock #{block_number}")
+            })?;
+        Ok(code_hash == H256::zero())
+    }
+
+    /// Extrapolates the gas limit for the transaction body from the gas used when executing it with `max_gas_limit`,
+    /// and confirms the extrapolated limit with another execution.
+    #[allow(clippy::too_many_arguments)]
+    async fn extrapolate_gas_limit(
+        &self,
+        vm_permit: &VmPermit,
+        tx: &Transaction,
+        max_gas_limit: u32,
+        gas_for_bytecodes_pubdata: u32,
+        gas_per_pubdata_byte: u32,
+        fee_input: BatchFeeInput,
+        block_args: BlockArgs,
+        base_fee: u64,
+        protocol_version: ProtocolVersionId,
+        preceding_state: Option<&Arc<PrecedingTxsState>>,
+        operator_balance_override: Option<U256>,
+        steps_sender: Option<&EstimationStepsSender>,
+        number_of_iterations: &mut usize,
+    ) -> Result<GasExtrapolation, SubmitTxError> {
+        let mut gas_limit = max_gas_limit;
+        for is_confirmation in [false, true] {
+            let try_gas_limit = gas_for_bytecodes_pubdata
+                .checked_add(gas_limit)
+                .ok_or(SubmitTxError::GasLimitIsTooBig)?;
+            let (result, _) = self
+                .estimate_gas_step(
+                    vm_permit.clone(),
+                    tx.clone(),
+                    try_gas_limit,
+                    gas_per_pubdata_byte,
+                    fee_input,
+                    block_args,
+                    base_fee,
+                    protocol_version.into(),
+                    preceding_state,
+                    operator_balance_override,
+                    &self.0.api_contracts.estimate_gas,
+                    vec![],
+                )
+                .await
+                .context("estimate_gas step failed")?;
+            *number_of_iterations += 1;
+            Self::report_estimation_step(steps_sender, try_gas_limit, &result.result);
+
+            if is_execution_timed_out(&result.result) {
+                return Err(SubmitTxError::ExecutionTimedOut);
+            }
+            if result.result.is_failed() {
+                return Ok(if is_confirmation {
+                    GasExtrapolation::Insufficient(gas_limit)
+                } else {
+                    GasExtrapolation::Failed
+                });
+            }
+            if !is_confirmation {
+                // The overhead is added to the gas limit by `estimate_gas_step()`.
+                let gas_used = result
+                    .statistics
+                    .gas_used
+                    .saturating_sub(gas_for_bytecodes_pubdata);
+                gas_limit = gas_limit.min(gas_used);
+            }
+        }
+        Ok(GasExtrapolation::Confirmed(gas_limit))
+    }
+
+    /// Returns the acceptable gas overestimation for a binary search iteration with the specified upper bound.
+    fn acceptable_overestimation(absolute: u32, pct: Option<f64>, upper_bound: u32) -> u32 {
+        let Some(pct) = pct else {
+            return absolute;
+        };
+        // Float-to-int casts are saturating, and NaN is cast to 0.
+        let relative = (f64::from(upper_bound) * pct / 100.0) as u32;
+        absolute.max(relative)
+    }
+
+    /// Checks that the gas required by the transaction regardless of its execution (intrinsic gas, gas for publishing
+    /// bytecodes and the overhead) fits into the block gas limit. Otherwise, the binary search cannot succeed.
+    fn ensure_min_gas_fits_into_block(
+        tx: &Transaction,
+        gas_for_bytecodes_pubdata: u32,
+        gas_per_pubdata_byte: u32,
+        protocol_version: ProtocolVersionId,
+    ) -> Result<(), SubmitTxError> {
+        let intrinsic_consts = get_intrinsic_constants(protocol_version.into());
+        let intrinsic_gas = if tx.is_l1() {
+            intrinsic_consts.l1_tx_intrinsic_gas
+        } else {
+            intrinsic_consts.l2_tx_intrinsic_gas
+        };
+        let min_gas_limit = intrinsic_gas.saturating_add(gas_for_bytecodes_pubdata);
+        let min_overhead = derive_overhead(
+            min_gas_limit,
+            gas_per_pubdata_byte,
+            tx.encoding_len(),
+            tx.tx_format() as u8,
+            protocol_version.into(),
+        );
+        let min_gas_limit = min_gas_limit.saturating_add(min_overhead);
+        if min_gas_limit > BLOCK_GAS_LIMIT {
+            return Err(SubmitTxError::ExceedsBlockGasLimit(
+                min_gas_limit,
+                BLOCK_GAS_LIMIT,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Raises the derived `gas_per_pubdata` to [`TxSenderConfig::min_gas_per_pubdata`] if necessary.
+    fn apply_min_gas_per_pubdata(&self, gas_per_pubdata: u64) -> u64 {
+        match self.0.sender_config.min_gas_per_pubdata {
+            Some(min_gas_per_pubdata) => gas_per_pubdata.max(min_gas_per_pubdata),
+            None => gas_per_pubdata,
+        }
+    }
+
+    fn override_l1_gas_price(fee_input: BatchFeeInput, l1_gas_price: u64) -> BatchFeeInput {
+        match fee_input {
+            BatchFeeInput::L1Pegged(input) => {
+                BatchFeeInput::l1_pegged(l1_gas_price, input.fair_l2_gas_price)
+            }
+            BatchFeeInput::PubdataIndependent(input) => {
+                // Pubdata is published on L1, so its price is expected to follow the L1 gas price.
+                let fair_pubdata_price = if input.l1_gas_price == 0 {
+                    input.fair_pubdata_price
+                } else {
+                    let price = u128::from(input.fair_pubdata_price) * u128::from(l1_gas_price)
+                        / u128::from(input.l1_gas_price);
+                    u64::try_from(price).unwrap_or(u64::MAX)
+                };
+                BatchFeeInput::pubdata_independent(
+                    l1_gas_price,
+                    input.fair_l2_gas_price,
+                    fair_pubdata_price,
+                )
+            }
+        }
+    }
+
     pub(super) async fn eth_call(
         &self,
         block_args: BlockArgs,
         tx: L2Tx,
     ) -> Result<Vec<u8>, SubmitTxError> {
+        let (output, _) = self.eth_call_with_gas(block_args, tx).await?;
+        Ok(output)
+    }
+
+    /// Same as [`Self::eth_call()`], but also returns the amount of gas used by the call.
+    pub(super) async fn eth_call_with_gas(
+        &self,
+        block_args: BlockArgs,
+        tx: L2Tx,
+    ) -> Result<(Vec<u8>, u64), SubmitTxError> {
+        self.with_cache_stats(
+            SandboxCallType::EthCall,
+            self.eth_call_inner(block_args, tx),
+        )
+        .await
+    }
+
+    async fn eth_call_inner(
+        &self,
+        block_args: BlockArgs,
+        tx: L2Tx,
+    ) -> Result<(Vec<u8>, u64), SubmitTxError> {
+        if let Some(allowlist) = &self.0.sender_config.eth_call_target_allowlist {
+            let target = tx.recipient_account();
+            if !allowlist.contains(&target) {
+                return Err(SubmitTxError::CallTargetNotAllowed(target));
+            }
+        }
+        if block_args.block_id() == api::BlockId::Number(api::BlockNumber::Pending) {
+            self.observe_pending_block(&block_args).await?;
+        }
+
         let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
         let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
 
         let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
-        self.0
+        let vm_result = self
+            .0
             .executor
             .execute_tx_eth_call(
                 vm_permit,
@@ -884,31 +3028,136 @@ impl TxSender {
                 vm_execution_cache_misses_limit,
                 vec![],
             )
-            .await?
-            .into_api_call_result()
+            .await?;
+        if let Some(max_bytes) = self.0.sender_config.max_eth_call_response_bytes {
+            // Checked before converting the result, so that oversized data isn't copied (e.g., into the revert error).
+            let response_bytes = Self::returndata_len(&vm_result.result);
+            if response_bytes > max_bytes {
+                return Err(SubmitTxError::ResponseTooLarge(response_bytes, max_bytes));
+            }
+        }
+        let gas_used = vm_result.statistics.gas_used.into();
+        Ok((vm_result.into_api_call_result()?, gas_used))
     }
 
+    /// Returns the size of data returned by the VM, either as the call output or as the revert data.
+    fn returndata_len(result: &ExecutionResult) -> usize {
+        match result {
+            ExecutionResult::Success { output } => output.len(),
+            ExecutionResult::Revert {
+                output: VmRevertReason::General { data, .. } | VmRevertReason::Unknown { data, .. },
+            } => data.len(),
+            ExecutionResult::Revert { .. } | ExecutionResult::Halt { .. } => 0,
+        }
+    }
+
+    /// Returns the base fee for the pending block. The base fee is cached while the batch fee input is unchanged
+    /// and no new miniblocks are observed, so that frequent polling doesn't read the pending protocol version from DB.
     pub async fn gas_price(&self) -> anyhow::Result<u64> {
+        // For now, both the L1 gas price and the L1 pubdata price are scaled with the same coefficient
+        let fee_input = self
+            .0
+            .batch_fee_input_provider
+            .get_batch_fee_input_scaled(
+                self.0.sender_config.gas_price_scale_factor,
+                self.0.sender_config.gas_price_scale_factor,
+            )
+            .await;
+        // A new miniblock may change the pending protocol version, so cached values are keyed by the newest observed
+        // miniblock. The latest sealed miniblock is looked up via the sink, which is cheap (it's either cached or kept
+        // in memory); if the sink doesn't know it, the cache is only invalidated by its TTL and by submissions.
+        let latest_sealed_miniblock = self
+            .0
+            .tx_sink
+            .lookup_latest_sealed_miniblock()
+            .await
+            .context("failed looking up latest sealed miniblock")?;
+        if let Some(number) = latest_sealed_miniblock {
+            self.observe_miniblock(number + 1);
+        }
+        let miniblock_number = self.last_observed_miniblock();
+        if let Some(base_fee) = self.0.gas_price_cache.get(fee_input, miniblock_number) {
+            return Ok(base_fee);
+        }
+
         let mut connection = self.acquire_replica_connection().await?;
         let protocol_version = pending_protocol_version(&mut connection)
             .await
             .context("failed obtaining pending protocol version")?;
         drop(connection);
 
-        let (base_fee, _) = derive_base_fee_and_gas_per_pubdata(
-            // For now, both the L1 gas price and the L1 pubdata price are scaled with the same coefficient
-            self.0
-                .batch_fee_input_provider
-                .get_batch_fee_input_scaled(
-                    self.0.sender_config.gas_price_scale_factor,
-                    self.0.sender_config.gas_price_scale_factor,
-                )
-                .await,
-            protocol_version.into(),
-        );
+        let (base_fee, _) = derive_base_fee_and_gas_per_pubdata(fee_input, protocol_version.into());
+        self.0
+            .gas_price_cache
+            .insert(base_fee, fee_input, miniblock_number);
         Ok(base_fee)
     }
 
+    /// Returns [`Self::gas_price()`] padded by `headroom_pct` percent (e.g., 10.0 for +10%), rounded up. Unlike the exact
+    /// gas price, such a quote remains sufficient if the base fee rises slightly before the transaction is submitted,
+    /// e.g. because of L1 gas price movements.
+    pub async fn gas_price_with_headroom(&self, headroom_pct: f64) -> anyhow::Result<u64> {
+        anyhow::ensure!(
+            headroom_pct.is_finite() && headroom_pct >= 0.0,
+            "gas price headroom must be a non-negative percentage, got {headroom_pct}"
+        );
+        let gas_price = self.gas_price().await?;
+        Ok(Self::apply_gas_price_headroom(gas_price, headroom_pct))
+    }
+
+    fn apply_gas_price_headroom(gas_price: u64, headroom_pct: f64) -> u64 {
+        (gas_price as f64 * (1.0 + headroom_pct / 100.0)).ceil() as u64
+    }
+
+    /// Estimates the L1 cost (in wei) of publishing the factory deps of `tx`, i.e. the number of pubdata bytes
+    /// for the deps multiplied by the current pubdata price. Bytecodes already known to the network are not charged for,
+    /// same as in [`Self::get_txs_fee_in_wei()`]. Since the transaction isn't executed, pubdata produced
+    /// during execution (e.g., for storage writes) is not included.
+    pub async fn estimate_l1_data_cost(&self, tx: &Transaction) -> Result<U256, SubmitTxError> {
+        // For L1->L2 transactions all the bytecodes have been made available on L1.
+        if tx.is_l1() {
+            return Ok(U256::zero());
+        }
+
+        let fee_input = self
+            .0
+            .batch_fee_input_provider
+            .get_batch_fee_input_scaled(
+                self.0.sender_config.gas_price_scale_factor,
+                self.0.sender_config.gas_price_scale_factor,
+            )
+            .await;
+        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
+        let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
+        let pubdata_for_factory_deps = get_pubdata_for_factory_deps(
+            &vm_permit,
+            &self.0.replica_connection_pool,
+            tx.execute.factory_deps.as_deref().unwrap_or_default(),
+            true,
+            self.storage_caches(),
+        )
+        .await?;
+        Ok(U256::from(pubdata_for_factory_deps) * U256::from(fee_input.fair_pubdata_price()))
+    }
+
+    /// Returns the minimum `max_fee_per_gas` value accepted for submitted transactions. Transactions
+    /// with a lower value are rejected with [`SubmitTxError::MaxFeePerGasTooLow`].
+    pub async fn min_max_fee_per_gas(&self) -> u64 {
+        let fee_input = self.0.batch_fee_input_provider.get_batch_fee_input().await;
+        fee_input.fair_l2_gas_price()
+    }
+
+    fn ensure_tx_pubdata_within_limit(&self, pubdata_published: u64) -> Result<(), SubmitTxError> {
+        let max_pubdata_per_tx = self.0.sender_config.max_pubdata_per_tx;
+        if pubdata_published > max_pubdata_per_tx {
+            return Err(SubmitTxError::TxPubdataLimitExceeded(
+                pubdata_published,
+                max_pubdata_per_tx,
+            ));
+        }
+        Ok(())
+    }
+
     fn ensure_tx_executable(
         &self,
         transaction: Transaction,
@@ -928,21 +3177,68 @@ impl TxSender {
         // still reject them as it's not.
         let protocol_version = ProtocolVersionId::latest();
         let seal_data = SealData::for_transaction(transaction, tx_metrics, protocol_version);
-        if let Some(reason) = self
-            .0
-            .sealer
-            .find_unexecutable_reason(&seal_data, protocol_version)
-        {
+        if let Some(criterion) = self.find_unexecutable_reason(&seal_data, protocol_version)? {
             let message = format!(
-                "Tx is Unexecutable because of {reason}; inputs for decision: {seal_data:?}"
+                "Tx is Unexecutable because of {criterion}; inputs for decision: {seal_data:?}"
             );
             if log_message {
                 tracing::info!("{tx_hash:#?} {message}");
             }
-            return Err(SubmitTxError::Unexecutable(message));
+            return Err(SubmitTxError::Unexecutable {
+                code: criterion.reason,
+                message,
+            });
         }
         Ok(())
     }
+
+    /// Checks whether the provided transactions executed with the specified metrics collectively fit into
+    /// a single L1 batch according to the seal criteria (pubdata, circuits, gas etc.) used by the sequencer.
+    ///
+    /// Resources consumed by all transactions are summed up and checked as if they were consumed by a single transaction.
+    /// Storage writes are not deduplicated across transactions, so the check is conservative.
+    pub fn fits_in_one_batch(
+        &self,
+        txs_and_metrics: Vec<(Transaction, TransactionExecutionMetrics)>,
+    ) -> Result<bool, SubmitTxError> {
+        if txs_and_metrics.is_empty() {
+            return Ok(true);
+        }
+
+        // See `Self::ensure_tx_executable()` for the reasoning behind using the latest protocol version.
+        let protocol_version = ProtocolVersionId::latest();
+        let mut batch_data = SealData::default();
+        for (transaction, tx_metrics) in txs_and_metrics {
+            let tx_data = SealData::for_transaction(transaction, &tx_metrics, protocol_version);
+            batch_data.accumulate(tx_data);
+        }
+        let unexecutable_reason = self.find_unexecutable_reason(&batch_data, protocol_version)?;
+        Ok(unexecutable_reason.is_none())
+    }
+
+    fn find_unexecutable_reason(
+        &self,
+        seal_data: &SealData,
+        protocol_version: ProtocolVersionId,
+    ) -> Result<Option<UnexecutableCriterion>, SubmitTxError> {
+        // The sealer may be provided externally, so we guard against its panics to not crash the API task.
+        let unexecutable_reason = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.0
+                .sealer
+                .find_unexecutable_reason(seal_data, protocol_version)
+        }));
+        let unexecutable_reason = unexecutable_reason.map_err(|panic| {
+            let panic_message = if let Some(message) = panic.downcast_ref::<&str>() {
+                message
+            } else if let Some(message) = panic.downcast_ref::<String>() {
+                message.as_str()
+            } else {
+                "unknown panic"
+            };
+            anyhow::anyhow!("sealer panicked checking whether tx is executable: {panic_message}")
+        })?;
+        Ok(unexecutable_reason)
+    }
 }
 
 /// During switch to the 1.4.1 protocol version, there will be a moment of discrepancy, when while
@@ -2,25 +2,30 @@ use std::{
     collections::{BTreeSet, HashMap},
     future::Future,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use chrono::{DateTime, Utc};
 use tokio::sync::{watch, RwLock};
 use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool, Core, CoreDal};
 use zksync_types::{
     api::{BlockId, Transaction, TransactionDetails, TransactionId},
     fee::TransactionExecutionMetrics,
     l2::L2Tx,
-    Address, Nonce, H256,
+    Address, MiniblockNumber, Nonce, H256,
 };
 use zksync_web3_decl::{
-    error::{ClientRpcContext, EnrichedClientResult, Web3Error},
-    jsonrpsee::http_client::HttpClient,
+    error::{ClientRpcContext, EnrichedClientError, EnrichedClientResult, Web3Error},
+    jsonrpsee::{core::ClientError, http_client::HttpClient},
     namespaces::{EthNamespaceClient, ZksNamespaceClient},
 };
 
 use super::{tx_sink::TxSink, SubmitTxError};
-use crate::metrics::{TxStage, APP_METRICS};
+use crate::{
+    api_server::web3::metrics::{ProxiedTxErrorKind, API_METRICS},
+    metrics::{TxStage, APP_METRICS},
+    sync_layer::SyncState,
+};
 
 #[derive(Debug, Clone, Default)]
 pub(crate) struct TxCache {
@@ -65,10 +70,12 @@ impl TxCache {
     async fn run_updates(
         self,
         pool: ConnectionPool<Core>,
+        mirrored_tx_ttl: Option<Duration>,
         stop_receiver: watch::Receiver<bool>,
     ) -> anyhow::Result<()> {
         const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 
+        let mut last_mirrored_txs_prune: Option<Instant> = None;
         loop {
             if *stop_receiver.borrow() {
                 return Ok(());
@@ -84,6 +91,23 @@ impl TxCache {
                 .storage_web3_dal()
                 .get_nonces_for_addresses(&addresses)
                 .await?;
+            let mirrored_tx_ttl_to_prune = mirrored_tx_ttl.filter(|&ttl| {
+                last_mirrored_txs_prune.map_or(true, |pruned_at| {
+                    pruned_at.elapsed() >= MIRRORED_TXS_PRUNE_INTERVAL.min(ttl)
+                })
+            });
+            if let Some(ttl) = mirrored_tx_ttl_to_prune {
+                let removed_tx_count = storage
+                    .transactions_dal()
+                    .remove_stale_mirrored_txs(ttl)
+                    .await?;
+                if removed_tx_count > 0 {
+                    tracing::info!(
+                        "Pruned {removed_tx_count} mirrored txs not synced from the main node"
+                    );
+                }
+                last_mirrored_txs_prune = Some(Instant::now());
+            }
             drop(storage); // Don't hold both `storage` and lock on `inner` at the same time.
 
             let mut inner = self.inner.write().await;
@@ -104,22 +128,58 @@ impl TxCache {
     }
 }
 
+/// Maximum interval between prunes of mirrored transactions. Since the mirrored tx TTL is normally large, pruning
+/// doesn't need to happen as often as nonce updates. If the TTL is smaller than this interval, pruning happens
+/// once per TTL.
+const MIRRORED_TXS_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Used by external node to proxy transaction to the main node
 /// and store them while they're not synced back yet
 #[derive(Debug)]
 pub struct TxProxy {
     tx_cache: TxCache,
     client: HttpClient,
+    local_mempool: Option<ConnectionPool<Core>>,
+    mirrored_tx_ttl: Option<Duration>,
+    sync_state: Option<SyncState>,
 }
 
 impl TxProxy {
+    /// Default time after which mirrored transactions that were not synced back from the main node are removed
+    /// from the local mempool.
+    pub const DEFAULT_MIRRORED_TX_TTL: Duration = Duration::from_secs(10 * 60);
+
     pub fn new(client: HttpClient) -> Self {
         Self {
             client,
             tx_cache: TxCache::default(),
+            local_mempool: None,
+            mirrored_tx_ttl: None,
+            sync_state: None,
         }
     }
 
+    /// Sets the sync state used to report the latest miniblock on the main node
+    /// (see [`TxSink::lookup_latest_sealed_miniblock()`]).
+    pub fn with_sync_state(mut self, sync_state: SyncState) -> Self {
+        self.sync_state = Some(sync_state);
+        self
+    }
+
+    /// Enables mirroring proxied transactions into the local mempool. Mirrored transactions are inserted
+    /// after they are accepted by the main node. Once a transaction is synced back from the main node,
+    /// its local copy is marked as executed; mirrored transactions that are never synced within `mirrored_tx_ttl`
+    /// are pruned by [`Self::run_account_nonce_sweeper()`].
+    pub fn with_local_mempool(
+        mut self,
+        pool: ConnectionPool<Core>,
+        mirrored_tx_ttl: Duration,
+    ) -> Self {
+        self.local_mempool = Some(pool);
+        self.mirrored_tx_ttl = Some(mirrored_tx_ttl);
+        self
+    }
+
     async fn submit_tx_impl(&self, tx: &L2Tx) -> EnrichedClientResult<H256> {
         let input_data = tx.common_data.input_data().expect("raw tx is absent");
         let raw_tx = zksync_types::Bytes(input_data.to_vec());
@@ -132,6 +192,24 @@ impl TxProxy {
             .await
     }
 
+    /// Reports an error proxying the transaction, distinguishing rejections by the main node (which indicate
+    /// a divergence between local and main node validation) from transport errors.
+    fn report_submission_error(tx_hash: H256, err: &EnrichedClientError) {
+        let kind = if let ClientError::Call(err) = err.as_ref() {
+            tracing::warn!(
+                ?tx_hash,
+                code = err.code(),
+                reason = err.message(),
+                "Main node rejected proxied tx"
+            );
+            ProxiedTxErrorKind::Rejected
+        } else {
+            tracing::warn!(?tx_hash, "Failed proxying tx to the main node: {err}");
+            ProxiedTxErrorKind::Transport
+        };
+        API_METRICS.proxied_tx_errors[&kind].inc();
+    }
+
     async fn save_tx(&self, tx: L2Tx) {
         self.tx_cache.push(tx).await;
     }
@@ -144,6 +222,33 @@ impl TxProxy {
         self.tx_cache.remove_tx(tx_hash).await;
     }
 
+    async fn mirror_tx(
+        pool: &ConnectionPool<Core>,
+        tx: L2Tx,
+        execution_metrics: TransactionExecutionMetrics,
+        valid_until: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<L2TxSubmissionResult> {
+        let tx_hash = tx.hash();
+        let mut storage = pool.connection_tagged("api").await?;
+        let mut transaction = storage.start_transaction().await?;
+        let submission_result = transaction
+            .transactions_dal()
+            .insert_transaction_l2_with_deadline(tx, execution_metrics, valid_until)
+            .await?;
+        if matches!(
+            submission_result,
+            L2TxSubmissionResult::Added | L2TxSubmissionResult::Replaced
+        ) {
+            // Marking allows to prune the transaction if it's never synced back without affecting other transactions.
+            transaction
+                .transactions_dal()
+                .mark_tx_as_mirrored(tx_hash)
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(submission_result)
+    }
+
     async fn next_nonce_by_initiator_account(
         &self,
         account_address: Address,
@@ -208,7 +313,7 @@ impl TxProxy {
         stop_receiver: watch::Receiver<bool>,
     ) -> impl Future<Output = anyhow::Result<()>> {
         let tx_cache = self.tx_cache.clone();
-        tx_cache.run_updates(pool, stop_receiver)
+        tx_cache.run_updates(pool, self.mirrored_tx_ttl, stop_receiver)
     }
 }
 
@@ -217,17 +322,38 @@ impl TxSink for TxProxy {
     async fn submit_tx(
         &self,
         tx: L2Tx,
-        _execution_metrics: TransactionExecutionMetrics,
+        execution_metrics: TransactionExecutionMetrics,
+        valid_until: Option<DateTime<Utc>>,
     ) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        // The main node API doesn't accept inclusion deadlines, so `valid_until` is only persisted
+        // if the transaction is mirrored locally.
+
         // We're running an external node: we have to proxy the transaction to the main node.
         // But before we do that, save the tx to cache in case someone will request it
         // Before it reaches the main node.
         self.save_tx(tx.clone()).await;
-        self.submit_tx_impl(&tx).await?;
+        if let Err(err) = self.submit_tx_impl(&tx).await {
+            Self::report_submission_error(tx.hash(), &err);
+            return Err(err.into());
+        }
         // Now, after we are sure that the tx is on the main node, remove it from cache
         // since we don't want to store txs that might have been replaced or otherwise removed
         // from the mempool.
         self.forget_tx(tx.hash()).await;
+        if let Some(pool) = &self.local_mempool {
+            // The transaction is already accepted by the main node, so failing to mirror it shouldn't fail the submission.
+            let tx_hash = tx.hash();
+            match Self::mirror_tx(pool, tx, execution_metrics, valid_until).await {
+                Ok(submission_result) => {
+                    tracing::debug!(
+                        "Mirrored proxied tx {tx_hash:?} locally: {submission_result:?}"
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!("Failed mirroring proxied tx {tx_hash:?} locally: {err:#}");
+                }
+            }
+        }
         APP_METRICS.processed_txs[&TxStage::Proxied].inc();
         Ok(L2TxSubmissionResult::Proxied)
     }
@@ -258,7 +384,150 @@ impl TxSink for TxProxy {
         Ok(self.request_tx(id).await?)
     }
 
+    async fn lookup_latest_sealed_miniblock(
+        &self,
+    ) -> Result<Option<MiniblockNumber>, SubmitTxError> {
+        Ok(self.sync_state.as_ref().map(SyncState::get_main_node_block))
+    }
+
     async fn lookup_tx_details(&self, hash: H256) -> Result<Option<TransactionDetails>, Web3Error> {
         Ok(self.request_tx_details(hash).await?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use zksync_types::L2ChainId;
+    use zksync_web3_decl::jsonrpsee::{
+        http_client::HttpClientBuilder,
+        server::{ServerBuilder, ServerHandle},
+        types::ErrorObjectOwned,
+        RpcModule,
+    };
+
+    use super::*;
+    use crate::utils::testonly::create_l2_transaction;
+
+    async fn spawn_main_node_mock() -> (HttpClient, ServerHandle) {
+        spawn_main_node_mock_with_response(|| Ok(H256::zero())).await
+    }
+
+    async fn spawn_main_node_mock_with_response(
+        response: impl Fn() -> Result<H256, ErrorObjectOwned> + Send + Sync + 'static,
+    ) -> (HttpClient, ServerHandle) {
+        let mut rpc = RpcModule::new(());
+        rpc.register_method("eth_sendRawTransaction", move |_, _| response())
+            .unwrap();
+        let server = ServerBuilder::default()
+            .http_only()
+            .build("127.0.0.1:0")
+            .await
+            .unwrap();
+        let local_addr = server.local_addr().unwrap();
+        let server_handle = server.start(rpc);
+        let client = HttpClientBuilder::default()
+            .build(format!("http://{local_addr}"))
+            .unwrap();
+        (client, server_handle)
+    }
+
+    async fn submit_tx_via_proxy(mirror_locally: bool) -> Option<Transaction> {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let (client, _server_handle) = spawn_main_node_mock().await;
+        let mut tx_proxy = TxProxy::new(client);
+        if mirror_locally {
+            tx_proxy = tx_proxy.with_local_mempool(pool.clone(), TxProxy::DEFAULT_MIRRORED_TX_TTL);
+        }
+
+        let tx = create_l2_transaction(10, 100);
+        let tx_hash = tx.hash();
+        let submission_result = tx_proxy
+            .submit_tx(tx, Default::default(), None)
+            .await
+            .unwrap();
+        assert_matches!(submission_result, L2TxSubmissionResult::Proxied);
+
+        let mut storage = pool.connection().await.unwrap();
+        storage
+            .transactions_web3_dal()
+            .get_transaction_by_hash(tx_hash, L2ChainId::default())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn proxied_tx_is_not_mirrored_by_default() {
+        assert!(submit_tx_via_proxy(false).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn mirroring_proxied_tx_locally() {
+        let local_tx = submit_tx_via_proxy(true)
+            .await
+            .expect("proxied tx is not mirrored");
+        assert_eq!(local_tx.block_number, None);
+    }
+
+    #[tokio::test]
+    async fn pruning_only_affects_mirrored_txs() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let (client, _server_handle) = spawn_main_node_mock().await;
+        let tx_proxy =
+            TxProxy::new(client).with_local_mempool(pool.clone(), TxProxy::DEFAULT_MIRRORED_TX_TTL);
+        let mirrored_tx = create_l2_transaction(10, 100);
+        tx_proxy
+            .submit_tx(mirrored_tx.clone(), Default::default(), None)
+            .await
+            .unwrap();
+        let local_tx = create_l2_transaction(10, 100);
+        let mut storage = pool.connection().await.unwrap();
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(local_tx.clone(), Default::default())
+            .await
+            .unwrap();
+
+        let removed_tx_count = storage
+            .transactions_dal()
+            .remove_stale_mirrored_txs(Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(removed_tx_count, 1);
+        for (tx_hash, should_exist) in [(mirrored_tx.hash(), false), (local_tx.hash(), true)] {
+            let tx = storage
+                .transactions_web3_dal()
+                .get_transaction_by_hash(tx_hash, L2ChainId::default())
+                .await
+                .unwrap();
+            assert_eq!(tx.is_some(), should_exist, "{tx_hash:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn reporting_tx_rejected_by_main_node() {
+        let (client, _server_handle) = spawn_main_node_mock_with_response(|| {
+            Err(ErrorObjectOwned::owned(
+                3,
+                "max fee per gas less than block base fee",
+                None::<()>,
+            ))
+        })
+        .await;
+        let tx_proxy = TxProxy::new(client);
+
+        let rejections = &API_METRICS.proxied_tx_errors[&ProxiedTxErrorKind::Rejected];
+        let rejections_before = rejections.get();
+        let tx = create_l2_transaction(10, 100);
+        let err = tx_proxy
+            .submit_tx(tx, Default::default(), None)
+            .await
+            .unwrap_err();
+        assert_matches!(
+            &err,
+            SubmitTxError::ProxyError(err)
+                if matches!(err.as_ref(), ClientError::Call(err) if err.code() == 3)
+        );
+        assert_eq!(rejections.get(), rejections_before + 1);
+    }
+}
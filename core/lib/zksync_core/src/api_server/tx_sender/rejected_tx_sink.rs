@@ -0,0 +1,28 @@
+//! Destinations for transactions rejected by `TxSender`.
+
+use std::fmt;
+
+use zksync_types::H256;
+
+use super::SubmitTxError;
+
+/// Destination for transactions rejected on submission, e.g. to persist them in a separate store for abuse analysis.
+///
+/// [`Self::record()`] is called for each failed submission, including ones failed because of internal errors;
+/// implementations may use [`SubmitTxError::is_user_error()`] to filter out the latter. The method is called
+/// on the hot path of transaction submission, so it should not block (e.g., it may buffer records and persist them
+/// in the background).
+pub trait RejectedTxSink: fmt::Debug + Send + Sync + 'static {
+    /// Records a rejected transaction together with the rejection reason.
+    fn record(&self, tx_hash: H256, reason: &SubmitTxError);
+}
+
+/// Sink discarding all rejected transactions. Used by `TxSender` by default.
+#[derive(Debug)]
+pub struct NoopRejectedTxSink;
+
+impl RejectedTxSink for NoopRejectedTxSink {
+    fn record(&self, _tx_hash: H256, _reason: &SubmitTxError) {
+        // Do nothing
+    }
+}
@@ -1,19 +1,57 @@
-use multivm::interface::{ExecutionResult, VmExecutionResultAndLogs};
+use chrono::{DateTime, Utc};
+use multivm::interface::{BytecodeCompressionError, ExecutionResult, VmExecutionResultAndLogs};
 use thiserror::Error;
-use zksync_types::{l2::error::TxCheckError, U256};
-use zksync_web3_decl::error::EnrichedClientError;
+use zksync_types::{
+    l2::{error::TxCheckError, TransactionType},
+    Address, MiniblockNumber, H256, U256,
+};
+use zksync_utils::u256_to_h256;
+use zksync_web3_decl::{error::EnrichedClientError, jsonrpsee::core::ClientError};
 
-use crate::api_server::execution_sandbox::{SandboxExecutionError, ValidationError};
+use crate::{
+    api_server::execution_sandbox::{SandboxExecutionError, ValidationError},
+    state_keeper::seal_criteria::UnexecutableReason,
+};
 
 /// Errors that con occur submitting a transaction or estimating gas for its execution.
 #[derive(Debug, Error)]
 pub enum SubmitTxError {
-    #[error("nonce too high. allowed nonce range: {0} - {1}, actual: {2}")]
-    NonceIsTooHigh(u32, u32, u32),
-    #[error("nonce too low. allowed nonce range: {0} - {1}, actual: {2}")]
-    NonceIsTooLow(u32, u32, u32),
-    #[error("insertion of another transaction with the same nonce is in progress")]
+    /// The last field is the miniblock the expected nonce was read at.
+    #[error(
+        "nonce too high. allowed nonce range: {0} - {1}, actual: {2} (nonce read at miniblock #{3})"
+    )]
+    NonceIsTooHigh(u32, u32, u32, MiniblockNumber),
+    /// The last field is the miniblock the expected nonce was read at.
+    #[error(
+        "nonce too low. allowed nonce range: {0} - {1}, actual: {2} (nonce read at miniblock #{3})"
+    )]
+    NonceIsTooLow(u32, u32, u32, MiniblockNumber),
+    /// Same as [`Self::NonceIsTooHigh`], but there is a gap in the account nonces that can be filled;
+    /// the last field is the first missing nonce, i.e. the next nonce that should be submitted.
+    #[error(
+        "nonce too high. allowed nonce range: {0} - {1}, actual: {2}. next nonce to submit: {3}"
+    )]
+    NonceGap(u32, u32, u32, u32),
+    /// The same transaction is being submitted by a concurrent request. Since it isn't guaranteed to be persisted yet,
+    /// clients may retry submission.
+    #[error("insertion of the same transaction is in progress")]
     InsertionInProgress,
+    /// Another transaction with the same initiator and nonce (its hash is provided) is being submitted
+    /// by a concurrent request. Since it isn't guaranteed to be persisted yet, clients may retry submission.
+    #[error(
+        "transaction {0:?} with the same nonce is being submitted concurrently; retry submission"
+    )]
+    ConcurrentNonceConflict(H256),
+    /// Exactly the same transaction is already in the mempool. Unlike other errors, this one doesn't mean that
+    /// the transaction won't be executed, so clients retrying submission can treat it as success.
+    #[error("transaction {0:?} is already in the mempool")]
+    AlreadyInMempool(H256),
+    /// Transaction was submitted with an inclusion deadline that has already passed.
+    #[error("transaction has expired at {0}")]
+    TransactionExpired(DateTime<Utc>),
+    /// Signature of a transaction initiated by an EOA doesn't recover to the initiator.
+    #[error("invalid signature: it doesn't recover to the transaction initiator")]
+    InvalidSignature,
     #[error("{0}")]
     IncorrectTx(#[from] TxCheckError),
     #[error("insufficient funds for gas + value. balance: {0}, fee: {1}, value: {2}")]
@@ -22,8 +60,14 @@ pub enum SubmitTxError {
     ExecutionReverted(String, Vec<u8>),
     #[error("exceeds block gas limit")]
     GasLimitIsTooBig,
-    #[error("{0}")]
-    Unexecutable(String),
+    /// Transaction cannot be executed by the sequencer, e.g. because it hits a batch limit on its own.
+    /// `code` allows distinguishing the cause without parsing the message; it is returned as error data
+    /// (a single 32-byte big-endian word).
+    #[error("{message}")]
+    Unexecutable {
+        code: UnexecutableReason,
+        message: String,
+    },
     #[error("too many transactions")]
     RateLimitExceeded,
     #[error("server shutting down")]
@@ -40,8 +84,12 @@ pub enum SubmitTxError {
     PrePaymasterPreparationFailed(String),
     #[error("invalid sender. can't start a transaction from a non-account")]
     FromIsNotAnAccount,
-    #[error("max fee per gas less than block base fee")]
-    MaxFeePerGasTooLow,
+    /// Transaction `max_fee_per_gas` is lower than the minimum currently accepted by the node. `required` can be used
+    /// to resubmit the transaction without re-querying the gas price.
+    #[error(
+        "max fee per gas less than block base fee: provided {provided}, required at least {required}"
+    )]
+    MaxFeePerGasTooLow { provided: U256, required: u64 },
     #[error("max priority fee per gas higher than max fee per gas")]
     MaxPriorityFeeGreaterThanMaxFee,
     #[error(
@@ -55,6 +103,9 @@ pub enum SubmitTxError {
         "too many factory dependencies in the transaction. {0} provided, while only {1} allowed"
     )]
     TooManyFactoryDependencies(usize, usize),
+    /// Factory dependency with the specified index duplicates one of the preceding dependencies.
+    #[error("factory dependency #{0} duplicates another factory dependency in the transaction")]
+    DuplicateFactoryDep(usize),
     #[error("max fee per gas higher than 2^32")]
     FeePerGasTooHigh,
     #[error("max fee per pubdata byte higher than 2^32")]
@@ -70,8 +121,70 @@ pub enum SubmitTxError {
     /// Error returned from main node
     #[error("{0}")]
     ProxyError(#[from] EnrichedClientError),
-    #[error("not enough gas to publish compressed bytecodes")]
-    FailedToPublishCompressedBytecodes,
+    /// Compressed bytecodes of the transaction were not published. Contains the cause, e.g. the offending bytecode hash.
+    #[error("not enough gas to publish compressed bytecodes: {0}")]
+    FailedToPublishCompressedBytecodes(BytecodeCompressionError),
+    /// Transaction type cannot be submitted via the API. L1 -> L2 (priority) and protocol upgrade transactions
+    /// are only ever received from L1 by the `eth_watch` component.
+    #[error("transaction type {0:?} is not supported by the API")]
+    UnsupportedTxType(TransactionType),
+    /// Amount minted by an L1 transaction doesn't cover its gas and value.
+    #[error("minted amount {0} does not cover L1 transaction gas and value ({1})")]
+    InsufficientMintForL1Tx(U256, U256),
+    /// Gas estimation could not find a gas limit within bounds under which the transaction succeeds.
+    /// Unlike [`Self::ExecutionReverted`], this does not mean that the transaction always reverts.
+    #[error(
+        "failed to estimate gas: no gas limit within bounds allows executing the transaction ({0})"
+    )]
+    GasEstimationFailed(String),
+    /// Transaction publishes more pubdata than fits into a batch. This includes pubdata produced at runtime,
+    /// e.g. by contracts deployed during execution, which isn't covered by checking declared factory deps.
+    #[error("exceeds limit for published pubdata: {0} bytes published, while only {1} allowed")]
+    PubdataLimitExceeded(u64, u64),
+    /// Transaction publishes more pubdata than allowed for a single transaction by the server configuration.
+    #[error("exceeds per-transaction pubdata limit: {0} bytes published, while only {1} allowed")]
+    TxPubdataLimitExceeded(u64, u64),
+    /// One of the transactions preceding the estimated one (e.g., in a bundle) has failed, so the estimated
+    /// transaction cannot be executed in the expected state. Contains the index of the failed transaction.
+    #[error("preceding transaction #{0} failed: {1}")]
+    PrecedingTxFailed(usize, String),
+    /// Gas required by the transaction regardless of its execution (e.g., for publishing its factory deps)
+    /// exceeds the block gas limit.
+    #[error("transaction requires at least {0} gas, which exceeds the block gas limit {1}")]
+    ExceedsBlockGasLimit(u32, u32),
+    /// VM execution was aborted because it took longer than allowed by the configured execution timeout.
+    #[error("transaction execution timed out")]
+    ExecutionTimedOut,
+    /// `eth_call` targets a contract outside the configured allowlist.
+    #[error("calls to contract {0:?} are not allowed")]
+    CallTargetNotAllowed(Address),
+    /// Data returned by `eth_call` exceeds the configured limit.
+    #[error("call response is too large: {0} bytes, while the limit is {1} bytes")]
+    ResponseTooLarge(usize, usize),
+    /// Submitted transaction targets an address from the configured set of forbidden targets.
+    #[error("transactions targeting {0:?} are forbidden")]
+    ForbiddenTarget(Address),
+    /// Transaction `gas_per_pubdata_limit` exceeds the maximum supported by the VM. Only returned if
+    /// the sender is configured to reject such transactions.
+    #[error("gas per pubdata limit {0} is higher than the maximum allowed {1}")]
+    GasPerPubdataLimitTooHigh(U256, u64),
+    /// Submitted transaction is initiated by the fee account. Only returned if the sender is configured
+    /// to reject such transactions.
+    #[error("transactions initiated by the fee account {0:?} are not accepted")]
+    InitiatorIsFeeAccount(Address),
+    /// Transaction deploys a contract via CREATE2 to an address that already has code or a non-zero nonce.
+    #[error("cannot deploy contract to {0:?}: address is already occupied")]
+    DeploymentAddressOccupied(Address),
+    /// Pending block resolved for the request lags too far behind the latest miniblock sealed at the transaction
+    /// destination, e.g. because the replica DB is behind.
+    #[error(
+        "node state is stale: pending miniblock #{pending_block_number} lags behind latest sealed \
+         miniblock #{latest_block_number}"
+    )]
+    StaleNodeState {
+        pending_block_number: MiniblockNumber,
+        latest_block_number: MiniblockNumber,
+    },
     /// Catch-all internal error (e.g., database error) that should not be exposed to the caller.
     #[error("internal error")]
     Internal(#[from] anyhow::Error),
@@ -80,14 +193,19 @@ pub enum SubmitTxError {
 impl SubmitTxError {
     pub fn prom_error_code(&self) -> &'static str {
         match self {
-            Self::NonceIsTooHigh(_, _, _) => "nonce-is-too-high",
-            Self::NonceIsTooLow(_, _, _) => "nonce-is-too-low",
+            Self::NonceIsTooHigh(..) => "nonce-is-too-high",
+            Self::NonceIsTooLow(..) => "nonce-is-too-low",
+            Self::NonceGap(..) => "nonce-gap",
             Self::InsertionInProgress => "insertion-in-progress",
+            Self::ConcurrentNonceConflict(_) => "concurrent-nonce-conflict",
+            Self::AlreadyInMempool(_) => "already-in-mempool",
+            Self::TransactionExpired(_) => "transaction-expired",
+            Self::InvalidSignature => "invalid-signature",
             Self::IncorrectTx(_) => "incorrect-tx",
             Self::NotEnoughBalanceForFeeValue(_, _, _) => "not-enough-balance-for-fee",
             Self::ExecutionReverted(_, _) => "execution-reverted",
             Self::GasLimitIsTooBig => "gas-limit-is-too-big",
-            Self::Unexecutable(_) => "unexecutable",
+            Self::Unexecutable { .. } => "unexecutable",
             Self::RateLimitExceeded => "rate-limit-exceeded",
             Self::ServerShuttingDown => "shutting-down",
             Self::BootloaderFailure(_) => "bootloader-failure",
@@ -96,26 +214,99 @@ impl SubmitTxError {
             Self::PaymasterValidationFailed(_) => "failed-paymaster-validation",
             Self::PrePaymasterPreparationFailed(_) => "failed-prepaymaster-preparation",
             Self::FromIsNotAnAccount => "from-is-not-an-account",
-            Self::MaxFeePerGasTooLow => "max-fee-per-gas-too-low",
+            Self::MaxFeePerGasTooLow { .. } => "max-fee-per-gas-too-low",
             Self::MaxPriorityFeeGreaterThanMaxFee => "max-priority-fee-greater-than-max-fee",
             Self::UnexpectedVMBehavior(_) => "unexpected-vm-behavior",
             Self::UnrealisticPubdataPriceLimit => "unrealistic-pubdata-price-limit",
             Self::TooManyFactoryDependencies(_, _) => "too-many-factory-dependencies",
+            Self::DuplicateFactoryDep(_) => "duplicate-factory-dep",
             Self::FeePerGasTooHigh => "gas-price-limit-too-high",
             Self::FeePerPubdataByteTooHigh => "pubdata-price-limit-too-high",
             Self::InsufficientFundsForTransfer => "insufficient-funds-for-transfer",
             Self::IntrinsicGas => "intrinsic-gas",
             Self::ProxyError(_) => "proxy-error",
-            Self::FailedToPublishCompressedBytecodes => "failed-to-publish-compressed-bytecodes",
+            Self::FailedToPublishCompressedBytecodes(_) => "failed-to-publish-compressed-bytecodes",
+            Self::UnsupportedTxType(_) => "unsupported-tx-type",
+            Self::InsufficientMintForL1Tx(_, _) => "insufficient-mint-for-l1-tx",
+            Self::GasEstimationFailed(_) => "gas-estimation-failed",
+            Self::PubdataLimitExceeded(_, _) => "pubdata-limit-exceeded",
+            Self::TxPubdataLimitExceeded(_, _) => "tx-pubdata-limit-exceeded",
+            Self::PrecedingTxFailed(_, _) => "preceding-tx-failed",
+            Self::ExceedsBlockGasLimit(_, _) => "exceeds-block-gas-limit",
+            Self::ExecutionTimedOut => "execution-timed-out",
+            Self::CallTargetNotAllowed(_) => "call-target-not-allowed",
+            Self::ResponseTooLarge(_, _) => "response-too-large",
+            Self::ForbiddenTarget(_) => "forbidden-target",
+            Self::GasPerPubdataLimitTooHigh(_, _) => "gas-per-pubdata-limit-too-high",
+            Self::InitiatorIsFeeAccount(_) => "initiator-is-fee-account",
+            Self::DeploymentAddressOccupied(_) => "deployment-address-occupied",
+            Self::StaleNodeState { .. } => "stale-node-state",
             Self::Internal(_) => "internal",
         }
     }
 
+    /// Checks whether this error is caused by the submitted transaction or request (e.g., an incorrect nonce
+    /// or an insufficient fee) rather than by a node malfunction. User errors shouldn't trigger alerts.
+    pub fn is_user_error(&self) -> bool {
+        match self {
+            Self::NonceIsTooHigh(..)
+            | Self::NonceIsTooLow(..)
+            | Self::NonceGap(..)
+            | Self::InsertionInProgress
+            | Self::ConcurrentNonceConflict(_)
+            | Self::AlreadyInMempool(_)
+            | Self::TransactionExpired(_)
+            | Self::InvalidSignature
+            | Self::IncorrectTx(_)
+            | Self::NotEnoughBalanceForFeeValue(..)
+            | Self::ExecutionReverted(..)
+            | Self::GasLimitIsTooBig
+            | Self::Unexecutable { .. }
+            | Self::RateLimitExceeded
+            | Self::ValidationFailed(_)
+            | Self::FailedToChargeFee(_)
+            | Self::PaymasterValidationFailed(_)
+            | Self::PrePaymasterPreparationFailed(_)
+            | Self::FromIsNotAnAccount
+            | Self::MaxFeePerGasTooLow { .. }
+            | Self::MaxPriorityFeeGreaterThanMaxFee
+            | Self::UnrealisticPubdataPriceLimit
+            | Self::TooManyFactoryDependencies(..)
+            | Self::DuplicateFactoryDep(_)
+            | Self::FeePerGasTooHigh
+            | Self::FeePerPubdataByteTooHigh
+            | Self::InsufficientFundsForTransfer
+            | Self::IntrinsicGas
+            | Self::FailedToPublishCompressedBytecodes(_)
+            | Self::UnsupportedTxType(_)
+            | Self::InsufficientMintForL1Tx(..)
+            | Self::GasEstimationFailed(_)
+            | Self::PubdataLimitExceeded(..)
+            | Self::TxPubdataLimitExceeded(..)
+            | Self::PrecedingTxFailed(..)
+            | Self::ExceedsBlockGasLimit(..)
+            | Self::ExecutionTimedOut
+            | Self::CallTargetNotAllowed(_)
+            | Self::ResponseTooLarge(..)
+            | Self::ForbiddenTarget(_)
+            | Self::GasPerPubdataLimitTooHigh(..)
+            | Self::InitiatorIsFeeAccount(_)
+            | Self::DeploymentAddressOccupied(_) => true,
+            // Errors returned by the main node itself (as opposed to transport errors) are caused by the transaction.
+            Self::ProxyError(err) => matches!(err.as_ref(), ClientError::Call(_)),
+            Self::ServerShuttingDown
+            | Self::BootloaderFailure(_)
+            | Self::UnexpectedVMBehavior(_)
+            | Self::StaleNodeState { .. }
+            | Self::Internal(_) => false,
+        }
+    }
+
     pub fn data(&self) -> Vec<u8> {
-        if let Self::ExecutionReverted(_, data) = self {
-            data.clone()
-        } else {
-            Vec::new()
+        match self {
+            Self::ExecutionReverted(_, data) => data.clone(),
+            Self::Unexecutable { code, .. } => u256_to_h256(code.code().into()).as_bytes().to_vec(),
+            _ => Vec::new(),
         }
     }
 }
@@ -145,6 +336,7 @@ impl From<SandboxExecutionError> for SubmitTxError {
             SandboxExecutionError::FailedToPayForTransaction(reason) => {
                 Self::FailedToChargeFee(reason)
             }
+            SandboxExecutionError::ExecutionTimedOut => Self::ExecutionTimedOut,
         }
     }
 }
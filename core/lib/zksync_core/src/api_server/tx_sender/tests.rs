@@ -1,22 +1,77 @@
 //! Tests for the transaction sender.
 
-use zksync_types::{get_nonce_key, L1BatchNumber, StorageLog};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use assert_matches::assert_matches;
+use multivm::interface::{BytecodeCompressionError, ExecutionResult, VmRevertReason};
+use test_casing::test_casing;
+use tokio::runtime::Handle;
+use zksync_state::{PostgresStorage, ReadStorage};
+use zksync_types::{
+    block::MiniblockHeader,
+    fee_model::FeeParams,
+    get_known_code_key, get_nonce_key,
+    l1::L1Tx,
+    utils::deployed_address_create,
+    zk_evm_types::{LogQuery, Timestamp},
+    Execute, L1BatchNumber, ProtocolVersion, StorageKey, StorageLog, StorageLogQuery,
+    StorageLogQueryType,
+};
+use zksync_utils::{
+    bytecode::{compress_bytecode, hash_bytecode},
+    u256_to_h256,
+};
+use zksync_web3_decl::{
+    error::{EnrichedClientError, Web3Error},
+    jsonrpsee::{core::ClientError, types::ErrorObjectOwned},
+};
 
 use super::*;
 use crate::{
     api_server::execution_sandbox::{testonly::MockTransactionExecutor, VmConcurrencyBarrier},
     genesis::{insert_genesis_batch, GenesisParams},
-    utils::testonly::{create_miniblock, prepare_recovery_snapshot, MockBatchFeeParamsProvider},
+    state_keeper::seal_criteria::{SealResolution, SequencerSealer},
+    utils::testonly::{
+        create_l2_transaction, create_miniblock, prepare_recovery_snapshot,
+        MockBatchFeeParamsProvider,
+    },
 };
 
+/// Returns the `TxSender` config used by [`create_test_tx_sender()`].
+pub(crate) fn test_tx_sender_config(l2_chain_id: L2ChainId) -> TxSenderConfig {
+    TxSenderConfig::new(
+        &StateKeeperConfig::for_tests(),
+        &Web3JsonRpcConfig::for_tests(),
+        l2_chain_id,
+    )
+}
+
 pub(crate) async fn create_test_tx_sender(
     pool: ConnectionPool<Core>,
     l2_chain_id: L2ChainId,
     tx_executor: TransactionExecutor,
 ) -> (TxSender, VmConcurrencyBarrier) {
+    let tx_sender_config = test_tx_sender_config(l2_chain_id);
+    create_test_tx_sender_with_config(pool, tx_sender_config, tx_executor).await
+}
+
+/// Same as [`create_test_tx_sender()`], but with a custom config, which is validated before building the sender.
+pub(crate) async fn create_test_tx_sender_with_config(
+    pool: ConnectionPool<Core>,
+    tx_sender_config: TxSenderConfig,
+    tx_executor: TransactionExecutor,
+) -> (TxSender, VmConcurrencyBarrier) {
+    tx_sender_config
+        .validate()
+        .expect("invalid `TxSender` config");
     let web3_config = Web3JsonRpcConfig::for_tests();
     let state_keeper_config = StateKeeperConfig::for_tests();
-    let tx_sender_config = TxSenderConfig::new(&state_keeper_config, &web3_config, l2_chain_id);
 
     let storage_caches = PostgresStorageCaches::new(1, 1);
     let batch_fee_model_input_provider = Arc::new(MockBatchFeeParamsProvider::default());
@@ -29,12 +84,169 @@ pub(crate) async fn create_test_tx_sender(
         batch_fee_model_input_provider,
         storage_caches,
     )
-    .await;
+    .await
+    .unwrap();
 
     Arc::get_mut(&mut tx_sender.0).unwrap().executor = tx_executor;
     (tx_sender, vm_barrier)
 }
 
+/// Creates a transaction passing `TxSender::validate_tx()` checks and funds its initiator account.
+pub(crate) async fn create_funded_tx(storage: &mut Connection<'_, Core>) -> L2Tx {
+    let fee_per_gas = StateKeeperConfig::for_tests().minimal_l2_gas_price;
+    let mut tx = create_l2_transaction(fee_per_gas, 50);
+    tx.common_data.fee.gas_limit =
+        (get_intrinsic_constants(VmVersion::latest()).l2_tx_intrinsic_gas * 2).into();
+
+    let balance_key = storage_key_for_eth_balance(&tx.initiator_account());
+    let balance_log = StorageLog::new_write_log(balance_key, u256_to_h256(U256::one() << 64));
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::zero(), vec![balance_log])])
+        .await
+        .unwrap();
+    tx
+}
+
+#[tokio::test]
+async fn building_tx_sender_with_vm_concurrency() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    let tx_sink = master_pool_sink::MasterPoolSink::new(pool.clone());
+    let (builder, vm_barrier) =
+        TxSenderBuilder::new(tx_sender_config, pool, Arc::new(tx_sink)).with_vm_concurrency(2);
+    let tx_sender = builder
+        .build(
+            Arc::new(MockBatchFeeParamsProvider::default()),
+            None,
+            ApiContracts::load_from_disk(),
+            PostgresStorageCaches::new(1, 1),
+        )
+        .await
+        .unwrap();
+
+    let limiter = tx_sender.vm_concurrency_limiter();
+    let permits = [
+        limiter.acquire().await.unwrap(),
+        limiter.acquire().await.unwrap(),
+    ];
+    let acquire_result = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+    assert!(
+        acquire_result.is_err(),
+        "limiter issued more permits than configured"
+    );
+
+    // The returned barrier controls the limiter shutdown.
+    vm_barrier.close();
+    assert!(limiter.acquire().await.is_none());
+    drop(permits);
+    tokio::time::timeout(Duration::from_secs(1), vm_barrier.wait_until_stopped())
+        .await
+        .expect("VM barrier wasn't stopped after dropping permits");
+}
+
+#[tokio::test]
+async fn building_tx_sender_without_vm_concurrency_limiter() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    let tx_sink = master_pool_sink::MasterPoolSink::new(pool.clone());
+    let err = TxSenderBuilder::new(tx_sender_config, pool, Arc::new(tx_sink))
+        .build(
+            Arc::new(MockBatchFeeParamsProvider::default()),
+            None,
+            ApiContracts::load_from_disk(),
+            PostgresStorageCaches::new(1, 1),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("VM concurrency limiter"),
+        "{err:#}"
+    );
+}
+
+#[tokio::test]
+async fn building_tx_sender_with_required_sealer_missing() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    let tx_sink = master_pool_sink::MasterPoolSink::new(pool.clone());
+    let (builder, _vm_barrier) =
+        TxSenderBuilder::new(tx_sender_config, pool, Arc::new(tx_sink)).with_vm_concurrency(1);
+    let err = builder
+        .require_sealer()
+        .build(
+            Arc::new(MockBatchFeeParamsProvider::default()),
+            None,
+            ApiContracts::load_from_disk(),
+            PostgresStorageCaches::new(1, 1),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("sealer is required"), "{err:#}");
+}
+
+#[tokio::test]
+async fn reporting_available_vm_permits() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    let tx_sink = master_pool_sink::MasterPoolSink::new(pool.clone());
+    let (builder, _vm_barrier) =
+        TxSenderBuilder::new(tx_sender_config, pool, Arc::new(tx_sink)).with_vm_concurrency(3);
+    let tx_sender = builder
+        .build(
+            Arc::new(MockBatchFeeParamsProvider::default()),
+            None,
+            ApiContracts::load_from_disk(),
+            PostgresStorageCaches::new(1, 1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(tx_sender.available_vm_permits(), 3);
+
+    let limiter = tx_sender.vm_concurrency_limiter();
+    let first_permit = limiter.acquire().await.unwrap();
+    let _second_permit = limiter.acquire().await.unwrap();
+    assert_eq!(tx_sender.available_vm_permits(), 1);
+    drop(first_permit);
+    assert_eq!(tx_sender.available_vm_permits(), 2);
+}
+
+#[tokio::test]
+async fn reporting_effective_config() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::from(270));
+    tx_sender_config.gas_price_scale_factor = 1.5;
+    tx_sender_config.min_gas_per_pubdata = Some(100);
+    tx_sender_config.vm_execution_timeout = Some(Duration::from_secs(3));
+    let tx_sink = master_pool_sink::MasterPoolSink::new(pool.clone());
+    let (builder, _vm_barrier) =
+        TxSenderBuilder::new(tx_sender_config.clone(), pool, Arc::new(tx_sink))
+            .with_vm_concurrency(1);
+    let tx_sender = builder
+        .build(
+            Arc::new(MockBatchFeeParamsProvider::default()),
+            None,
+            ApiContracts::load_from_disk(),
+            PostgresStorageCaches::new(1, 1),
+        )
+        .await
+        .unwrap();
+
+    let snapshot = tx_sender.effective_config();
+    assert_eq!(snapshot, TxSenderConfigSnapshot::from(&tx_sender_config));
+    assert_eq!(snapshot.chain_id, L2ChainId::from(270));
+    assert_eq!(snapshot.gas_price_scale_factor, 1.5);
+    assert_eq!(snapshot.min_gas_per_pubdata, Some(100));
+    assert_eq!(snapshot.vm_execution_timeout_ms, Some(3_000));
+    assert_eq!(
+        snapshot.max_allowed_l2_tx_gas_limit,
+        StateKeeperConfig::for_tests().max_allowed_l2_tx_gas_limit
+    );
+
+    let snapshot_json = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(snapshot_json["gasPriceScaleFactor"], 1.5);
+}
+
 #[tokio::test]
 async fn getting_nonce_for_account() {
     let l2_chain_id = L2ChainId::default();
@@ -56,8 +268,9 @@ async fn getting_nonce_for_account() {
     let tx_executor = MockTransactionExecutor::default().into();
     let (tx_sender, _) = create_test_tx_sender(pool.clone(), l2_chain_id, tx_executor).await;
 
-    let nonce = tx_sender.get_expected_nonce(test_address).await.unwrap();
+    let (nonce, block_number) = tx_sender.get_expected_nonce(test_address).await.unwrap();
     assert_eq!(nonce, Nonce(123));
+    assert_eq!(block_number, MiniblockNumber(0));
 
     // Insert another miniblock with a new nonce log.
     storage
@@ -75,13 +288,78 @@ async fn getting_nonce_for_account() {
         .await
         .unwrap();
 
-    let nonce = tx_sender.get_expected_nonce(test_address).await.unwrap();
+    let (nonce, block_number) = tx_sender.get_expected_nonce(test_address).await.unwrap();
     assert_eq!(nonce, Nonce(321));
+    assert_eq!(block_number, MiniblockNumber(1));
     let missing_address = Address::repeat_byte(0xff);
-    let nonce = tx_sender.get_expected_nonce(missing_address).await.unwrap();
+    let (nonce, _) = tx_sender.get_expected_nonce(missing_address).await.unwrap();
     assert_eq!(nonce, Nonce(0));
 }
 
+#[tokio::test]
+async fn getting_nonces_for_multiple_accounts() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let accounts = [Address::repeat_byte(1), Address::repeat_byte(2)];
+    let nonce_logs: Vec<_> = accounts
+        .iter()
+        .zip([123, 25])
+        .map(|(address, nonce)| {
+            StorageLog::new_write_log(get_nonce_key(address), H256::from_low_u64_be(nonce))
+        })
+        .collect();
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::default(), nonce_logs)])
+        .await
+        .unwrap();
+
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+    let missing_address = Address::repeat_byte(0xff);
+    let nonces = tx_sender
+        .get_expected_nonces(&[accounts[0], accounts[1], missing_address])
+        .await
+        .unwrap();
+    let expected_nonces = HashMap::from([
+        (accounts[0], Nonce(123)),
+        (accounts[1], Nonce(25)),
+        (missing_address, Nonce(0)),
+    ]);
+    assert_eq!(nonces, expected_nonces);
+
+    // Nonces must be consistent with ones returned for individual accounts.
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+    let nonce_log =
+        StorageLog::new_write_log(get_nonce_key(&accounts[0]), H256::from_low_u64_be(321));
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(MiniblockNumber(1), &[(H256::default(), vec![nonce_log])])
+        .await
+        .unwrap();
+    let nonces = tx_sender
+        .get_expected_nonces(&[accounts[0], accounts[1], missing_address])
+        .await
+        .unwrap();
+    assert_eq!(nonces[&accounts[0]], Nonce(321));
+    for (address, nonce) in nonces {
+        let (expected_nonce, _) = tx_sender.get_expected_nonce(address).await.unwrap();
+        assert_eq!(nonce, expected_nonce, "{address:?}");
+    }
+    assert_eq!(
+        tx_sender.get_expected_nonces(&[]).await.unwrap(),
+        HashMap::new()
+    );
+}
+
 #[tokio::test]
 async fn getting_nonce_for_account_after_snapshot_recovery() {
     const SNAPSHOT_MINIBLOCK_NUMBER: MiniblockNumber = MiniblockNumber(42);
@@ -106,12 +384,13 @@ async fn getting_nonce_for_account_after_snapshot_recovery() {
     let tx_executor = MockTransactionExecutor::default().into();
     let (tx_sender, _) = create_test_tx_sender(pool.clone(), l2_chain_id, tx_executor).await;
 
-    let nonce = tx_sender.get_expected_nonce(test_address).await.unwrap();
+    let (nonce, block_number) = tx_sender.get_expected_nonce(test_address).await.unwrap();
     assert_eq!(nonce, Nonce(123));
-    let nonce = tx_sender.get_expected_nonce(other_address).await.unwrap();
+    assert_eq!(block_number, SNAPSHOT_MINIBLOCK_NUMBER);
+    let (nonce, _) = tx_sender.get_expected_nonce(other_address).await.unwrap();
     assert_eq!(nonce, Nonce(25));
     let missing_address = Address::repeat_byte(0xff);
-    let nonce = tx_sender.get_expected_nonce(missing_address).await.unwrap();
+    let (nonce, _) = tx_sender.get_expected_nonce(missing_address).await.unwrap();
     assert_eq!(nonce, Nonce(0));
 
     storage
@@ -132,10 +411,4323 @@ async fn getting_nonce_for_account_after_snapshot_recovery() {
         .await
         .unwrap();
 
-    let nonce = tx_sender.get_expected_nonce(test_address).await.unwrap();
+    let (nonce, _) = tx_sender.get_expected_nonce(test_address).await.unwrap();
     assert_eq!(nonce, Nonce(321));
-    let nonce = tx_sender.get_expected_nonce(other_address).await.unwrap();
+    let (nonce, _) = tx_sender.get_expected_nonce(other_address).await.unwrap();
     assert_eq!(nonce, Nonce(25));
-    let nonce = tx_sender.get_expected_nonce(missing_address).await.unwrap();
+    let (nonce, _) = tx_sender.get_expected_nonce(missing_address).await.unwrap();
     assert_eq!(nonce, Nonce(0));
 }
+
+#[tokio::test]
+async fn nonce_errors_report_miniblock_nonce_was_read_at() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_nonce_ahead = 0;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let mut gapped_tx = tx.clone();
+    gapped_tx.common_data.nonce = Nonce(5);
+    let err = tx_sender.submit_tx(gapped_tx).await.unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::NonceIsTooHigh(0, 0, 5, MiniblockNumber(1))
+    );
+
+    // Sealing a miniblock that bumps the nonce is reflected in the error for a nonce that's too low.
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(2))
+        .await
+        .unwrap();
+    let nonce_log = StorageLog::new_write_log(
+        get_nonce_key(&tx.initiator_account()),
+        H256::from_low_u64_be(1),
+    );
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(MiniblockNumber(2), &[(H256::default(), vec![nonce_log])])
+        .await
+        .unwrap();
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::NonceIsTooLow(1, 1, 0, MiniblockNumber(2))
+    );
+    assert!(err.to_string().contains("miniblock #2"), "{err}");
+}
+
+#[tokio::test]
+async fn getting_nonce_is_retried_on_transient_db_errors() {
+    let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let test_address = Address::repeat_byte(1);
+    let nonce_log =
+        StorageLog::new_write_log(get_nonce_key(&test_address), H256::from_low_u64_be(5));
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::default(), vec![nonce_log])])
+        .await
+        .unwrap();
+
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.replica_connection_acquire_timeout = Some(Duration::from_millis(50));
+    tx_sender_config.nonce_read_retry_interval = Duration::from_millis(100);
+    tx_sender_config.nonce_read_retries = 0;
+    let (tx_sender, _) = create_test_tx_sender_with_config(
+        pool.clone(),
+        tx_sender_config.clone(),
+        MockTransactionExecutor::default().into(),
+    )
+    .await;
+
+    // The only connection in the pool is held by `storage`, so reading the nonce fails without retries.
+    let err = tx_sender
+        .get_expected_nonce(test_address)
+        .await
+        .unwrap_err();
+    assert!(is_transient_db_error(&err), "{err:#}");
+
+    // Release the connection after the first attempt has failed; the retry should succeed.
+    tx_sender_config.nonce_read_retries = 1;
+    let (tx_sender, _) = create_test_tx_sender_with_config(
+        pool.clone(),
+        tx_sender_config,
+        MockTransactionExecutor::default().into(),
+    )
+    .await;
+    let release_connection = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(storage);
+    };
+    let (nonce, ()) = tokio::join!(
+        tx_sender.get_expected_nonce(test_address),
+        release_connection
+    );
+    assert_eq!(nonce.unwrap().0, Nonce(5));
+}
+
+#[tokio::test]
+async fn submitting_tx_with_unsupported_type() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+
+    for tx_type in [
+        TransactionType::PriorityOpTransaction,
+        TransactionType::ProtocolUpgradeTransaction,
+    ] {
+        let mut tx = create_l2_transaction(10, 100);
+        tx.common_data.transaction_type = tx_type;
+        let err = tx_sender.submit_tx(tx).await.unwrap_err();
+        assert_matches!(err, SubmitTxError::UnsupportedTxType(ty) if ty == tx_type);
+    }
+}
+
+#[tokio::test]
+async fn submitting_tx_invokes_executor_once() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+    let tx_hash = tx.hash();
+
+    let executor_calls = Arc::new(AtomicUsize::new(0));
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses({
+        let executor_calls = executor_calls.clone();
+        move |tx, _| {
+            assert_eq!(tx.hash(), tx_hash);
+            executor_calls.fetch_add(1, Ordering::SeqCst);
+            ExecutionResult::Success { output: vec![] }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let submission_result = tx_sender.submit_tx(tx).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+    assert_eq!(executor_calls.load(Ordering::SeqCst), 1);
+}
+
+/// Sink returning the predefined submission result without persisting transactions.
+#[derive(Debug)]
+struct FixedResultTxSink(L2TxSubmissionResult);
+
+#[async_trait::async_trait]
+impl TxSink for FixedResultTxSink {
+    async fn submit_tx(
+        &self,
+        _tx: L2Tx,
+        _execution_metrics: TransactionExecutionMetrics,
+        _valid_until: Option<DateTime<Utc>>,
+    ) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        Ok(self.0)
+    }
+}
+
+const SUBMISSION_RESULTS: [L2TxSubmissionResult; 6] = [
+    L2TxSubmissionResult::Added,
+    L2TxSubmissionResult::Replaced,
+    L2TxSubmissionResult::AlreadyExecuted,
+    L2TxSubmissionResult::Duplicate,
+    L2TxSubmissionResult::Proxied,
+    L2TxSubmissionResult::InsertionInProgress,
+];
+
+#[test_casing(6, SUBMISSION_RESULTS)]
+#[tokio::test]
+async fn handling_tx_sink_submission_result(sink_result: L2TxSubmissionResult) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+    let tx_hash = tx.hash();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (mut tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+    Arc::get_mut(&mut tx_sender.0).unwrap().tx_sink = Arc::new(FixedResultTxSink(sink_result));
+
+    let submission_result = tx_sender.submit_tx(tx).await;
+    match sink_result {
+        L2TxSubmissionResult::Added
+        | L2TxSubmissionResult::Replaced
+        | L2TxSubmissionResult::Proxied => {
+            assert_eq!(submission_result.unwrap(), sink_result);
+        }
+        L2TxSubmissionResult::AlreadyExecuted => {
+            assert_matches!(
+                submission_result.unwrap_err(),
+                SubmitTxError::NonceIsTooLow(0, _, 0, _)
+            );
+        }
+        L2TxSubmissionResult::Duplicate => {
+            // The transaction is not persisted by the sink, so it cannot be in the mempool.
+            assert_matches!(
+                submission_result.unwrap_err(),
+                SubmitTxError::IncorrectTx(TxDuplication(hash)) if hash == tx_hash
+            );
+        }
+        L2TxSubmissionResult::InsertionInProgress => {
+            assert_matches!(
+                submission_result.unwrap_err(),
+                SubmitTxError::InsertionInProgress
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn submitting_tx_with_single_pool() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+    let tx_hash = tx.hash();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    // Replica and master pools are the same, so the replica pool is used for inserting transactions as well.
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+    assert!(tx_sender.0.replica_connection_pool.has_same_database(&pool));
+
+    let submission_result = tx_sender.submit_tx(tx.clone()).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+    let details = storage
+        .transactions_web3_dal()
+        .get_transaction_details(tx_hash)
+        .await
+        .unwrap()
+        .expect("transaction not persisted");
+    assert_matches!(details.status, api::TransactionStatus::Pending);
+
+    // Reads performed by `TxSender` see the inserted transaction as well.
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::AlreadyInMempool(hash) if hash == tx_hash);
+}
+
+#[tokio::test]
+async fn validating_tx_with_tampered_signature() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+    // Re-sign the transaction since it's modified after signing and uses another chain ID.
+    let private_key = H256::random();
+    let initiator = PackedEthSignature::address_from_private_key(&private_key).unwrap();
+    tx.common_data.initiator_address = initiator;
+    let signed_message = tx.get_signed_bytes(L2ChainId::default());
+    tx.set_signature(PackedEthSignature::sign_raw(&private_key, &signed_message).unwrap());
+    let balance_log = StorageLog::new_write_log(
+        storage_key_for_eth_balance(&initiator),
+        u256_to_h256(U256::one() << 64),
+    );
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::zero(), vec![balance_log])])
+        .await
+        .unwrap();
+
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+    let mut tampered_tx = tx.clone();
+    tampered_tx.common_data.fee.gas_limit += 1;
+    // Without the early check, signatures are only checked by the VM, which is mocked.
+    tx_sender.validate_tx(&tampered_tx).await.unwrap();
+
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.early_signature_check = true;
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor).await;
+    tx_sender.validate_tx(&tx).await.unwrap();
+    let err = tx_sender.validate_tx(&tampered_tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::InvalidSignature);
+
+    // Custom accounts are exempt from the check.
+    let code_log = StorageLog::new_write_log(get_code_key(&initiator), H256::repeat_byte(1));
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::zero(), vec![code_log])])
+        .await
+        .unwrap();
+    tx_sender.validate_tx(&tampered_tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn concurrent_submissions_with_same_nonce() {
+    let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let tx_sink = master_pool_sink::MasterPoolSink::new(pool.clone());
+    let tx = create_l2_transaction(10, 100);
+    let tx_hash = tx.hash();
+    let metrics = TransactionExecutionMetrics::default();
+
+    // Occupy the only DB connection, so that the first submission stalls midway.
+    let storage = pool.connection().await.unwrap();
+    let first_submission = tx_sink.submit_tx(tx.clone(), metrics, None);
+    tokio::pin!(first_submission);
+    let first_result = tokio::time::timeout(Duration::from_millis(50), &mut first_submission).await;
+    assert!(first_result.is_err(), "{first_result:?}");
+
+    let submission_result = tx_sink.submit_tx(tx.clone(), metrics, None).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::InsertionInProgress);
+    let mut other_tx = create_l2_transaction(20, 100);
+    other_tx.common_data.initiator_address = tx.initiator_account();
+    other_tx.common_data.nonce = tx.nonce();
+    let err = tx_sink
+        .submit_tx(other_tx, metrics, None)
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::ConcurrentNonceConflict(hash) if hash == tx_hash);
+
+    // Cancelling the first submission should not leave it registered as in-flight.
+    drop(first_submission);
+    drop(storage);
+    let submission_result = tx_sink.submit_tx(tx, metrics, None).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+}
+
+#[tokio::test]
+async fn submitting_tx_with_unpublishable_bytecodes() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+    tx.execute.factory_deps = Some(vec![vec![1; 32]]);
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    tx_executor.set_published_bytecodes_responses(|_| false);
+    tx_executor.set_validation_responses(|_, _| panic!("validation should be skipped"));
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::FailedToPublishCompressedBytecodes(_));
+}
+
+#[tokio::test]
+async fn submitting_expired_tx() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let valid_until = Utc::now() - chrono::Duration::seconds(1);
+    let err = tx_sender
+        .submit_tx_with_deadline(tx.clone(), Some(valid_until))
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::TransactionExpired(ts) if ts == valid_until);
+    let stored_tx = storage
+        .transactions_web3_dal()
+        .get_transaction_by_hash(tx.hash(), L2ChainId::default())
+        .await
+        .unwrap();
+    assert!(stored_tx.is_none(), "{stored_tx:?}");
+
+    let valid_until = Utc::now() + chrono::Duration::hours(1);
+    let submission_result = tx_sender
+        .submit_tx_with_deadline(tx.clone(), Some(valid_until))
+        .await
+        .unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+    let mempool_txs = storage
+        .transactions_dal()
+        .sync_mempool(&[], &[], 0, 0, 10)
+        .await
+        .unwrap();
+    assert_eq!(mempool_txs.len(), 1);
+    assert_eq!(mempool_txs[0].hash(), tx.hash());
+}
+
+#[tokio::test]
+async fn unpublishable_bytecode_is_surfaced_in_error() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+    let bytecode = vec![1; 32 * 3];
+    let compressed_bytecode = compress_bytecode(&bytecode).unwrap();
+    tx.execute.factory_deps = Some(vec![bytecode.clone()]);
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    tx_executor.set_published_bytecodes_responses(|_| false);
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    let SubmitTxError::FailedToPublishCompressedBytecodes(cause) = &err else {
+        panic!("Unexpected error: {err:?}");
+    };
+    assert_eq!(cause.bytecode_hash(), Some(hash_bytecode(&bytecode)));
+    assert_matches!(
+        cause,
+        BytecodeCompressionError::BytecodeNotPublished { original_len, compressed_len, .. }
+            if *original_len == bytecode.len() && *compressed_len == compressed_bytecode.len()
+    );
+    let message = err.to_string();
+    assert!(
+        message.contains(&format!("{:?}", hash_bytecode(&bytecode))),
+        "{message}"
+    );
+    assert_eq!(
+        err.prom_error_code(),
+        "failed-to-publish-compressed-bytecodes"
+    );
+}
+
+#[tokio::test]
+async fn getting_gas_price_with_headroom() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let (tx_sender, _) = create_test_tx_sender(
+        pool.clone(),
+        L2ChainId::default(),
+        MockTransactionExecutor::default().into(),
+    )
+    .await;
+
+    let gas_price = tx_sender.gas_price().await.unwrap();
+    assert!(gas_price > 0);
+    let padded_gas_price = tx_sender.gas_price_with_headroom(0.0).await.unwrap();
+    assert_eq!(padded_gas_price, gas_price);
+    for headroom_pct in [1.0, 10.0, 50.0] {
+        let padded_gas_price = tx_sender
+            .gas_price_with_headroom(headroom_pct)
+            .await
+            .unwrap();
+        let expected = (gas_price as f64 * (1.0 + headroom_pct / 100.0)).ceil() as u64;
+        assert_eq!(padded_gas_price, expected);
+        assert!(padded_gas_price > gas_price);
+    }
+    // The exact gas price is not affected.
+    assert_eq!(tx_sender.gas_price().await.unwrap(), gas_price);
+
+    tx_sender.gas_price_with_headroom(-1.0).await.unwrap_err();
+    tx_sender
+        .gas_price_with_headroom(f64::NAN)
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn caching_gas_price() {
+    let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    drop(storage);
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.replica_connection_acquire_timeout = Some(Duration::from_millis(50));
+    let (mut tx_sender, _) = create_test_tx_sender_with_config(
+        pool.clone(),
+        tx_sender_config,
+        MockTransactionExecutor::default().into(),
+    )
+    .await;
+    let tx_sink = Arc::new(HeadReportingTxSink::default());
+    tx_sink.set_latest_sealed_miniblock(MiniblockNumber(0));
+    Arc::get_mut(&mut tx_sender.0).unwrap().tx_sink = tx_sink.clone();
+
+    let gas_price = tx_sender.gas_price().await.unwrap();
+    // Occupy the only DB connection, so that the gas price can only be returned from the cache.
+    let storage = pool.connection().await.unwrap();
+    assert_eq!(tx_sender.gas_price().await.unwrap(), gas_price);
+
+    // A new sealed miniblock may change the pending protocol version, so it invalidates the cache
+    // even if the sender didn't observe the miniblock otherwise (e.g., when handling submissions).
+    tx_sink.set_latest_sealed_miniblock(MiniblockNumber(1));
+    let err = tx_sender.gas_price().await.unwrap_err();
+    assert!(is_transient_db_error(&err), "{err:#}");
+    drop(storage);
+    assert_eq!(tx_sender.gas_price().await.unwrap(), gas_price);
+
+    // Changing the L1 gas price invalidates the cache as well.
+    let storage = pool.connection().await.unwrap();
+    let mut fee_params = FeeParams::sensible_v1_default();
+    let FeeParams::V1(params) = &mut fee_params else {
+        unreachable!();
+    };
+    params.l1_gas_price *= 2;
+    Arc::get_mut(&mut tx_sender.0)
+        .unwrap()
+        .batch_fee_input_provider = Arc::new(MockBatchFeeParamsProvider(fee_params));
+    let err = tx_sender.gas_price().await.unwrap_err();
+    assert!(is_transient_db_error(&err), "{err:#}");
+    drop(storage);
+    let new_gas_price = tx_sender.gas_price().await.unwrap();
+    assert!(new_gas_price >= gas_price);
+}
+
+#[derive(Debug, Default)]
+struct RecordingRejectedTxSink(Mutex<Vec<(H256, &'static str)>>);
+
+impl RejectedTxSink for RecordingRejectedTxSink {
+    fn record(&self, tx_hash: H256, reason: &SubmitTxError) {
+        let mut records = self.0.lock().unwrap();
+        records.push((tx_hash, reason.prom_error_code()));
+    }
+}
+
+#[tokio::test]
+async fn recording_rejected_txs() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    tx_executor.set_published_bytecodes_responses(|tx| tx.execute.factory_deps_length() == 0);
+    let (mut tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+    let rejected_tx_sink = Arc::<RecordingRejectedTxSink>::default();
+    Arc::get_mut(&mut tx_sender.0).unwrap().rejected_tx_sink = rejected_tx_sink.clone();
+
+    let mut tx_with_high_nonce = tx.clone();
+    tx_with_high_nonce.common_data.nonce = Nonce(1_000);
+    tx_with_high_nonce.set_input(H256::random().0.to_vec(), H256::random());
+    let err = tx_sender
+        .submit_tx(tx_with_high_nonce.clone())
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::NonceIsTooHigh(..));
+
+    let mut tx_with_unpublishable_bytecode = tx.clone();
+    tx_with_unpublishable_bytecode.execute.factory_deps = Some(vec![vec![1; 32]]);
+    tx_with_unpublishable_bytecode.set_input(H256::random().0.to_vec(), H256::random());
+    let err = tx_sender
+        .submit_tx(tx_with_unpublishable_bytecode.clone())
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::FailedToPublishCompressedBytecodes(_));
+
+    // Accepted transactions must not be recorded.
+    let submission_result = tx_sender.submit_tx(tx).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+
+    let records = rejected_tx_sink.0.lock().unwrap();
+    assert_eq!(
+        *records,
+        [
+            (tx_with_high_nonce.hash(), "nonce-is-too-high"),
+            (
+                tx_with_unpublishable_bytecode.hash(),
+                "failed-to-publish-compressed-bytecodes"
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn submitting_tx_with_forbidden_target() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+    let forbidden_target = Address::repeat_byte(0x80);
+    assert_ne!(tx.recipient_account(), forbidden_target);
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(move |tx, _| {
+        assert_ne!(tx.recipient_account(), forbidden_target);
+        ExecutionResult::Success { output: vec![] }
+    });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.forbidden_tx_targets = HashSet::from([forbidden_target]);
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let mut forbidden_tx = tx.clone();
+    forbidden_tx.execute.contract_address = forbidden_target;
+    forbidden_tx.set_input(H256::random().0.to_vec(), H256::random());
+    let err = tx_sender.submit_tx(forbidden_tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::ForbiddenTarget(addr) if addr == forbidden_target);
+
+    let submission_result = tx_sender.submit_tx(tx).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+}
+
+#[tokio::test]
+async fn validating_tx_with_duplicate_factory_deps() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+    let (tx_sender, _) = create_test_tx_sender(
+        pool.clone(),
+        L2ChainId::default(),
+        MockTransactionExecutor::default().into(),
+    )
+    .await;
+
+    tx.execute.factory_deps = Some(vec![vec![1; 32], vec![2; 32]]);
+    tx_sender.validate_tx(&tx).await.unwrap();
+
+    tx.execute.factory_deps = Some(vec![vec![1; 32], vec![2; 32], vec![1; 32]]);
+    let err = tx_sender.validate_tx(&tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::DuplicateFactoryDep(2));
+}
+
+#[tokio::test]
+async fn resubmitting_duplicate_tx() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+    let tx_hash = tx.hash();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let submission_result = tx_sender.submit_tx(tx.clone()).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+    let err = tx_sender.submit_tx(tx.clone()).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::AlreadyInMempool(hash) if hash == tx_hash);
+
+    // Once the transaction is rejected, it's no longer in the mempool, so resubmitting it is an error.
+    storage
+        .transactions_dal()
+        .mark_tx_as_rejected(tx_hash, "rejected")
+        .await;
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::IncorrectTx(TxDuplication(hash)) if hash == tx_hash
+    );
+}
+
+#[tokio::test]
+async fn submitting_tx_with_nonce_gap() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_nonce_ahead = 2;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let submission_result = tx_sender.submit_tx(tx.clone()).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+
+    let mut gapped_tx = tx.clone();
+    gapped_tx.common_data.nonce = Nonce(5);
+    gapped_tx.set_input(H256::random().0.to_vec(), H256::random());
+    let err = tx_sender.submit_tx(gapped_tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NonceGap(0, 2, 5, 1));
+
+    // If the window is filled, there's no gap to fill.
+    for nonce in 1..=2 {
+        let mut tx = tx.clone();
+        tx.common_data.nonce = Nonce(nonce);
+        tx.set_input(H256::random().0.to_vec(), H256::random());
+        let submission_result = tx_sender.submit_tx(tx).await.unwrap();
+        assert_matches!(submission_result, L2TxSubmissionResult::Added);
+    }
+    let mut tx = tx;
+    tx.common_data.nonce = Nonce(5);
+    tx.set_input(H256::random().0.to_vec(), H256::random());
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NonceIsTooHigh(0, 2, 5, _));
+}
+
+#[tokio::test]
+async fn submitting_tx_within_nonce_grace_window() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_nonce_ahead = 2;
+    tx_sender_config.max_nonce_ahead_grace = 1;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let with_nonce = |nonce| {
+        let mut tx = tx.clone();
+        tx.common_data.nonce = Nonce(nonce);
+        tx.set_input(H256::random().0.to_vec(), H256::random());
+        tx
+    };
+
+    // The grace window only applies if the preceding nonces are pending.
+    let err = tx_sender.submit_tx(with_nonce(3)).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NonceGap(0, 2, 3, 0));
+
+    for nonce in 0..=2 {
+        let submission_result = tx_sender.submit_tx(with_nonce(nonce)).await.unwrap();
+        assert_matches!(submission_result, L2TxSubmissionResult::Added);
+    }
+    let submission_result = tx_sender.submit_tx(with_nonce(3)).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+
+    // Nonces beyond the grace window are still rejected.
+    let err = tx_sender.submit_tx(with_nonce(4)).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NonceIsTooHigh(0, 2, 4, _));
+}
+
+#[test_casing(2, [ExpectedNoncePolicy::Sealed, ExpectedNoncePolicy::Pending])]
+#[tokio::test]
+async fn submitting_tx_with_expected_nonce_policy(policy: ExpectedNoncePolicy) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_nonce_ahead = 0;
+    tx_sender_config.expected_nonce_policy = policy;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let with_nonce = |nonce| {
+        let mut tx = tx.clone();
+        tx.common_data.nonce = Nonce(nonce);
+        tx.set_input(H256::random().0.to_vec(), H256::random());
+        tx
+    };
+    for nonce in 0..=1 {
+        let submission_result = tx_sender.submit_tx(with_nonce(nonce)).await;
+        match (policy, nonce) {
+            (ExpectedNoncePolicy::Sealed, 1) => {
+                // The sealed nonce is still 0, and the window doesn't allow nonces ahead of it.
+                assert_matches!(
+                    submission_result.unwrap_err(),
+                    SubmitTxError::NonceIsTooHigh(0, 0, 1, _)
+                );
+            }
+            _ => {
+                assert_matches!(submission_result.unwrap(), L2TxSubmissionResult::Added);
+            }
+        }
+    }
+
+    if policy == ExpectedNoncePolicy::Pending {
+        // The window starts after pending txs, so resubmitting a pending nonce is rejected.
+        let err = tx_sender.submit_tx(with_nonce(0)).await.unwrap_err();
+        assert_matches!(err, SubmitTxError::NonceIsTooLow(2, 2, 0, _));
+    }
+}
+
+#[test_casing(2, [ExcessiveGasPerPubdataPolicy::Clamp, ExcessiveGasPerPubdataPolicy::Reject])]
+#[tokio::test]
+async fn submitting_tx_with_excessive_gas_per_pubdata_limit(policy: ExcessiveGasPerPubdataPolicy) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+    let max_gas_per_pubdata = get_max_gas_per_pubdata_byte(VmVersion::latest());
+    tx.common_data.fee.gas_per_pubdata_limit = U256::from(max_gas_per_pubdata) + 1;
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.excessive_gas_per_pubdata_policy = policy;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let submission_result = tx_sender.submit_tx(tx).await;
+    match policy {
+        ExcessiveGasPerPubdataPolicy::Clamp => {
+            assert_matches!(submission_result.unwrap(), L2TxSubmissionResult::Added);
+        }
+        ExcessiveGasPerPubdataPolicy::Reject => {
+            assert_matches!(
+                submission_result.unwrap_err(),
+                SubmitTxError::GasPerPubdataLimitTooHigh(limit, max)
+                    if limit == U256::from(max_gas_per_pubdata) + 1 && max == max_gas_per_pubdata
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn submitting_tx_with_overflowing_fee_and_value() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+    tx.execute.value = U256::MAX;
+
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::NotEnoughBalanceForFeeValue(_, fee, value)
+            if !fee.is_zero() && value == U256::MAX
+    );
+}
+
+#[tokio::test]
+async fn validating_tx_at_min_max_fee_per_gas() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+    let min_max_fee_per_gas = tx_sender.min_max_fee_per_gas().await;
+    assert!(min_max_fee_per_gas > 0);
+
+    tx.common_data.fee.max_fee_per_gas = min_max_fee_per_gas.into();
+    tx.common_data.fee.max_priority_fee_per_gas = 0.into();
+    tx_sender.validate_tx(&tx).await.unwrap();
+
+    tx.common_data.fee.max_fee_per_gas = (min_max_fee_per_gas - 1).into();
+    let err = tx_sender.validate_tx(&tx).await.unwrap_err();
+    // The required fee must be the enforced threshold, so that the transaction can be resubmitted with it.
+    assert_matches!(
+        err,
+        SubmitTxError::MaxFeePerGasTooLow { provided, required }
+            if provided == (min_max_fee_per_gas - 1).into() && required == min_max_fee_per_gas
+    );
+    let message = err.to_string();
+    assert!(
+        message.contains(&format!("required at least {min_max_fee_per_gas}")),
+        "{message}"
+    );
+
+    tx.common_data.fee.max_fee_per_gas = min_max_fee_per_gas.into();
+    tx_sender.validate_tx(&tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn validating_tx_with_priority_fee_above_max_fee() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+    let max_fee_per_gas = U256::from(tx_sender.min_max_fee_per_gas().await);
+    tx.common_data.fee.max_fee_per_gas = max_fee_per_gas;
+    tx.common_data.fee.max_priority_fee_per_gas = max_fee_per_gas;
+    tx_sender.validate_tx(&tx).await.unwrap();
+
+    // The priority fee is covered by the transaction signature, so it must not be capped; the transaction is rejected.
+    tx.common_data.fee.max_priority_fee_per_gas = max_fee_per_gas + 1;
+    let err = tx_sender.validate_tx(&tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::MaxPriorityFeeGreaterThanMaxFee);
+}
+
+#[test_casing(2, [ProtocolVersionId::Version20, ProtocolVersionId::latest()])]
+#[tokio::test]
+async fn validating_intrinsic_gas_for_pending_protocol_version(
+    protocol_version: ProtocolVersionId,
+) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    if protocol_version != ProtocolVersionId::latest() {
+        storage
+            .protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion {
+                id: protocol_version,
+                ..ProtocolVersion::default()
+            })
+            .await;
+    }
+    let miniblock = MiniblockHeader {
+        protocol_version: Some(protocol_version),
+        ..create_miniblock(1)
+    };
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock)
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+
+    // The floor is taken from the constants for the pending protocol version, whether or not they differ
+    // from the latest ones.
+    let min_gas_limit = get_intrinsic_constants(protocol_version.into()).l2_tx_intrinsic_gas;
+    tx.common_data.fee.gas_limit = min_gas_limit.into();
+    tx_sender.validate_tx(&tx).await.unwrap();
+
+    tx.common_data.fee.gas_limit = (min_gas_limit - 1).into();
+    let err = tx_sender.validate_tx(&tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::IntrinsicGas);
+}
+
+#[test]
+fn classifying_user_errors() {
+    let user_errors = [
+        SubmitTxError::NonceIsTooHigh(0, 1, 2, MiniblockNumber(0)),
+        SubmitTxError::NonceIsTooLow(1, 2, 0, MiniblockNumber(0)),
+        SubmitTxError::NonceGap(0, 2, 2, 1),
+        SubmitTxError::InsertionInProgress,
+        SubmitTxError::ConcurrentNonceConflict(H256::zero()),
+        SubmitTxError::AlreadyInMempool(H256::zero()),
+        SubmitTxError::TransactionExpired(Utc::now()),
+        SubmitTxError::InvalidSignature,
+        SubmitTxError::IncorrectTx(TxDuplication(H256::zero())),
+        SubmitTxError::NotEnoughBalanceForFeeValue(0.into(), 1.into(), 1.into()),
+        SubmitTxError::ExecutionReverted("reverted".to_owned(), vec![]),
+        SubmitTxError::GasLimitIsTooBig,
+        SubmitTxError::Unexecutable {
+            code: UnexecutableReason::SealCriterion,
+            message: "unexecutable".to_owned(),
+        },
+        SubmitTxError::RateLimitExceeded,
+        SubmitTxError::ValidationFailed("invalid".to_owned()),
+        SubmitTxError::FailedToChargeFee("no funds".to_owned()),
+        SubmitTxError::PaymasterValidationFailed("invalid".to_owned()),
+        SubmitTxError::PrePaymasterPreparationFailed("invalid".to_owned()),
+        SubmitTxError::FromIsNotAnAccount,
+        SubmitTxError::MaxFeePerGasTooLow {
+            provided: 1.into(),
+            required: 2,
+        },
+        SubmitTxError::MaxPriorityFeeGreaterThanMaxFee,
+        SubmitTxError::UnrealisticPubdataPriceLimit,
+        SubmitTxError::TooManyFactoryDependencies(100, 32),
+        SubmitTxError::DuplicateFactoryDep(1),
+        SubmitTxError::FeePerGasTooHigh,
+        SubmitTxError::FeePerPubdataByteTooHigh,
+        SubmitTxError::InsufficientFundsForTransfer,
+        SubmitTxError::IntrinsicGas,
+        SubmitTxError::FailedToPublishCompressedBytecodes(
+            BytecodeCompressionError::BytecodeCompressionFailed,
+        ),
+        SubmitTxError::UnsupportedTxType(TransactionType::PriorityOpTransaction),
+        SubmitTxError::InsufficientMintForL1Tx(0.into(), 1.into()),
+        SubmitTxError::GasEstimationFailed("failed".to_owned()),
+        SubmitTxError::PubdataLimitExceeded(200_000, 100_000),
+        SubmitTxError::TxPubdataLimitExceeded(50_000, 10_000),
+        SubmitTxError::PrecedingTxFailed(0, "reverted".to_owned()),
+        SubmitTxError::ExceedsBlockGasLimit(u32::MAX, BLOCK_GAS_LIMIT),
+        SubmitTxError::ExecutionTimedOut,
+        SubmitTxError::CallTargetNotAllowed(Address::zero()),
+        SubmitTxError::ResponseTooLarge(1_024, 512),
+        SubmitTxError::ForbiddenTarget(Address::zero()),
+        SubmitTxError::GasPerPubdataLimitTooHigh(100_000.into(), 50_000),
+        SubmitTxError::InitiatorIsFeeAccount(Address::zero()),
+        SubmitTxError::DeploymentAddressOccupied(Address::zero()),
+        SubmitTxError::ProxyError(EnrichedClientError::new(
+            ClientError::Call(ErrorObjectOwned::owned(3, "rejected", None::<()>)),
+            "send_raw_transaction",
+        )),
+    ];
+    for err in &user_errors {
+        assert!(err.is_user_error(), "{err:?}");
+    }
+
+    let node_errors = [
+        SubmitTxError::ServerShuttingDown,
+        SubmitTxError::BootloaderFailure("failure".to_owned()),
+        SubmitTxError::UnexpectedVMBehavior("panic".to_owned()),
+        SubmitTxError::StaleNodeState {
+            pending_block_number: MiniblockNumber(1),
+            latest_block_number: MiniblockNumber(10),
+        },
+        SubmitTxError::ProxyError(EnrichedClientError::custom(
+            "connection refused",
+            "send_raw_transaction",
+        )),
+        SubmitTxError::Internal(anyhow::anyhow!("DB error")),
+    ];
+    for err in &node_errors {
+        assert!(!err.is_user_error(), "{err:?}");
+    }
+}
+
+#[test]
+fn narrowing_gas_values() {
+    assert_eq!(
+        TxSender::narrow_gas_value(U256::from(u32::MAX)).unwrap(),
+        u32::MAX
+    );
+    assert_eq!(
+        TxSender::narrow_gas_value(u64::from(u32::MAX)).unwrap(),
+        u32::MAX
+    );
+    let err = TxSender::narrow_gas_value(U256::from(u32::MAX) + 1).unwrap_err();
+    assert_matches!(err, SubmitTxError::GasLimitIsTooBig);
+    let err = TxSender::narrow_gas_value(u64::from(u32::MAX) + 1).unwrap_err();
+    assert_matches!(err, SubmitTxError::GasLimitIsTooBig);
+    let err = TxSender::narrow_gas_value(U256::MAX).unwrap_err();
+    assert_matches!(err, SubmitTxError::GasLimitIsTooBig);
+}
+
+#[tokio::test]
+async fn validating_tx_with_gas_limit_at_u32_boundary() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx = create_funded_tx(&mut storage).await;
+
+    let tx_executor = MockTransactionExecutor::default().into();
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_allowed_l2_tx_gas_limit = u32::MAX;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor).await;
+
+    // The funded balance covers the maximum fee at the `u32::MAX` gas limit.
+    tx.common_data.fee.gas_limit = u32::MAX.into();
+    tx_sender.validate_tx(&tx).await.unwrap();
+
+    for gas_limit in [U256::from(u32::MAX) + 1, U256::MAX] {
+        tx.common_data.fee.gas_limit = gas_limit;
+        let err = tx_sender.validate_tx(&tx).await.unwrap_err();
+        assert_matches!(err, SubmitTxError::GasLimitIsTooBig);
+    }
+
+    tx.common_data.fee.gas_limit = u32::MAX.into();
+    tx.common_data.fee.gas_per_pubdata_limit = U256::from(u32::MAX) + 1;
+    let err = tx_sender.validate_tx(&tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::GasLimitIsTooBig);
+}
+
+#[tokio::test]
+async fn auditing_submission_at_historical_block() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let fee_per_gas = StateKeeperConfig::for_tests().minimal_l2_gas_price;
+    let mut tx = create_l2_transaction(fee_per_gas, 50);
+    tx.common_data.fee.gas_limit =
+        (get_intrinsic_constants(VmVersion::latest()).l2_tx_intrinsic_gas * 2).into();
+    // Fund the initiator only in the next miniblock, so that the transaction is invalid at genesis.
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+    let balance_key = storage_key_for_eth_balance(&tx.initiator_account());
+    let balance_log = StorageLog::new_write_log(balance_key, u256_to_h256(U256::one() << 64));
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(MiniblockNumber(1), &[(H256::zero(), vec![balance_log])])
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let start_info = BlockStartInfo::new(&mut storage).await.unwrap();
+    let earliest_block = api::BlockId::Number(api::BlockNumber::Earliest);
+    let historical_block_args = BlockArgs::new(&mut storage, earliest_block, start_info)
+        .await
+        .unwrap();
+    let audit = tx_sender
+        .audit_submission(tx.clone(), historical_block_args)
+        .await;
+    assert!(!audit.is_accepted());
+    assert_eq!(audit.block_number, MiniblockNumber(0));
+    assert!(audit.execution_metrics.is_none());
+    assert_matches!(
+        audit.rejection,
+        Some(SubmitTxError::NotEnoughBalanceForFeeValue(balance, ..)) if balance.is_zero()
+    );
+
+    let pending_block_args = tx_sender.pending_block_args().await.unwrap();
+    let audit = tx_sender
+        .audit_submission(tx.clone(), pending_block_args)
+        .await;
+    assert!(audit.is_accepted(), "{audit:?}");
+    assert_eq!(audit.block_number, MiniblockNumber(2));
+    assert!(audit.execution_metrics.is_some());
+
+    // Auditing must not submit the transaction.
+    let stored_tx = storage
+        .transactions_web3_dal()
+        .get_transaction_details(tx.hash())
+        .await
+        .unwrap();
+    assert!(stored_tx.is_none());
+}
+
+#[tokio::test]
+async fn caching_fee_account_balance() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+
+    let fee_account = tx_sender.0.sender_config.fee_account_addr;
+    let balance_key = storage_key_for_eth_balance(&fee_account);
+    let balance_log = StorageLog::new_write_log(balance_key, u256_to_h256(123.into()));
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::zero(), vec![balance_log])])
+        .await
+        .unwrap();
+    let balance = tx_sender.get_balance(&fee_account).await.unwrap();
+    assert_eq!(balance, 123.into());
+
+    // Update the balance in a new miniblock; the cached balance should be returned until the miniblock is observed.
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+    let balance_log = StorageLog::new_write_log(balance_key, u256_to_h256(321.into()));
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(MiniblockNumber(1), &[(H256::zero(), vec![balance_log])])
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        let balance = tx_sender.get_balance(&fee_account).await.unwrap();
+        assert_eq!(balance, 123.into());
+    }
+
+    tx_sender.observe_miniblock(MiniblockNumber(1));
+    let balance = tx_sender.get_balance(&fee_account).await.unwrap();
+    assert_eq!(balance, 321.into());
+}
+
+#[tokio::test]
+async fn submitting_tx_from_fee_account() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+    let fee_account = tx.initiator_account();
+
+    let tx_executor = || -> TransactionExecutor {
+        let mut tx_executor = MockTransactionExecutor::default();
+        tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+        tx_executor.into()
+    };
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.fee_account_addr = fee_account;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config.clone(), tx_executor())
+            .await;
+
+    // The fee account must be able to cover the maximum fee, even though it receives the fee afterwards.
+    let max_fee = tx.common_data.fee.gas_limit * tx.common_data.fee.max_fee_per_gas;
+    let balance_key = storage_key_for_eth_balance(&fee_account);
+    for (number, balance) in [(1, max_fee - 1), (2, max_fee)] {
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock(number))
+            .await
+            .unwrap();
+        let balance_log = StorageLog::new_write_log(balance_key, u256_to_h256(balance));
+        storage
+            .storage_logs_dal()
+            .insert_storage_logs(
+                MiniblockNumber(number),
+                &[(H256::zero(), vec![balance_log])],
+            )
+            .await
+            .unwrap();
+
+        let submission_result = tx_sender.submit_tx(tx.clone()).await;
+        if balance < max_fee {
+            assert_matches!(
+                submission_result.unwrap_err(),
+                SubmitTxError::NotEnoughBalanceForFeeValue(actual_balance, fee, value)
+                    if actual_balance == balance && fee == max_fee && value.is_zero()
+            );
+        } else {
+            assert_matches!(submission_result.unwrap(), L2TxSubmissionResult::Added);
+        }
+    }
+
+    tx_sender_config.fee_account_tx_policy = FeeAccountTxPolicy::Reject;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor()).await;
+    let mut next_tx = tx;
+    next_tx.common_data.nonce = Nonce(1);
+    next_tx.set_input(H256::random().0.to_vec(), H256::random());
+    let err = tx_sender.submit_tx(next_tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::InitiatorIsFeeAccount(addr) if addr == fee_account);
+}
+
+#[tokio::test]
+async fn estimating_gas_for_transfer_from_fee_account_with_operator_balance_override() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let mut tx = create_l2_transaction(10, 100);
+    tx.execute.value = 1_000.into();
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.fee_account_addr = tx.initiator_account();
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    // The fee account has no funds to transfer.
+    let err = tx_sender
+        .estimate_gas(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::InsufficientFundsForTransfer);
+
+    // The override applies to the fee account balance, so it should be taken into account for the transfer.
+    let options = GasEstimationOptions {
+        operator_balance_override: Some(1_000.into()),
+        ..GasEstimationOptions::default()
+    };
+    tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn resolving_pending_block_args() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let (tx_sender, _) = create_test_tx_sender(
+        pool.clone(),
+        L2ChainId::default(),
+        MockTransactionExecutor::default().into(),
+    )
+    .await;
+
+    let block_args = tx_sender.pending_block_args().await.unwrap();
+    let expected_block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    assert_eq!(block_args.block_id(), expected_block_args.block_id());
+    assert_eq!(
+        block_args.resolved_block_number(),
+        expected_block_args.resolved_block_number()
+    );
+    assert_eq!(block_args.resolved_block_number(), MiniblockNumber(1));
+    assert_eq!(block_args.l1_batch_timestamp_s(), None);
+
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+    let block_args = tx_sender.pending_block_args().await.unwrap();
+    assert_eq!(block_args.resolved_block_number(), MiniblockNumber(2));
+}
+
+/// Sink reporting a configurable latest sealed miniblock, e.g. to simulate a lagging replica DB.
+#[derive(Debug, Default)]
+struct HeadReportingTxSink(Mutex<Option<MiniblockNumber>>);
+
+impl HeadReportingTxSink {
+    fn set_latest_sealed_miniblock(&self, number: MiniblockNumber) {
+        *self.0.lock().unwrap() = Some(number);
+    }
+}
+
+#[async_trait::async_trait]
+impl TxSink for HeadReportingTxSink {
+    async fn submit_tx(
+        &self,
+        _tx: L2Tx,
+        _execution_metrics: TransactionExecutionMetrics,
+        _valid_until: Option<DateTime<Utc>>,
+    ) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        Ok(L2TxSubmissionResult::Added)
+    }
+
+    async fn lookup_latest_sealed_miniblock(
+        &self,
+    ) -> Result<Option<MiniblockNumber>, SubmitTxError> {
+        Ok(*self.0.lock().unwrap())
+    }
+}
+
+#[tokio::test]
+async fn rejecting_stale_pending_block() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_pending_block_lag = Some(3);
+    let (mut tx_sender, _) = create_test_tx_sender_with_config(
+        pool.clone(),
+        tx_sender_config,
+        MockTransactionExecutor::default().into(),
+    )
+    .await;
+    let tx_sink = Arc::new(HeadReportingTxSink::default());
+    Arc::get_mut(&mut tx_sender.0).unwrap().tx_sink = tx_sink.clone();
+
+    // If the sink doesn't know the latest miniblock, the pending block is never considered stale.
+    let block_args = tx_sender.pending_block_args().await.unwrap();
+    assert_eq!(block_args.resolved_block_number(), MiniblockNumber(1));
+
+    // Simulate the destination having sealed more miniblocks than present in the (lagging) replica DB.
+    tx_sink.set_latest_sealed_miniblock(MiniblockNumber(3));
+    let block_args = tx_sender.pending_block_args().await.unwrap();
+    assert_eq!(block_args.resolved_block_number(), MiniblockNumber(1));
+
+    tx_sink.set_latest_sealed_miniblock(MiniblockNumber(4));
+    let err = tx_sender.pending_block_args().await.unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::StaleNodeState {
+            pending_block_number: MiniblockNumber(1),
+            latest_block_number: MiniblockNumber(4),
+        }
+    );
+    assert!(err.to_string().contains("node state is stale"), "{err}");
+
+    let tx = create_l2_transaction(10, 100);
+    let err = tx_sender
+        .get_txs_fee_in_wei(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::StaleNodeState { .. });
+
+    let pending_block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    let err = tx_sender
+        .eth_call(pending_block_args, tx)
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::StaleNodeState { .. });
+}
+
+#[tokio::test]
+async fn master_pool_sink_reports_latest_sealed_miniblock() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+
+    let sink = master_pool_sink::MasterPoolSink::new(pool);
+    let latest_miniblock = sink.lookup_latest_sealed_miniblock().await.unwrap();
+    assert_eq!(latest_miniblock, Some(MiniblockNumber(1)));
+
+    // The latest miniblock is cached, so a newly sealed miniblock isn't observed right away.
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(2))
+        .await
+        .unwrap();
+    let latest_miniblock = sink.lookup_latest_sealed_miniblock().await.unwrap();
+    assert_eq!(latest_miniblock, Some(MiniblockNumber(1)));
+}
+
+#[tokio::test]
+async fn estimating_gas_on_genesis_only_node() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    let genesis_params = GenesisParams::mock();
+    insert_genesis_batch(&mut storage, &genesis_params)
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, block_args| {
+        assert_eq!(block_args.resolved_block_number(), MiniblockNumber(1));
+        ExecutionResult::Success { output: vec![] }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(estimate.block_number, MiniblockNumber(1));
+    assert_eq!(estimate.protocol_version, genesis_params.protocol_version());
+}
+
+#[tokio::test]
+async fn limiting_concurrent_estimations() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let funded_tx = create_funded_tx(&mut storage).await;
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_concurrent_estimations = Some(1);
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    // Occupy the only estimation slot, emulating a long-running estimation.
+    let limiter = tx_sender.0.estimation_limiter.as_ref().unwrap();
+    let permit = limiter.acquire().await.unwrap();
+    let tx = create_l2_transaction(10, 100);
+    let options = GasEstimationOptions::default();
+    let estimation = tx_sender.estimate_gas(tx.into(), 1.0, 1_000, &options);
+    tokio::pin!(estimation);
+    let estimation_result = tokio::time::timeout(Duration::from_millis(50), &mut estimation).await;
+    assert!(estimation_result.is_err(), "{estimation_result:?}");
+
+    // Other traffic is not throttled by the estimation limiter.
+    let submission_result = tx_sender.submit_tx(funded_tx).await.unwrap();
+    assert_matches!(submission_result, L2TxSubmissionResult::Added);
+
+    drop(permit);
+    let estimate = estimation.await.unwrap();
+    assert_eq!(estimate.block_number, MiniblockNumber(1));
+}
+
+#[tokio::test]
+async fn estimating_gas_after_snapshot_recovery_without_miniblocks() {
+    const SNAPSHOT_MINIBLOCK_NUMBER: MiniblockNumber = MiniblockNumber(42);
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    let snapshot_recovery = prepare_recovery_snapshot(
+        &mut storage,
+        L1BatchNumber(23),
+        SNAPSHOT_MINIBLOCK_NUMBER,
+        &[],
+    )
+    .await;
+    assert_eq!(
+        storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .unwrap(),
+        None
+    );
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, block_args| {
+        assert_eq!(
+            block_args.resolved_block_number(),
+            SNAPSHOT_MINIBLOCK_NUMBER + 1
+        );
+        ExecutionResult::Success { output: vec![] }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(estimate.block_number, SNAPSHOT_MINIBLOCK_NUMBER + 1);
+    assert_eq!(
+        estimate.protocol_version,
+        snapshot_recovery.protocol_version
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_on_uninitialized_node() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| panic!("transaction should not be executed"));
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let err = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_err();
+    let SubmitTxError::Internal(err) = err else {
+        panic!("Unexpected error: {err:?}");
+    };
+    let err = format!("{err:#}");
+    assert!(
+        err.contains("neither miniblocks, nor snapshot recovery info"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn eth_call_with_target_allowlist() {
+    let allowed_target = Address::repeat_byte(1);
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_call_responses(move |tx, _| {
+        assert_eq!(tx.recipient_account(), allowed_target);
+        ExecutionResult::Success {
+            output: b"output".to_vec(),
+        }
+    });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.eth_call_target_allowlist = Some(HashSet::from([allowed_target]));
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let mut call = create_l2_transaction(10, 100);
+    call.common_data.input = None;
+    call.execute.contract_address = allowed_target;
+    let output = tx_sender.eth_call(block_args, call.clone()).await.unwrap();
+    assert_eq!(output, b"output");
+
+    let disallowed_target = Address::repeat_byte(2);
+    call.execute.contract_address = disallowed_target;
+    let err = tx_sender.eth_call(block_args, call).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::CallTargetNotAllowed(addr) if addr == disallowed_target);
+}
+
+#[tokio::test]
+async fn eth_call_with_response_size_limit() {
+    const MAX_RESPONSE_BYTES: usize = 1_024;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_call_responses(|tx, _| {
+        let output_len = tx.execute.calldata()[0] as usize * 256;
+        let output = vec![1; output_len];
+        if tx.execute.calldata()[1] == 0 {
+            ExecutionResult::Success { output }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::Unknown {
+                    function_selector: vec![],
+                    data: output,
+                },
+            }
+        }
+    });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_eth_call_response_bytes = Some(MAX_RESPONSE_BYTES);
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let mut call = create_l2_transaction(10, 100);
+    call.common_data.input = None;
+    // The first calldata byte encodes the response size in 256-byte units, and the second one whether the call reverts.
+    call.execute.calldata = vec![4, 0];
+    let output = tx_sender.eth_call(block_args, call.clone()).await.unwrap();
+    assert_eq!(output.len(), MAX_RESPONSE_BYTES);
+
+    call.execute.calldata = vec![5, 0];
+    let err = tx_sender
+        .eth_call(block_args, call.clone())
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::ResponseTooLarge(1_280, MAX_RESPONSE_BYTES)
+    );
+
+    call.execute.calldata = vec![5, 1];
+    let err = tx_sender.eth_call(block_args, call).await.unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::ResponseTooLarge(1_280, MAX_RESPONSE_BYTES)
+    );
+}
+
+#[tokio::test]
+async fn eth_call_returning_gas_used() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+
+    let tx_metrics = TransactionExecutionMetrics {
+        gas_used: 123_456,
+        ..TransactionExecutionMetrics::default()
+    };
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_call_responses(|_, _| ExecutionResult::Success {
+        output: b"output".to_vec(),
+    });
+    tx_executor.set_tx_metrics_responses(move |_| tx_metrics);
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let mut call = create_l2_transaction(10, 100);
+    call.common_data.input = None;
+    let (output, gas_used) = tx_sender
+        .eth_call_with_gas(block_args, call.clone())
+        .await
+        .unwrap();
+    assert_eq!(output, b"output");
+    assert_eq!(gas_used, 123_456);
+
+    let output = tx_sender.eth_call(block_args, call).await.unwrap();
+    assert_eq!(output, b"output");
+}
+
+#[tokio::test]
+async fn vm_execution_timing_out() {
+    const EXECUTION_DELAY: Duration = Duration::from_millis(50);
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+
+    let slow_response = |_: &Transaction, _: &BlockArgs| {
+        std::thread::sleep(EXECUTION_DELAY);
+        ExecutionResult::Success { output: vec![] }
+    };
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_call_responses(slow_response);
+    tx_executor.set_tx_responses(slow_response);
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.vm_execution_timeout = Some(EXECUTION_DELAY / 5);
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let err = tx_sender
+        .estimate_gas(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::ExecutionTimedOut);
+
+    let mut call = create_l2_transaction(10, 100);
+    call.common_data.input = None;
+    let err = tx_sender.eth_call(block_args, call).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::ExecutionTimedOut);
+
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::ExecutionTimedOut);
+}
+
+#[tokio::test]
+async fn capturing_execution() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_call_responses(|_, _| ExecutionResult::Success {
+        output: b"output".to_vec(),
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let mut call = create_l2_transaction(10, 100);
+    call.common_data.input = None;
+    let capture = tx_sender
+        .capture_execution(call.clone(), block_args)
+        .await
+        .unwrap();
+    assert_eq!(capture.tx, call);
+    assert_eq!(
+        capture.resolved_block_number,
+        block_args.resolved_block_number()
+    );
+    assert_eq!(capture.block_id, block_args.block_id());
+    assert_eq!(capture.output.as_deref(), Ok(b"output".as_slice()));
+
+    let serialized = serde_json::to_value(&capture).unwrap();
+    let restored: ExecutionCapture = serde_json::from_value(serialized).unwrap();
+    assert_eq!(restored.tx.initiator_account(), call.initiator_account());
+    assert_eq!(restored.tx.execute, call.execute);
+    assert_eq!(
+        restored.resolved_block_number,
+        capture.resolved_block_number
+    );
+}
+
+fn storage_log_query(
+    key: StorageKey,
+    log_type: StorageLogQueryType,
+    written_value: U256,
+    rollback: bool,
+) -> StorageLogQuery {
+    StorageLogQuery {
+        log_query: LogQuery {
+            timestamp: Timestamp(0),
+            tx_number_in_block: 0,
+            aux_byte: 0,
+            shard_id: 0,
+            address: *key.address(),
+            key: h256_to_u256(*key.key()),
+            read_value: U256::zero(),
+            written_value,
+            rw_flag: !matches!(log_type, StorageLogQueryType::Read),
+            rollback,
+            is_service: false,
+        },
+        log_type,
+    }
+}
+
+#[tokio::test]
+async fn capturing_attempted_writes_of_reverted_call() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+
+    let contract = Address::repeat_byte(1);
+    let written_key = StorageKey::new(AccountTreeId::new(contract), H256::repeat_byte(2));
+    let read_key = StorageKey::new(AccountTreeId::new(contract), H256::repeat_byte(3));
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_call_responses(|_, _| ExecutionResult::Revert {
+        output: VmRevertReason::General {
+            msg: "written, then reverted".to_owned(),
+            data: vec![],
+        },
+    });
+    // The contract reads a slot and writes another one; the write is then rolled back by the revert.
+    tx_executor.set_storage_logs_responses(move |_| {
+        vec![
+            storage_log_query(read_key, StorageLogQueryType::Read, U256::zero(), false),
+            storage_log_query(
+                written_key,
+                StorageLogQueryType::InitialWrite,
+                5.into(),
+                false,
+            ),
+            storage_log_query(
+                written_key,
+                StorageLogQueryType::InitialWrite,
+                5.into(),
+                true,
+            ),
+        ]
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let mut call = create_l2_transaction(10, 100);
+    call.common_data.input = None;
+    let capture = tx_sender.capture_execution(call, block_args).await.unwrap();
+    let err = capture.output.unwrap_err();
+    assert!(err.contains("written, then reverted"), "{err}");
+    assert_eq!(
+        capture.attempted_writes,
+        [StorageLog::new_write_log(
+            written_key,
+            u256_to_h256(5.into())
+        )]
+    );
+    let slot_keys: Vec<_> = capture.storage_slots.iter().map(|log| log.key).collect();
+    assert_eq!(slot_keys, [read_key, written_key]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn eth_call_records_storage_cache_misses() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    drop(storage);
+
+    let (mut tx_sender, _) = create_test_tx_sender(
+        pool.clone(),
+        L2ChainId::default(),
+        MockTransactionExecutor::default().into(),
+    )
+    .await;
+    let caches = tx_sender.storage_caches();
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_call_responses(move |_, _| {
+        // Emulate VM execution reading a factory dependency, which is not cached yet.
+        tokio::task::block_in_place(|| {
+            let handle = Handle::current();
+            let connection = handle.block_on(pool.connection()).unwrap();
+            let mut storage = PostgresStorage::new(handle, connection, MiniblockNumber(0), false)
+                .with_caches(caches.clone());
+            storage.load_factory_dep(H256::repeat_byte(0xff));
+        });
+        ExecutionResult::Success { output: vec![] }
+    });
+    Arc::get_mut(&mut tx_sender.0).unwrap().executor = tx_executor.into();
+
+    let misses_counter = &SANDBOX_METRICS.storage_cache_misses[&SandboxCallType::EthCall];
+    let misses_before = misses_counter.get();
+    let mut call = create_l2_transaction(10, 100);
+    call.common_data.input = None;
+    tx_sender.eth_call(block_args, call).await.unwrap();
+    assert!(misses_counter.get() > misses_before);
+}
+
+#[tokio::test]
+async fn estimating_gas_with_min_gas_per_pubdata() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let tx = create_l2_transaction(10, 100);
+    let estimate_gas_per_pubdata = |min_gas_per_pubdata: Option<u64>| {
+        let pool = pool.clone();
+        let tx = tx.clone();
+        async move {
+            let mut tx_executor = MockTransactionExecutor::default();
+            tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+            let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+            tx_sender_config.min_gas_per_pubdata = min_gas_per_pubdata;
+            let (tx_sender, _) =
+                create_test_tx_sender_with_config(pool, tx_sender_config, tx_executor.into()).await;
+            let fee = tx_sender
+                .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+                .await
+                .unwrap();
+            fee.gas_per_pubdata_limit.as_u64()
+        }
+    };
+    let derived_gas_per_pubdata = estimate_gas_per_pubdata(None).await;
+
+    // A floor below the derived value has no effect.
+    let gas_per_pubdata = estimate_gas_per_pubdata(Some(derived_gas_per_pubdata / 2)).await;
+    assert_eq!(gas_per_pubdata, derived_gas_per_pubdata);
+
+    let min_gas_per_pubdata = derived_gas_per_pubdata * 2 + 1;
+    let gas_per_pubdata = estimate_gas_per_pubdata(Some(min_gas_per_pubdata)).await;
+    assert_eq!(gas_per_pubdata, min_gas_per_pubdata);
+}
+
+async fn estimate_gas_with_response(response: ExecutionResult) -> SubmitTxError {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(move |_, _| response.clone());
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_err()
+}
+
+#[tokio::test]
+async fn estimating_gas_for_tx_that_cannot_converge() {
+    let err = estimate_gas_with_response(ExecutionResult::Halt {
+        reason: Halt::NotEnoughGasProvided,
+    })
+    .await;
+    assert_matches!(err, SubmitTxError::GasEstimationFailed(_));
+}
+
+#[tokio::test]
+async fn estimating_gas_for_reverting_tx() {
+    let err = estimate_gas_with_response(ExecutionResult::Revert {
+        output: VmRevertReason::General {
+            msg: "oops".to_owned(),
+            data: vec![],
+        },
+    })
+    .await;
+    assert_matches!(err, SubmitTxError::ExecutionReverted(msg, _) if msg.contains("oops"));
+}
+
+#[tokio::test]
+async fn estimate_gas_failures_are_counted_by_cause() {
+    let failures = &SANDBOX_METRICS.estimate_gas_failures;
+    let reverts_before = failures[&"execution-reverted"].get();
+    let err = estimate_gas_with_response(ExecutionResult::Revert {
+        output: VmRevertReason::General {
+            msg: "oops".to_owned(),
+            data: vec![],
+        },
+    })
+    .await;
+    assert_matches!(err, SubmitTxError::ExecutionReverted(..));
+    // Metrics are global, so other tests may increment them concurrently.
+    assert!(failures[&"execution-reverted"].get() > reverts_before);
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+    let mut tx = create_l2_transaction(10, 100);
+    tx.execute.value = 1.into(); // The initiator has no funds
+
+    let insufficient_funds_before = failures[&"insufficient-funds-for-transfer"].get();
+    let err = tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::InsufficientFundsForTransfer);
+    assert!(failures[&"insufficient-funds-for-transfer"].get() > insufficient_funds_before);
+}
+
+#[tokio::test]
+async fn estimating_gas_with_failing_code_hash_read() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+    // Break storage reads; the account code hash is the first storage value read during estimation.
+    sqlx::query("ALTER TABLE storage_logs RENAME TO storage_logs_unavailable")
+        .execute(storage.conn())
+        .await
+        .unwrap();
+
+    let mut tx = create_l2_transaction(10, 100);
+    tx.execute.value = 1.into(); // The initiator has no funds
+    let err = tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_err();
+    // The DB error must not be mistaken for the account having no code (and thus insufficient funds).
+    assert_matches!(err, SubmitTxError::Internal(_));
+}
+
+#[tokio::test]
+async fn estimating_gas_for_tx_exceeding_pubdata_limit_at_runtime() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let max_pubdata_per_batch = StateKeeperConfig::for_tests().max_pubdata_per_batch;
+    // Emulates a factory contract deploying a contract during execution, so that the deployed bytecode
+    // isn't declared in factory deps. The transaction runs out of gas for any gas limit.
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Revert {
+        output: VmRevertReason::General {
+            msg: "out of gas".to_owned(),
+            data: vec![],
+        },
+    });
+    tx_executor.set_tx_metrics_responses(move |_| TransactionExecutionMetrics {
+        contracts_deployed: 1,
+        pubdata_published: (max_pubdata_per_batch + 1) as u32,
+        ..TransactionExecutionMetrics::default()
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    assert!(tx.execute.factory_deps.is_none());
+    let err = tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::PubdataLimitExceeded(published, limit)
+            if published > limit && limit == max_pubdata_per_batch
+    );
+}
+
+#[tokio::test]
+async fn tx_exceeding_per_tx_pubdata_limit() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx = create_funded_tx(&mut storage).await;
+
+    let max_pubdata_per_batch = StateKeeperConfig::for_tests().max_pubdata_per_batch;
+    let max_pubdata_per_tx = max_pubdata_per_batch / 4;
+    // The published pubdata fits into a batch, but not into the per-transaction limit.
+    let pubdata_published = max_pubdata_per_tx + 1;
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    tx_executor.set_tx_metrics_responses(move |_| TransactionExecutionMetrics {
+        pubdata_published: pubdata_published as u32,
+        ..TransactionExecutionMetrics::default()
+    });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_pubdata_per_tx = max_pubdata_per_tx;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let err = tx_sender
+        .get_txs_fee_in_wei(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::TxPubdataLimitExceeded(published, limit)
+            if published == pubdata_published && limit == max_pubdata_per_tx
+    );
+
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::TxPubdataLimitExceeded(published, limit)
+            if published == pubdata_published && limit == max_pubdata_per_tx
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_for_tx_exceeding_block_gas_limit_outright() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    // The executor must not be invoked, so all execution responses panic.
+    let tx_executor = MockTransactionExecutor::default();
+    // Raise `gas_per_pubdata` so that bytecodes exceeding the block gas limit still fit into the pubdata limit.
+    let gas_per_pubdata = 50_000;
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.min_gas_per_pubdata = Some(gas_per_pubdata);
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    // Bytecode consisting of distinct words is not compressible. Its word count must be odd.
+    let word_count = (u64::from(BLOCK_GAS_LIMIT) * 3 / 2 / gas_per_pubdata / 32) | 1;
+    let bytecode: Vec<_> = (0..word_count)
+        .flat_map(|i| H256::from_low_u64_be(i).0)
+        .collect();
+    let mut tx = create_l2_transaction(10, 100);
+    tx.execute.factory_deps = Some(vec![bytecode]);
+
+    let err = tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::ExceedsBlockGasLimit(required_gas, limit)
+            if required_gas > limit && limit == BLOCK_GAS_LIMIT
+    );
+}
+
+#[tokio::test]
+async fn replica_connection_acquisition_timeout() {
+    let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let tx_executor = MockTransactionExecutor::default().into();
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.replica_connection_acquire_timeout = Some(Duration::from_millis(50));
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor).await;
+
+    let _held_connection = pool.connection().await.unwrap();
+    let err = tx_sender
+        .get_expected_nonce(Address::repeat_byte(1))
+        .await
+        .unwrap_err();
+    let err = format!("{err:#}");
+    assert!(err.contains("DB pool exhausted"), "{err}");
+}
+
+async fn estimate_gas_with_flaky_final_step(
+    final_step_failure: ExecutionResult,
+    final_step_retries: u32,
+) -> Result<Fee, SubmitTxError> {
+    const MIN_GAS_LIMIT: u64 = 100_000;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    // Binary search never tries the same gas limit twice, so the first repeated gas limit is the one
+    // checked during the final step.
+    let seen_gas_limits = Mutex::new(HashSet::new());
+    let has_failed = AtomicBool::new(false);
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(move |tx, _| {
+        let gas_limit = tx.gas_limit();
+        let is_repeated = !seen_gas_limits.lock().unwrap().insert(gas_limit);
+        if gas_limit < U256::from(MIN_GAS_LIMIT) {
+            ExecutionResult::Halt {
+                reason: Halt::NotEnoughGasProvided,
+            }
+        } else if is_repeated && !has_failed.swap(true, Ordering::SeqCst) {
+            final_step_failure.clone()
+        } else {
+            ExecutionResult::Success { output: vec![] }
+        }
+    });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.estimate_gas_final_step_retries = final_step_retries;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+}
+
+#[tokio::test]
+async fn estimating_gas_with_flaky_final_step() {
+    let out_of_gas = ExecutionResult::Halt {
+        reason: Halt::NotEnoughGasProvided,
+    };
+    let fee = estimate_gas_with_flaky_final_step(out_of_gas.clone(), 1)
+        .await
+        .unwrap();
+    assert!(fee.gas_limit > 100_000.into());
+
+    let err = estimate_gas_with_flaky_final_step(out_of_gas, 0)
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::GasEstimationFailed(_));
+}
+
+#[tokio::test]
+async fn reverted_final_step_is_not_retried() {
+    let revert = ExecutionResult::Revert {
+        output: VmRevertReason::General {
+            msg: "oops".to_owned(),
+            data: vec![],
+        },
+    };
+    let err = estimate_gas_with_flaky_final_step(revert, 1)
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::ExecutionReverted(msg, _) if msg.contains("oops"));
+}
+
+#[tokio::test]
+async fn estimating_gas_returns_binary_search_iterations() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let step_count = Arc::new(AtomicUsize::new(0));
+    let step_count_for_executor = step_count.clone();
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(move |tx, _| {
+        step_count_for_executor.fetch_add(1, Ordering::SeqCst);
+        if tx.gas_limit() >= 100_000.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    assert!(estimate.binary_search_iterations > 0);
+    // The final step (which succeeds here) is not counted as a binary search iteration.
+    assert_eq!(
+        estimate.binary_search_iterations + 1,
+        step_count.load(Ordering::SeqCst)
+    );
+}
+
+#[tokio::test]
+async fn estimated_fee_scale_factor_is_clamped() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|tx, _| {
+        if tx.gas_limit() >= 100_000.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_estimate_gas_scale_factor = 2.0;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    assert_eq!(tx_sender.clamp_estimated_fee_scale_factor(0.5), 1.0);
+    assert_eq!(tx_sender.clamp_estimated_fee_scale_factor(1.5), 1.5);
+    assert_eq!(tx_sender.clamp_estimated_fee_scale_factor(1_000.0), 2.0);
+    assert_eq!(tx_sender.clamp_estimated_fee_scale_factor(f64::NAN), 1.0);
+
+    let tx = create_l2_transaction(10, 100);
+    let mut tx_body_gas_limits = vec![];
+    for scale_factor in [0.5, 1.0, 2.0, 1_000.0] {
+        let estimate = tx_sender
+            .estimate_gas(
+                tx.clone().into(),
+                scale_factor,
+                1_000,
+                &GasEstimationOptions::default(),
+            )
+            .await
+            .unwrap();
+        tx_body_gas_limits.push(estimate.tx_body_gas_limit);
+    }
+    // A sub-1.0 factor must not under-quote, and an excessive factor must not over-quote.
+    assert_eq!(tx_body_gas_limits[0], tx_body_gas_limits[1]);
+    assert_eq!(tx_body_gas_limits[2], tx_body_gas_limits[3]);
+    assert!(tx_body_gas_limits[2] > tx_body_gas_limits[1]);
+}
+
+#[test]
+fn validating_estimated_fee_scale_factor_bounds() {
+    let mut config = test_tx_sender_config(L2ChainId::default());
+    config.validate().unwrap();
+
+    for (min, max) in [(2.0, 1.0), (f64::NAN, 2.0), (1.0, f64::NAN), (0.0, 2.0)] {
+        config.min_estimate_gas_scale_factor = min;
+        config.max_estimate_gas_scale_factor = max;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("scale factor bounds"), "{err}");
+    }
+}
+
+#[test]
+fn validating_estimate_gas_retry_bump() {
+    let mut config = test_tx_sender_config(L2ChainId::default());
+    config.estimate_gas_retry_bump = 0.0;
+    config.validate().unwrap();
+
+    for retry_bump in [-0.1, f64::NAN, f64::INFINITY] {
+        config.estimate_gas_retry_bump = retry_bump;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("retry bump"), "{err}");
+    }
+}
+
+#[test]
+fn validating_max_concurrent_estimations() {
+    let mut config = test_tx_sender_config(L2ChainId::default());
+    config.max_concurrent_estimations = Some(1);
+    config.validate().unwrap();
+
+    config.max_concurrent_estimations = Some(0);
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("max concurrent estimations"), "{err}");
+}
+
+#[tokio::test]
+async fn estimating_gas_returns_min_viable_gas_limit() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|tx, _| {
+        if tx.gas_limit() >= 100_000.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    for scale_factor in [1.0, 1.5] {
+        let estimate = tx_sender
+            .estimate_gas(
+                tx.clone().into(),
+                scale_factor,
+                1_000,
+                &GasEstimationOptions::default(),
+            )
+            .await
+            .unwrap();
+        let min_viable_gas_limit = U256::from(estimate.min_viable_gas_limit);
+        assert!(
+            min_viable_gas_limit <= estimate.fee.gas_limit,
+            "{estimate:?}"
+        );
+        if scale_factor > 1.0 {
+            assert!(
+                min_viable_gas_limit < estimate.fee.gas_limit,
+                "{estimate:?}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn estimating_gas_for_paymaster_sponsored_tx() {
+    const BASE_GAS: u32 = 100_000;
+    const PAYMASTER_GAS: u32 = 50_000;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    fn required_gas(tx: &Transaction) -> u32 {
+        let has_paymaster = tx.payer() != tx.initiator_account();
+        BASE_GAS + if has_paymaster { PAYMASTER_GAS } else { 0 }
+    }
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|tx, _| {
+        if tx.gas_limit() >= required_gas(tx).into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    tx_executor.set_tx_metrics_responses(|tx| TransactionExecutionMetrics {
+        gas_used: required_gas(tx) as usize,
+        ..TransactionExecutionMetrics::default()
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let unsponsored_estimate = tx_sender
+        .estimate_gas(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(unsponsored_estimate.paymaster_gas, None);
+
+    let mut sponsored_tx = tx;
+    sponsored_tx.common_data.paymaster_params = PaymasterParams {
+        paymaster: Address::repeat_byte(0x11),
+        paymaster_input: vec![],
+    };
+    let estimate_without_paymaster_gas = tx_sender
+        .estimate_gas(
+            sponsored_tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    // Paymaster gas is only estimated on request.
+    assert_eq!(estimate_without_paymaster_gas.paymaster_gas, None);
+
+    let options = GasEstimationOptions {
+        estimate_paymaster_gas: true,
+        ..GasEstimationOptions::default()
+    };
+    let sponsored_estimate = tx_sender
+        .estimate_gas(sponsored_tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    assert_eq!(sponsored_estimate.paymaster_gas, Some(PAYMASTER_GAS));
+    assert_eq!(
+        sponsored_estimate.fee.gas_limit,
+        estimate_without_paymaster_gas.fee.gas_limit
+    );
+    assert!(
+        sponsored_estimate.fee.gas_limit
+            >= unsponsored_estimate.fee.gas_limit + U256::from(PAYMASTER_GAS) - 1_000,
+        "sponsored: {sponsored_estimate:?}, unsponsored: {unsponsored_estimate:?}"
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_returns_resolved_block_number() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+    let pending_block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    drop(storage);
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(move |_, block_args| {
+        assert_eq!(
+            block_args.resolved_block_number(),
+            pending_block_args.resolved_block_number()
+        );
+        ExecutionResult::Success { output: vec![] }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(estimate.block_number, MiniblockNumber(2));
+    assert_eq!(
+        estimate.block_number,
+        pending_block_args.resolved_block_number()
+    );
+}
+
+/// Protocol versions covering all [`MultiVMBaseSystemContracts`] used for gas estimation.
+const ESTIMATION_PROTOCOL_VERSIONS: [ProtocolVersionId; 7] = [
+    ProtocolVersionId::Version12, // pre-virtual-blocks
+    ProtocolVersionId::Version13, // post-virtual-blocks
+    ProtocolVersionId::Version17, // post-virtual-blocks upgrade fix
+    ProtocolVersionId::Version18, // post-boojum
+    ProtocolVersionId::Version19, // post-allowlist removal
+    ProtocolVersionId::Version20, // post-1.4.1
+    ProtocolVersionId::Version22, // post-1.4.2
+];
+
+/// Estimates gas for a simple transfer using the real VM sandbox with the specified pending protocol version.
+async fn estimate_gas_for_protocol_version(protocol_version: ProtocolVersionId) -> Fee {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    if protocol_version != ProtocolVersionId::latest() {
+        storage
+            .protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion {
+                id: protocol_version,
+                ..ProtocolVersion::default()
+            })
+            .await;
+    }
+    let miniblock = MiniblockHeader {
+        protocol_version: Some(protocol_version),
+        ..create_miniblock(1)
+    };
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock)
+        .await
+        .unwrap();
+    drop(storage);
+
+    let (tx_sender, _) =
+        create_test_tx_sender(pool, L2ChainId::default(), TransactionExecutor::Real).await;
+    let tx = create_l2_transaction(10, 100);
+    tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_or_else(|err| panic!("gas estimation failed for {protocol_version:?}: {err}"))
+}
+
+#[tokio::test]
+async fn estimations_are_counted_by_protocol_version() {
+    let protocol_version =
+        ProtocolVersionId::try_from(ProtocolVersionId::latest() as u16 - 1).unwrap();
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(ProtocolVersion {
+            id: protocol_version,
+            ..ProtocolVersion::default()
+        })
+        .await;
+    let miniblock = MiniblockHeader {
+        protocol_version: Some(protocol_version),
+        ..create_miniblock(1)
+    };
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock)
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let estimations = &SANDBOX_METRICS.estimate_gas_protocol_versions;
+    let estimations_before = estimations[&(protocol_version as u16)].get();
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(estimate.protocol_version, protocol_version);
+    // Metrics are global, so other tests may increment them concurrently.
+    assert!(estimations[&(protocol_version as u16)].get() > estimations_before);
+}
+
+#[test_casing(7, ESTIMATION_PROTOCOL_VERSIONS)]
+#[tokio::test]
+async fn estimating_gas_for_protocol_version(protocol_version: ProtocolVersionId) {
+    let fee = estimate_gas_for_protocol_version(protocol_version).await;
+    let min_gas_limit =
+        U256::from(get_intrinsic_constants(protocol_version.into()).l2_tx_intrinsic_gas);
+    assert!(fee.gas_limit > min_gas_limit, "{fee:?}");
+    assert!(fee.gas_limit <= U256::from(MAX_L2_TX_GAS_LIMIT), "{fee:?}");
+
+    // Estimates for a simple transfer shouldn't differ much among protocol versions.
+    let latest_fee = estimate_gas_for_protocol_version(ProtocolVersionId::latest()).await;
+    assert!(
+        fee.gas_limit < latest_fee.gas_limit * 2 && latest_fee.gas_limit < fee.gas_limit * 2,
+        "{fee:?} vs latest {latest_fee:?}"
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_with_factory_deps() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(estimate.gas_for_bytecodes_pubdata, 0);
+
+    let mut tx_with_deps = tx;
+    tx_with_deps.execute.factory_deps = Some(vec![vec![1; 32 * 3]]);
+    let estimate_with_deps = tx_sender
+        .estimate_gas(
+            tx_with_deps.into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert!(estimate_with_deps.gas_for_bytecodes_pubdata > 0);
+    assert_ne!(
+        estimate_with_deps.gas_for_bytecodes_pubdata,
+        estimate_with_deps.tx_body_gas_limit
+    );
+    let gas_limit = estimate_with_deps.fee.gas_limit;
+    let expected_min_gas_limit =
+        estimate_with_deps.gas_for_bytecodes_pubdata + estimate_with_deps.tx_body_gas_limit;
+    assert!(gas_limit > expected_min_gas_limit.into());
+}
+
+#[tokio::test]
+async fn estimating_gas_with_already_published_factory_dep() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let published_bytecode = vec![1; 32 * 3];
+    let new_bytecode = vec![2; 32 * 5];
+    let mut tx = create_l2_transaction(10, 100);
+    tx.execute.factory_deps = Some(vec![published_bytecode.clone(), new_bytecode.clone()]);
+    let estimate = tx_sender
+        .estimate_gas(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let known_code_key = get_known_code_key(&hash_bytecode(&published_bytecode));
+    let known_code_log = StorageLog::new_write_log(known_code_key, H256::from_low_u64_be(1));
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::zero(), vec![known_code_log])])
+        .await
+        .unwrap();
+
+    let reduced_estimate = tx_sender
+        .estimate_gas(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert!(
+        reduced_estimate.gas_for_bytecodes_pubdata < estimate.gas_for_bytecodes_pubdata,
+        "{reduced_estimate:?}"
+    );
+    assert!(reduced_estimate.gas_for_bytecodes_pubdata > 0);
+
+    // Only the new bytecode should be charged for.
+    let mut tx_with_new_bytecode = tx;
+    tx_with_new_bytecode.execute.factory_deps = Some(vec![new_bytecode]);
+    let new_bytecode_estimate = tx_sender
+        .estimate_gas(
+            tx_with_new_bytecode.into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        reduced_estimate.gas_for_bytecodes_pubdata,
+        new_bytecode_estimate.gas_for_bytecodes_pubdata
+    );
+}
+
+#[tokio::test]
+async fn estimating_l1_data_cost() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let cost = tx_sender
+        .estimate_l1_data_cost(&tx.clone().into())
+        .await
+        .unwrap();
+    assert_eq!(cost, U256::zero());
+
+    let mut costs = vec![];
+    for word_count in [3_u64, 33, 333] {
+        // Bytecode consisting of distinct words is not compressible.
+        let bytecode: Vec<_> = (0..word_count)
+            .flat_map(|i| H256::from_low_u64_be(i).0)
+            .collect();
+        let mut tx_with_deps = tx.clone();
+        tx_with_deps.execute.factory_deps = Some(vec![bytecode]);
+        let cost = tx_sender
+            .estimate_l1_data_cost(&tx_with_deps.into())
+            .await
+            .unwrap();
+        costs.push(cost);
+    }
+    assert!(costs[0] > U256::zero(), "{costs:?}");
+    assert!(
+        costs.windows(2).all(|window| window[0] < window[1]),
+        "{costs:?}"
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_with_uncompressed_bytecodes() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    // Bytecode consisting of repeated words is well compressible.
+    let bytecode = vec![1; 32 * 5];
+    assert!(compress_bytecode(&bytecode).unwrap().len() < bytecode.len());
+    let mut tx = create_l2_transaction(10, 100);
+    tx.execute.factory_deps = Some(vec![bytecode]);
+
+    let compressed_estimate = tx_sender
+        .estimate_gas(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    let options = GasEstimationOptions {
+        uncompressed_bytecodes: true,
+        ..GasEstimationOptions::default()
+    };
+    let uncompressed_estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    assert!(
+        uncompressed_estimate.gas_for_bytecodes_pubdata
+            > compressed_estimate.gas_for_bytecodes_pubdata,
+        "{uncompressed_estimate:?}"
+    );
+    assert!(uncompressed_estimate.fee.gas_limit > compressed_estimate.fee.gas_limit);
+}
+
+#[tokio::test]
+async fn streaming_estimation_steps() {
+    const REQUIRED_GAS_LIMIT: u32 = 1_234_567;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|tx, _| {
+        if tx.gas_limit() >= REQUIRED_GAS_LIMIT.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Halt {
+                reason: Halt::BootloaderOutOfGas,
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let options = GasEstimationOptions::default();
+    let steps: Vec<_> = tx_sender
+        .estimate_gas_steps(tx.clone().into(), 1.0, 1_000, options.clone())
+        .collect()
+        .await;
+    let expected_fee = tx_sender
+        .get_txs_fee_in_wei(tx.clone().into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+
+    let (last_step, iterations) = steps.split_last().unwrap();
+    assert_matches!(last_step, EstimationStep::Finished(Ok(fee)) if *fee == expected_fee);
+    assert_eq!(iterations.len(), estimate.binary_search_iterations);
+    for step in iterations {
+        let EstimationStep::Iteration { gas_limit, result } = step else {
+            panic!("Unexpected step: {step:?}");
+        };
+        // Gas limits reported in steps don't include the overhead, so they may succeed only with a larger gas limit.
+        if *gas_limit >= REQUIRED_GAS_LIMIT {
+            assert_matches!(result, ExecutionResult::Success { .. });
+        }
+    }
+    let successful_gas_limits = iterations.iter().filter_map(|step| match step {
+        EstimationStep::Iteration {
+            gas_limit,
+            result: ExecutionResult::Success { .. },
+        } => Some(*gas_limit),
+        _ => None,
+    });
+    assert_eq!(
+        successful_gas_limits.min(),
+        Some(estimate.tx_body_gas_limit)
+    );
+}
+
+/// Log writer collecting all logs into a shared buffer.
+#[derive(Debug, Clone, Default)]
+struct SharedLogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for SharedLogBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test_casing(2, [false, true])]
+#[tokio::test]
+async fn logging_estimation_summary(enabled: bool) {
+    let logs = SharedLogBuffer::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer({
+            let logs = logs.clone();
+            move || logs.clone()
+        })
+        .finish();
+    let _subscriber_guard = tracing::subscriber::set_default(subscriber);
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.log_estimation_summary = enabled;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let summary_line = logs
+        .lines()
+        .find(|line| line.contains("fee estimation summary"));
+    if enabled {
+        let summary_line = summary_line.expect("no summary logged");
+        assert!(summary_line.contains("DEBUG"), "{summary_line}");
+        let expected_iterations = format!("{} iterations", estimate.binary_search_iterations);
+        assert!(
+            summary_line.contains(&expected_iterations),
+            "{summary_line}"
+        );
+        assert!(summary_line.contains("final bounds"), "{summary_line}");
+    } else {
+        assert_eq!(summary_line, None);
+    }
+}
+
+#[tokio::test]
+async fn estimating_gas_with_overridden_l1_gas_price() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 50_000);
+    let mut fees = vec![];
+    for l1_gas_price in [1_000_000_000, 10_000_000_000] {
+        let options = GasEstimationOptions {
+            l1_gas_price_override: Some(l1_gas_price),
+            ..GasEstimationOptions::default()
+        };
+        let fee = tx_sender
+            .get_txs_fee_in_wei(tx.clone().into(), 1.0, 1_000, &options)
+            .await
+            .unwrap();
+        fees.push(fee);
+    }
+
+    // Pubdata price (and thus gas per pubdata) should scale with the L1 gas price.
+    let ratio = fees[1].gas_per_pubdata_limit.as_u64() as f64
+        / fees[0].gas_per_pubdata_limit.as_u64() as f64;
+    assert!((ratio - 10.0).abs() < 0.1, "{fees:?}");
+}
+
+/// Emulates a contract deploying another contract via CREATE; the deployment cost depends on the deployed address,
+/// which is derived from the sender nonce.
+fn gas_limit_for_deployment(sender: Address, nonce: Nonce) -> u64 {
+    let deployed_address = deployed_address_create(sender, nonce.0.into());
+    100_000 + 1_000 * u64::from(deployed_address.as_bytes()[19])
+}
+
+#[tokio::test]
+async fn estimating_gas_with_nonce_override() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|tx, _| {
+        let ExecuteTransactionCommon::L2(common_data) = &tx.common_data else {
+            unreachable!("unexpected tx: {tx:?}");
+        };
+        let required_gas_limit =
+            gas_limit_for_deployment(common_data.initiator_address, common_data.nonce);
+        if tx.gas_limit() >= required_gas_limit.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let sender = tx.initiator_account();
+    let tx_nonce = tx.nonce();
+    let other_nonce = (1..)
+        .map(Nonce)
+        .find(|&nonce| {
+            gas_limit_for_deployment(sender, nonce) != gas_limit_for_deployment(sender, tx_nonce)
+        })
+        .unwrap();
+
+    let mut tx_body_gas_limits = vec![];
+    for nonce_override in [None, Some(tx_nonce), Some(other_nonce)] {
+        let options = GasEstimationOptions {
+            nonce_override,
+            ..GasEstimationOptions::default()
+        };
+        let estimate = tx_sender
+            .estimate_gas(tx.clone().into(), 1.0, 0, &options)
+            .await
+            .unwrap();
+        tx_body_gas_limits.push(estimate.tx_body_gas_limit);
+    }
+
+    assert_eq!(tx_body_gas_limits[0], tx_body_gas_limits[1]);
+    // Executed gas limits include the tx overhead, so we only compare estimates with each other.
+    let other_nonce_needs_more_gas =
+        gas_limit_for_deployment(sender, other_nonce) > gas_limit_for_deployment(sender, tx_nonce);
+    assert_eq!(
+        tx_body_gas_limits[2] > tx_body_gas_limits[1],
+        other_nonce_needs_more_gas,
+        "{tx_body_gas_limits:?}"
+    );
+    assert_ne!(tx_body_gas_limits[2], tx_body_gas_limits[1]);
+}
+
+#[tokio::test]
+async fn estimating_gas_with_signature_override() {
+    const GAS_PER_SIGNATURE_BYTE: u64 = 1_000;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    // Emulates a custom account with the validation cost growing with the signature length.
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|tx, _| {
+        let ExecuteTransactionCommon::L2(common_data) = &tx.common_data else {
+            unreachable!("unexpected tx: {tx:?}");
+        };
+        assert!(!common_data.signature.is_empty());
+        let required_gas_limit =
+            100_000 + GAS_PER_SIGNATURE_BYTE * common_data.signature.len() as u64;
+        if tx.gas_limit() >= required_gas_limit.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let mut tx = create_l2_transaction(10, 100);
+    tx.common_data.signature = vec![];
+    let placeholder_estimate = tx_sender
+        .estimate_gas(tx.clone().into(), 1.0, 0, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+
+    let realistic_signature = vec![1; 65 * 3];
+    let options = GasEstimationOptions {
+        signature_override: Some(realistic_signature),
+        ..GasEstimationOptions::default()
+    };
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 0, &options)
+        .await
+        .unwrap();
+
+    // Executed gas limits include the tx overhead (which depends on the signature length as well),
+    // so we only compare estimates with each other.
+    assert!(
+        estimate.tx_body_gas_limit > placeholder_estimate.tx_body_gas_limit,
+        "{estimate:?}, {placeholder_estimate:?}"
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_with_operator_balance_override() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    // Emulates a contract taking a more expensive path if the operator balance is low.
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_with_args_responses(|tx, args| {
+        let is_operator_funded = args.operator_balance.unwrap_or_default() >= U256::from(1_000);
+        let required_gas_limit: u64 = if is_operator_funded { 100_000 } else { 300_000 };
+        if tx.gas_limit() >= required_gas_limit.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let default_estimate = tx_sender
+        .estimate_gas(tx.clone().into(), 1.0, 0, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    let options = GasEstimationOptions {
+        operator_balance_override: Some(1_000.into()),
+        ..GasEstimationOptions::default()
+    };
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 0, &options)
+        .await
+        .unwrap();
+
+    assert!(
+        estimate.tx_body_gas_limit < default_estimate.tx_body_gas_limit,
+        "{estimate:?}, {default_estimate:?}"
+    );
+}
+
+#[test]
+fn computing_acceptable_overestimation() {
+    assert_eq!(
+        TxSender::acceptable_overestimation(1_000, None, 100_000),
+        1_000
+    );
+    assert_eq!(
+        TxSender::acceptable_overestimation(0, Some(1.0), 100_000),
+        1_000
+    );
+    assert_eq!(
+        TxSender::acceptable_overestimation(5_000, Some(1.0), 100_000),
+        5_000
+    );
+    assert_eq!(
+        TxSender::acceptable_overestimation(5_000, Some(10.0), 100_000),
+        10_000
+    );
+    assert_eq!(
+        TxSender::acceptable_overestimation(0, Some(f64::NAN), 100_000),
+        0
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_with_relative_overestimation() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|tx, _| {
+        if tx.gas_limit() >= 100_000.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let precise_estimate = tx_sender
+        .estimate_gas(tx.clone().into(), 1.0, 0, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    let options = GasEstimationOptions {
+        acceptable_overestimation_pct: Some(1.0),
+        ..GasEstimationOptions::default()
+    };
+    let relative_estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 0, &options)
+        .await
+        .unwrap();
+
+    assert!(
+        relative_estimate.binary_search_iterations < precise_estimate.binary_search_iterations,
+        "{relative_estimate:?}, {precise_estimate:?}"
+    );
+    assert!(relative_estimate.tx_body_gas_limit >= precise_estimate.tx_body_gas_limit);
+    // The last iteration has an upper bound close to the resulting estimate, so the overestimation is about 1%.
+    let max_overestimation = relative_estimate.tx_body_gas_limit / 50;
+    assert!(
+        relative_estimate.tx_body_gas_limit - precise_estimate.tx_body_gas_limit
+            <= max_overestimation,
+        "{relative_estimate:?}, {precise_estimate:?}"
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_for_call_to_non_contract() {
+    const GAS_USED: u32 = 100_000;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let step_count = Arc::new(AtomicUsize::new(0));
+    let step_count_for_executor = step_count.clone();
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(move |tx, _| {
+        step_count_for_executor.fetch_add(1, Ordering::SeqCst);
+        if tx.gas_limit() >= GAS_USED.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    tx_executor.set_tx_metrics_responses(|_| TransactionExecutionMetrics {
+        gas_used: GAS_USED as usize,
+        ..TransactionExecutionMetrics::default()
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let mut tx = create_l2_transaction(10, 100);
+    tx.execute.contract_address = Address::repeat_byte(0x23);
+    tx.execute.calldata = b"no-op call".to_vec();
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 0, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(estimate.binary_search_iterations, 1);
+    assert_eq!(estimate.tx_body_gas_limit, GAS_USED);
+    // One step to get gas used with the maximum gas limit, and the final step.
+    assert_eq!(step_count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn estimating_gas_for_call_to_contract_deployed_by_preceding_tx() {
+    const GAS_USED: u32 = 100_000;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let target = Address::repeat_byte(0x23);
+    let deploy_tx = create_l2_transaction(10, 100);
+    let deployer = deploy_tx.initiator_account();
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_storage_logs_responses(move |tx| {
+        if tx.initiator_account() == deployer {
+            let log_type = StorageLogQueryType::InitialWrite;
+            vec![storage_log_query(
+                get_code_key(&target),
+                log_type,
+                1.into(),
+                false,
+            )]
+        } else {
+            vec![]
+        }
+    });
+    tx_executor.set_tx_responses(move |tx, _| {
+        if tx.initiator_account() == deployer || tx.gas_limit() >= GAS_USED.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let mut tx = create_l2_transaction(10, 100);
+    tx.execute.contract_address = target;
+    tx.execute.calldata = b"call".to_vec();
+    let estimate = tx_sender
+        .estimate_gas_after_txs(
+            tx.into(),
+            1.0,
+            0,
+            &GasEstimationOptions::default(),
+            &[deploy_tx.into()],
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+
+    // The target has code in the state produced by the preceding tx, so the binary search must not be skipped.
+    assert!(estimate.binary_search_iterations > 1, "{estimate:?}");
+    assert_eq!(estimate.tx_body_gas_limit, GAS_USED);
+}
+
+#[test_casing(2, [false, true])]
+#[tokio::test]
+async fn estimating_gas_for_deterministically_reverting_tx(probe_max_gas_limit: bool) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let step_count = Arc::new(AtomicUsize::new(0));
+    let step_count_for_executor = step_count.clone();
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(move |_, _| {
+        step_count_for_executor.fetch_add(1, Ordering::SeqCst);
+        ExecutionResult::Revert {
+            output: VmRevertReason::General {
+                msg: "unauthorized".to_owned(),
+                data: vec![],
+            },
+        }
+    });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.estimate_gas_probe_max_gas_limit = probe_max_gas_limit;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let err = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::ExecutionReverted(msg, _) if msg.contains("unauthorized"));
+
+    let step_count = step_count.load(Ordering::SeqCst);
+    if probe_max_gas_limit {
+        assert_eq!(step_count, 1);
+    } else {
+        // The binary search and the final step.
+        assert!(step_count > 10, "{step_count}");
+    }
+}
+
+#[tokio::test]
+async fn estimating_gas_with_raised_max_gas_limit() {
+    const REQUIRED_GAS_LIMIT: u32 = MAX_L2_TX_GAS_LIMIT as u32 + 1_000_000;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let tx_executor = || -> TransactionExecutor {
+        let mut tx_executor = MockTransactionExecutor::default();
+        tx_executor.set_tx_responses(|tx, _| {
+            if tx.gas_limit() >= REQUIRED_GAS_LIMIT.into() {
+                ExecutionResult::Success { output: vec![] }
+            } else {
+                ExecutionResult::Revert {
+                    output: VmRevertReason::General {
+                        msg: "out of gas".to_owned(),
+                        data: vec![],
+                    },
+                }
+            }
+        });
+        tx_executor.into()
+    };
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor()).await;
+    let tx = create_l2_transaction(10, 100);
+    tx_sender
+        .estimate_gas(tx.clone().into(), 1.0, 0, &GasEstimationOptions::default())
+        .await
+        .unwrap_err();
+
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.max_gas_limit_for_estimation = MAX_L2_TX_GAS_LIMIT as u32 * 2;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor()).await;
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 0, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(estimate.tx_body_gas_limit, REQUIRED_GAS_LIMIT);
+    assert!(estimate.fee.gas_limit > U256::from(MAX_L2_TX_GAS_LIMIT));
+}
+
+#[tokio::test]
+async fn estimating_gas_with_capped_gas_limit() {
+    const REQUIRED_GAS_LIMIT: u32 = MAX_L2_TX_GAS_LIMIT as u32 / 2 + 1;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|tx, _| {
+        if tx.gas_limit() >= REQUIRED_GAS_LIMIT.into() {
+            ExecutionResult::Success { output: vec![] }
+        } else {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "out of gas".to_owned(),
+                    data: vec![],
+                },
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas(tx.clone().into(), 1.0, 0, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    assert!(!estimate.gas_limit_capped, "{estimate:?}");
+    assert!(estimate.tx_body_gas_limit < MAX_L2_TX_GAS_LIMIT as u32);
+
+    // The scaled gas limit exceeds the cap.
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 2.5, 0, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+    assert!(estimate.gas_limit_capped, "{estimate:?}");
+    assert_eq!(estimate.tx_body_gas_limit, MAX_L2_TX_GAS_LIMIT as u32);
+}
+
+#[test_casing(2, [false, true])]
+#[tokio::test]
+async fn estimating_gas_for_reverting_tx_with_playground_revert_reasons(use_playground: bool) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Revert {
+        output: VmRevertReason::General {
+            msg: "terse".to_owned(),
+            data: vec![],
+        },
+    });
+    tx_executor.set_playground_tx_responses(|_, _| ExecutionResult::Revert {
+        output: VmRevertReason::General {
+            msg: "Insufficient allowance for token transfer".to_owned(),
+            data: vec![1, 2, 3],
+        },
+    });
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.estimate_gas_playground_revert_reasons = use_playground;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let err = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap_err();
+    let SubmitTxError::ExecutionReverted(msg, data) = err else {
+        panic!("Unexpected error: {err:?}");
+    };
+    if use_playground {
+        assert_eq!(msg, "Insufficient allowance for token transfer");
+        assert_eq!(data, [1, 2, 3]);
+    } else {
+        assert_eq!(msg, "terse");
+        assert!(data.is_empty());
+    }
+}
+
+#[derive(Debug)]
+struct PanickingSealer;
+
+impl ConditionalSealer for PanickingSealer {
+    fn find_unexecutable_reason(
+        &self,
+        _data: &SealData,
+        _protocol_version: ProtocolVersionId,
+    ) -> Option<UnexecutableCriterion> {
+        panic!("oops");
+    }
+
+    fn should_seal_l1_batch(
+        &self,
+        _l1_batch_number: u32,
+        _block_open_timestamp_ms: u128,
+        _tx_count: usize,
+        _block_data: &SealData,
+        _tx_data: &SealData,
+        _protocol_version: ProtocolVersionId,
+    ) -> SealResolution {
+        SealResolution::NoSeal
+    }
+}
+
+#[tokio::test]
+async fn sealer_panic_is_reported_as_error() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (mut tx_sender, _) = create_test_tx_sender(pool, L2ChainId::default(), tx_executor).await;
+    Arc::get_mut(&mut tx_sender.0).unwrap().sealer = Arc::new(PanickingSealer);
+
+    let tx = create_l2_transaction(10, 100);
+    let err = tx_sender
+        .ensure_tx_executable(tx.into(), &TransactionExecutionMetrics::default(), false)
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::Internal(err) if err.to_string().contains("oops"));
+}
+
+/// Sealer considering all transactions unexecutable because of the specified criterion.
+#[derive(Debug)]
+struct UnexecutableSealer(UnexecutableCriterion);
+
+impl ConditionalSealer for UnexecutableSealer {
+    fn find_unexecutable_reason(
+        &self,
+        _data: &SealData,
+        _protocol_version: ProtocolVersionId,
+    ) -> Option<UnexecutableCriterion> {
+        Some(self.0)
+    }
+
+    fn should_seal_l1_batch(
+        &self,
+        _l1_batch_number: u32,
+        _block_open_timestamp_ms: u128,
+        _tx_count: usize,
+        _block_data: &SealData,
+        _tx_data: &SealData,
+        _protocol_version: ProtocolVersionId,
+    ) -> SealResolution {
+        SealResolution::NoSeal
+    }
+}
+
+const UNEXECUTABLE_REASONS: [UnexecutableReason; 4] = [
+    UnexecutableReason::PubdataLimit,
+    UnexecutableReason::CircuitLimit,
+    UnexecutableReason::BlockGasLimit,
+    UnexecutableReason::SealCriterion,
+];
+
+#[test_casing(4, UNEXECUTABLE_REASONS)]
+#[tokio::test]
+async fn unexecutable_error_has_reason_code(reason: UnexecutableReason) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (mut tx_sender, _) = create_test_tx_sender(pool, L2ChainId::default(), tx_executor).await;
+    let criterion = UnexecutableCriterion {
+        name: "test_criterion",
+        reason,
+    };
+    Arc::get_mut(&mut tx_sender.0).unwrap().sealer = Arc::new(UnexecutableSealer(criterion));
+
+    let tx = create_l2_transaction(10, 100);
+    let err = tx_sender
+        .ensure_tx_executable(tx.into(), &TransactionExecutionMetrics::default(), false)
+        .unwrap_err();
+    let SubmitTxError::Unexecutable { code, message } = &err else {
+        panic!("Unexpected error: {err:?}");
+    };
+    assert_eq!(*code, reason);
+    assert!(message.contains("test_criterion"), "{message}");
+
+    let expected_data = u256_to_h256(reason.code().into()).as_bytes().to_vec();
+    assert_eq!(err.data(), expected_data);
+    let err = Web3Error::from(err);
+    assert_matches!(
+        err,
+        Web3Error::SubmitTransactionError(_, data) if data == expected_data
+    );
+}
+
+#[tokio::test]
+async fn unexecutable_error_code_for_pubdata_limit() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (mut tx_sender, _) = create_test_tx_sender(pool, L2ChainId::default(), tx_executor).await;
+    let sealer = SequencerSealer::new(StateKeeperConfig::for_tests());
+    Arc::get_mut(&mut tx_sender.0).unwrap().sealer = Arc::new(sealer);
+
+    let tx = create_l2_transaction(10, 100);
+    let tx_metrics = TransactionExecutionMetrics {
+        pubdata_published: u32::MAX / 2,
+        ..TransactionExecutionMetrics::default()
+    };
+    let err = tx_sender
+        .ensure_tx_executable(tx.into(), &tx_metrics, false)
+        .unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::Unexecutable {
+            code: UnexecutableReason::PubdataLimit,
+            ..
+        }
+    );
+}
+
+#[tokio::test]
+async fn estimating_gas_with_skipped_seal_check() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (mut tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+    let sealer = SequencerSealer::new(StateKeeperConfig::for_tests());
+    Arc::get_mut(&mut tx_sender.0).unwrap().sealer = Arc::new(sealer);
+
+    // Default metrics have no gas remaining for the batch tip, so the transaction is unexecutable.
+    let tx = create_l2_transaction(10, 100);
+    let err = tx_sender
+        .get_txs_fee_in_wei(
+            tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::Unexecutable { .. });
+
+    let options = GasEstimationOptions {
+        skip_seal_check: true,
+        ..GasEstimationOptions::default()
+    };
+    let fee = tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    assert!(fee.gas_limit > U256::zero());
+}
+
+#[tokio::test]
+async fn checking_whether_txs_fit_in_one_batch() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (mut tx_sender, _) = create_test_tx_sender(pool, L2ChainId::default(), tx_executor).await;
+    let sealer = SequencerSealer::new(StateKeeperConfig::for_tests());
+    Arc::get_mut(&mut tx_sender.0).unwrap().sealer = Arc::new(sealer);
+
+    // Each transaction is executable on its own, but only 2 of them fit into the pubdata limit of a batch (100 kB).
+    let tx_metrics = TransactionExecutionMetrics {
+        pubdata_published: 30_000,
+        gas_remaining: u32::MAX / 2,
+        ..TransactionExecutionMetrics::default()
+    };
+    let txs_and_metrics: Vec<_> = (0..4)
+        .map(|_| (create_l2_transaction(10, 100).into(), tx_metrics))
+        .collect();
+    for (tx, tx_metrics) in &txs_and_metrics {
+        tx_sender
+            .ensure_tx_executable(tx.clone(), tx_metrics, false)
+            .unwrap();
+    }
+
+    let fits = tx_sender
+        .fits_in_one_batch(txs_and_metrics[..2].to_vec())
+        .unwrap();
+    assert!(fits);
+    let fits = tx_sender.fits_in_one_batch(txs_and_metrics).unwrap();
+    assert!(!fits);
+}
+
+#[tokio::test]
+async fn estimating_gas_for_bundle() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let first_tx = create_l2_transaction(10, 100);
+    let second_tx = create_l2_transaction(10, 100);
+    assert_ne!(first_tx.initiator_account(), second_tx.initiator_account());
+    let options = GasEstimationOptions::default();
+    let bundle = vec![first_tx.clone().into(), second_tx.into()];
+    let fees = tx_sender
+        .estimate_bundle(bundle, 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    assert_eq!(fees.len(), 2);
+
+    // The following transaction from the same sender must have the next nonce.
+    let mut next_tx = first_tx.clone();
+    let bundle = vec![first_tx.clone().into(), next_tx.clone().into()];
+    let err = tx_sender
+        .estimate_bundle(bundle, 1.0, 1_000, &options)
+        .await
+        .unwrap_err();
+    assert_matches!(err, SubmitTxError::NonceIsTooLow(1, 1, 0, _));
+
+    next_tx.common_data.nonce = Nonce(1);
+    let bundle = vec![first_tx.into(), next_tx.into()];
+    let fees = tx_sender
+        .estimate_bundle(bundle, 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    assert_eq!(fees.len(), 2);
+}
+
+#[tokio::test]
+async fn estimating_gas_after_txs_reuses_preceding_state() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let preceding_tx = create_l2_transaction(10, 100);
+    let preceding_initiator = preceding_tx.initiator_account();
+    let written_key = StorageKey::new(AccountTreeId::new(Address::repeat_byte(1)), H256::zero());
+    let preceding_executions = Arc::new(AtomicUsize::new(0));
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_storage_logs_responses(move |tx| {
+        if tx.initiator_account() == preceding_initiator {
+            let log_type = StorageLogQueryType::InitialWrite;
+            vec![storage_log_query(written_key, log_type, 1.into(), false)]
+        } else {
+            vec![]
+        }
+    });
+    tx_executor.set_tx_with_args_responses({
+        let preceding_executions = preceding_executions.clone();
+        move |tx, args| {
+            if tx.initiator_account() == preceding_initiator {
+                preceding_executions.fetch_add(1, Ordering::SeqCst);
+                return ExecutionResult::Success { output: vec![] };
+            }
+            // The main transaction only succeeds in the state produced by the preceding transaction.
+            let written_value = args
+                .preceding_state
+                .as_ref()
+                .and_then(|state| state.storage_value(&written_key));
+            if written_value == Some(H256::from_low_u64_be(1)) {
+                ExecutionResult::Success { output: vec![] }
+            } else {
+                ExecutionResult::Revert {
+                    output: VmRevertReason::General {
+                        msg: "unexpected state".to_owned(),
+                        data: vec![],
+                    },
+                }
+            }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let estimate = tx_sender
+        .estimate_gas_after_txs(
+            tx.into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+            &[preceding_tx.into()],
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+    assert!(estimate.fee.gas_limit > U256::zero());
+    // The preceding transaction is executed once rather than on each estimation step.
+    assert_eq!(preceding_executions.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn preceding_txs_are_executed_without_minted_balance() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut preceding_tx = create_funded_tx(&mut storage).await;
+    preceding_tx.common_data.fee.gas_limit = 10_000_000.into();
+    let balance_key = storage_key_for_eth_balance(&preceding_tx.initiator_account());
+    let initial_balance = h256_to_u256(
+        storage
+            .storage_web3_dal()
+            .get_value(&balance_key)
+            .await
+            .unwrap(),
+    );
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    drop(storage);
+
+    let (tx_sender, _) =
+        create_test_tx_sender(pool, L2ChainId::default(), TransactionExecutor::Real).await;
+    let vm_permit = tx_sender.vm_concurrency_limiter().acquire().await.unwrap();
+    let base_fee = preceding_tx.common_data.fee.max_fee_per_gas.as_u64();
+    let state = tx_sender
+        .execute_preceding_txs(
+            &vm_permit,
+            &[preceding_tx.into()],
+            BatchFeeInput::sensible_l1_pegged_default(),
+            block_args,
+            base_fee,
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Subsequent transactions must observe the payer balance reduced by the paid fee, rather than the balance
+    // with the gas estimation top-up.
+    let balance = h256_to_u256(state.storage_value(&balance_key).unwrap());
+    assert!(balance < initial_balance, "{balance} >= {initial_balance}");
+}
+
+#[tokio::test]
+async fn estimating_gas_after_failing_tx() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let succeeding_tx = create_l2_transaction(10, 100);
+    let failing_tx = create_l2_transaction(10, 100);
+    let failing_initiator = failing_tx.initiator_account();
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(move |tx, _| {
+        if tx.initiator_account() == failing_initiator {
+            ExecutionResult::Revert {
+                output: VmRevertReason::General {
+                    msg: "oops".to_owned(),
+                    data: vec![],
+                },
+            }
+        } else {
+            ExecutionResult::Success { output: vec![] }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let err = tx_sender
+        .estimate_gas_after_txs(
+            tx.into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+            &[succeeding_tx.into(), failing_tx.into()],
+            None,
+            vec![],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::PrecedingTxFailed(1, msg) if msg.contains("oops")
+    );
+}
+
+fn create_l1_tx_with_mint(to_mint: u64) -> L1Tx {
+    let mut execute = create_l2_transaction(10, 100).execute;
+    execute.value = 1_000.into();
+    L1Tx {
+        execute,
+        common_data: L1TxCommonData {
+            sender: Address::repeat_byte(1),
+            gas_limit: 100_000.into(),
+            max_fee_per_gas: 100.into(),
+            gas_per_pubdata_limit: 800.into(),
+            to_mint: to_mint.into(),
+            ..L1TxCommonData::default()
+        },
+        received_timestamp_ms: 0,
+    }
+}
+
+#[test_casing(2, [false, true])]
+#[tokio::test]
+async fn estimating_gas_for_l1_tx_with_insufficient_mint(as_preceding_tx: bool) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let executed_tx_count = Arc::new(AtomicUsize::new(0));
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses({
+        let executed_tx_count = executed_tx_count.clone();
+        move |_, _| {
+            executed_tx_count.fetch_add(1, Ordering::SeqCst);
+            ExecutionResult::Success { output: vec![] }
+        }
+    });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    // Covers the gas, but not the value. Would've succeeded if the mint were overwritten before the check.
+    let l1_tx = create_l1_tx_with_mint(10_000_000);
+    let (tx, preceding_txs) = if as_preceding_tx {
+        let tx = create_l2_transaction(10, 100).into();
+        (tx, vec![l1_tx.into()])
+    } else {
+        (l1_tx.into(), vec![])
+    };
+    let err = tx_sender
+        .estimate_gas_after_txs(
+            tx,
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+            &preceding_txs,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        SubmitTxError::InsufficientMintForL1Tx(to_mint, required)
+            if to_mint == U256::from(10_000_000) && required == U256::from(10_001_000)
+    );
+    assert_eq!(executed_tx_count.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn estimating_gas_for_l1_tx_with_exact_mint() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l1_tx_with_mint(10_001_000);
+    tx_sender
+        .get_txs_fee_in_wei(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn reporting_pubdata_price_adjustment_in_estimates() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let generous_tx = create_l2_transaction(10, u32::MAX.into());
+    let estimate = tx_sender
+        .estimate_gas(
+            generous_tx.into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(estimate.pubdata_price_adjustment, None);
+
+    // The pubdata limit of this transaction is below the gas per pubdata derived from the default fee params.
+    let constrained_tx = create_l2_transaction(10, 50);
+    let estimate = tx_sender
+        .estimate_gas(
+            constrained_tx.clone().into(),
+            1.0,
+            1_000,
+            &GasEstimationOptions::default(),
+        )
+        .await
+        .unwrap();
+    let adjustment = estimate
+        .pubdata_price_adjustment
+        .expect("pubdata price was not adjusted");
+    assert!(adjustment.original_gas_per_pubdata > 50, "{adjustment:?}");
+    assert!(adjustment.adjusted_gas_per_pubdata <= 50, "{adjustment:?}");
+    assert_eq!(
+        estimate.fee.gas_per_pubdata_limit,
+        adjustment.adjusted_gas_per_pubdata.into()
+    );
+
+    let options = GasEstimationOptions {
+        skip_pubdata_price_adjustment: true,
+        ..GasEstimationOptions::default()
+    };
+    let diagnostic_estimate = tx_sender
+        .estimate_gas(constrained_tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    assert_eq!(diagnostic_estimate.pubdata_price_adjustment, None);
+    assert_eq!(
+        diagnostic_estimate.fee.gas_per_pubdata_limit,
+        adjustment.original_gas_per_pubdata.into()
+    );
+}
+
+#[tokio::test]
+async fn estimation_phase_latencies_are_reported() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+    let tx = create_l2_transaction(10, 100);
+    tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &GasEstimationOptions::default())
+        .await
+        .unwrap();
+
+    let mut registry = vise::Registry::empty();
+    registry.register_metrics(&*SANDBOX_METRICS);
+    let mut encoded_metrics = String::new();
+    registry
+        .encode(&mut encoded_metrics, vise::Format::OpenMetrics)
+        .unwrap();
+    for phase in ["preparation", "search"] {
+        let label = format!("phase=\"{phase}\"");
+        let has_phase_latency = encoded_metrics.lines().any(|line| {
+            line.starts_with("api_web3_estimate_gas_phase_latency") && line.contains(&label)
+        });
+        assert!(has_phase_latency, "{encoded_metrics}");
+    }
+}
+
+#[test_casing(2, [false, true])]
+#[tokio::test]
+async fn estimating_gas_by_extrapolation(accurate_gas_used: bool) {
+    const REQUIRED_GAS_LIMIT: u32 = 1_234_567;
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    // If gas used is not accurate, the extrapolated gas limit is insufficient.
+    let reported_gas_used = if accurate_gas_used {
+        REQUIRED_GAS_LIMIT
+    } else {
+        REQUIRED_GAS_LIMIT / 2
+    };
+    let tx_executor = || -> TransactionExecutor {
+        let mut tx_executor = MockTransactionExecutor::default();
+        tx_executor.set_tx_responses(|tx, _| {
+            if tx.gas_limit() >= REQUIRED_GAS_LIMIT.into() {
+                ExecutionResult::Success { output: vec![] }
+            } else {
+                ExecutionResult::Halt {
+                    reason: Halt::BootloaderOutOfGas,
+                }
+            }
+        });
+        tx_executor.set_tx_metrics_responses(move |_| TransactionExecutionMetrics {
+            gas_used: reported_gas_used as usize,
+            ..TransactionExecutionMetrics::default()
+        });
+        tx_executor.into()
+    };
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let options = GasEstimationOptions::default();
+    let binary_search_estimate = tx_sender
+        .estimate_gas(tx.clone().into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+
+    let mut tx_sender_config = test_tx_sender_config(L2ChainId::default());
+    tx_sender_config.estimate_gas_by_extrapolation = true;
+    let (tx_sender, _) =
+        create_test_tx_sender_with_config(pool.clone(), tx_sender_config, tx_executor()).await;
+    let extrapolated_estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+
+    if accurate_gas_used {
+        assert_eq!(extrapolated_estimate.binary_search_iterations, 2);
+        // The extrapolated gas limit doesn't account for the overhead being included into the execution gas limit.
+        let binary_search_gas_limit = binary_search_estimate.fee.gas_limit.as_u32();
+        let extrapolated_gas_limit = extrapolated_estimate.fee.gas_limit.as_u32();
+        assert!(
+            (binary_search_gas_limit..=binary_search_gas_limit * 11 / 10)
+                .contains(&extrapolated_gas_limit),
+            "{binary_search_estimate:?} vs {extrapolated_estimate:?}"
+        );
+    } else {
+        assert!(
+            extrapolated_estimate.binary_search_iterations > 2,
+            "{extrapolated_estimate:?}"
+        );
+        assert!(
+            extrapolated_estimate.tx_body_gas_limit > REQUIRED_GAS_LIMIT / 2,
+            "{extrapolated_estimate:?}"
+        );
+        let gas_limit_diff = extrapolated_estimate
+            .fee
+            .gas_limit
+            .as_u32()
+            .abs_diff(binary_search_estimate.fee.gas_limit.as_u32());
+        assert!(
+            gas_limit_diff <= 1_000,
+            "{binary_search_estimate:?} vs {extrapolated_estimate:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn estimating_gas_with_call_trace() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let tx = create_l2_transaction(10, 100);
+    let options = GasEstimationOptions::default();
+    let (traced_estimate, call_trace) = tx_sender
+        .estimate_gas_with_call_trace(tx.clone().into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    let [call] = call_trace.as_slice() else {
+        panic!("unexpected call trace: {call_trace:?}");
+    };
+    assert_eq!(call.to, tx.execute.contract_address);
+    // The traced execution must use the estimated gas limit.
+    assert_eq!(call.gas, traced_estimate.fee.gas_limit.as_u32());
+
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    assert_eq!(estimate.fee, traced_estimate.fee);
+    assert_eq!(
+        estimate.binary_search_iterations,
+        traced_estimate.binary_search_iterations
+    );
+}
+
+#[test_casing(2, [false, true])]
+#[tokio::test]
+async fn estimating_gas_for_create2_deployment(occupied: bool) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let mut tx_executor = MockTransactionExecutor::default();
+    tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+    let (tx_sender, _) =
+        create_test_tx_sender(pool.clone(), L2ChainId::default(), tx_executor.into()).await;
+
+    let mut tx = create_l2_transaction(10, 100);
+    let salt = H256::repeat_byte(1);
+    let bytecode_hash = H256::repeat_byte(2);
+    let constructor_input = vec![3; 64];
+    tx.execute.contract_address = CONTRACT_DEPLOYER_ADDRESS;
+    tx.execute.calldata =
+        Execute::encode_deploy_params_create2(salt, bytecode_hash, constructor_input.clone());
+    let expected_address = deployed_address_create2(
+        tx.initiator_account(),
+        salt,
+        bytecode_hash,
+        &constructor_input,
+    );
+    if occupied {
+        let code_log = StorageLog::new_write_log(get_code_key(&expected_address), bytecode_hash);
+        storage
+            .storage_logs_dal()
+            .append_storage_logs(MiniblockNumber(0), &[(H256::zero(), vec![code_log])])
+            .await
+            .unwrap();
+    }
+
+    let options = GasEstimationOptions::default();
+    let result = tx_sender
+        .estimate_gas(tx.clone().into(), 1.0, 1_000, &options)
+        .await;
+    if occupied {
+        assert_matches!(
+            result.unwrap_err(),
+            SubmitTxError::DeploymentAddressOccupied(address) if address == expected_address
+        );
+    } else {
+        assert_eq!(result.unwrap().create2_address, Some(expected_address));
+    }
+
+    // Calls to other deployer methods are not treated as CREATE2 deployments.
+    tx.execute.calldata =
+        Execute::encode_deploy_params_create(salt, bytecode_hash, constructor_input);
+    let estimate = tx_sender
+        .estimate_gas(tx.into(), 1.0, 1_000, &options)
+        .await
+        .unwrap();
+    assert_eq!(estimate.create2_address, None);
+}
@@ -1,9 +1,10 @@
+use chrono::{DateTime, Utc};
 use zksync_dal::transactions_dal::L2TxSubmissionResult;
 use zksync_types::{
     api::{Transaction, TransactionDetails, TransactionId},
     fee::TransactionExecutionMetrics,
     l2::L2Tx,
-    Address, Nonce, H256,
+    Address, MiniblockNumber, Nonce, H256,
 };
 use zksync_web3_decl::error::Web3Error;
 
@@ -23,11 +24,13 @@ use super::SubmitTxError;
 /// and may be implemented as no-ops.
 #[async_trait::async_trait]
 pub trait TxSink: std::fmt::Debug + Send + Sync + 'static {
-    /// Ensures that transaction is propagated to the mempool.
+    /// Ensures that transaction is propagated to the mempool. `valid_until` is the deadline for the transaction
+    /// inclusion; sinks should persist it if they are able to.
     async fn submit_tx(
         &self,
         tx: L2Tx,
         execution_metrics: TransactionExecutionMetrics,
+        valid_until: Option<DateTime<Utc>>,
     ) -> Result<L2TxSubmissionResult, SubmitTxError>;
 
     /// Attempts to look up the pending nonce for the account in the sink-specific storage.
@@ -54,4 +57,13 @@ pub trait TxSink: std::fmt::Debug + Send + Sync + 'static {
     ) -> Result<Option<TransactionDetails>, Web3Error> {
         Ok(None)
     }
+
+    /// Attempts to look up the latest sealed miniblock at the transaction destination (e.g., in the master DB
+    /// or on the main node). Used to check whether the state read via the replica pool is stale.
+    /// By default, returns `Ok(None)`, in which case the staleness check is skipped.
+    async fn lookup_latest_sealed_miniblock(
+        &self,
+    ) -> Result<Option<MiniblockNumber>, SubmitTxError> {
+        Ok(None)
+    }
 }
@@ -1,8 +1,8 @@
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, SubmissionAudit, TracerConfig},
     debug_flat_call::DebugCallFlat,
     transaction_request::CallRequest,
-    H256,
+    Bytes, H256,
 };
 use zksync_web3_decl::{
     jsonrpsee::core::{async_trait, RpcResult},
@@ -63,4 +63,14 @@ impl DebugNamespaceServer for DebugNamespace {
             .await
             .map_err(|err| self.current_method().map_err(err))
     }
+
+    async fn audit_submission(
+        &self,
+        tx_bytes: Bytes,
+        block: Option<BlockId>,
+    ) -> RpcResult<SubmissionAudit> {
+        self.debug_audit_submission_impl(tx_bytes, block)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
 }
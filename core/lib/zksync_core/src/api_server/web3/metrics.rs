@@ -27,6 +27,16 @@ impl From<&ApiTransport> for ApiTransportLabel {
     }
 }
 
+/// Kind of error returned by the main node when proxying a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "kind", rename_all = "snake_case")]
+pub(in crate::api_server) enum ProxiedTxErrorKind {
+    /// Main node has rejected the transaction, e.g. because it has failed main node validation.
+    Rejected,
+    /// Transaction hasn't reached the main node, e.g. because of a network error.
+    Transport,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
 #[metrics(rename_all = "snake_case")]
 enum BlockIdLabel {
@@ -174,6 +184,9 @@ pub(in crate::api_server) struct ApiMetrics {
     /// Number of transaction submission errors for a specific submission error reason.
     #[metrics(labels = ["reason"])]
     pub submit_tx_error: LabeledFamily<&'static str, Counter>,
+    /// Number of errors proxying transactions to the main node on the external node. Unlike [`Self::submit_tx_error`],
+    /// these errors occur after a transaction has passed local validation.
+    pub proxied_tx_errors: Family<ProxiedTxErrorKind, Counter>,
 
     #[metrics(buckets = Buckets::exponential(1.0..=128.0, 2.0))]
     pub web3_in_flight_requests: Family<ApiTransportLabel, Histogram<usize>>,
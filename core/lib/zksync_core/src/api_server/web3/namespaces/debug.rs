@@ -6,13 +6,13 @@ use once_cell::sync::OnceCell;
 use zksync_dal::CoreDal;
 use zksync_system_constants::MAX_ENCODED_TX_SIZE;
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, SubmissionAudit, TracerConfig},
     debug_flat_call::{flatten_debug_calls, DebugCallFlat},
     fee_model::BatchFeeInput,
     l2::L2Tx,
     transaction_request::CallRequest,
     vm_trace::Call,
-    AccountTreeId, H256,
+    AccountTreeId, Bytes, H256,
 };
 use zksync_web3_decl::error::Web3Error;
 
@@ -211,6 +211,45 @@ impl DebugNamespace {
         Ok(call.into())
     }
 
+    #[tracing::instrument(skip(self, tx_bytes))]
+    pub async fn debug_audit_submission_impl(
+        &self,
+        tx_bytes: Bytes,
+        block_id: Option<BlockId>,
+    ) -> Result<SubmissionAudit, Web3Error> {
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
+        self.current_method().set_block_id(block_id);
+
+        let (mut tx, hash) = self.state.parse_transaction_bytes(&tx_bytes.0)?;
+        tx.set_input(tx_bytes.0, hash);
+
+        let mut connection = self.state.connection_pool.connection_tagged("api").await?;
+        let block_args = self
+            .state
+            .resolve_block_args(&mut connection, block_id)
+            .await?;
+        drop(connection);
+        self.current_method().set_block_diff(
+            self.state
+                .last_sealed_miniblock
+                .diff_with_block_args(&block_args),
+        );
+
+        let audit = self.state.tx_sender.audit_submission(tx, block_args).await;
+        Ok(SubmissionAudit {
+            block_number: audit.block_number.0.into(),
+            accepted: audit.is_accepted(),
+            rejection_reason: audit.rejection.as_ref().map(ToString::to_string),
+            rejection_code: audit
+                .rejection
+                .as_ref()
+                .map(|err| err.prom_error_code().to_owned()),
+            gas_used: audit
+                .execution_metrics
+                .map(|metrics| metrics.gas_used.into()),
+        })
+    }
+
     fn shared_args(&self) -> TxSharedArgs {
         let sender_config = self.sender_config();
         TxSharedArgs {
@@ -220,6 +259,7 @@ impl DebugNamespace {
             caches: self.state.tx_sender.storage_caches().clone(),
             validation_computational_gas_limit: BLOCK_GAS_LIMIT,
             chain_id: sender_config.chain_id,
+            execution_timeout: sender_config.vm_execution_timeout,
         }
     }
 }
@@ -21,8 +21,9 @@ use zksync_web3_decl::{
     types::{Address, Block, Filter, FilterChanges, Log, U64},
 };
 
-use crate::api_server::web3::{
-    backend_jsonrpsee::MethodTracer, metrics::API_METRICS, state::RpcState, TypedFilter,
+use crate::api_server::{
+    tx_sender::GasEstimationOptions,
+    web3::{backend_jsonrpsee::MethodTracer, metrics::API_METRICS, state::RpcState, TypedFilter},
 };
 
 pub const EVENT_TOPIC_NUMBER_LIMIT: usize = 4;
@@ -126,7 +127,12 @@ impl EthNamespace {
         let fee = self
             .state
             .tx_sender
-            .get_txs_fee_in_wei(tx.into(), scale_factor, acceptable_overestimation)
+            .get_txs_fee_in_wei(
+                tx.into(),
+                scale_factor,
+                acceptable_overestimation,
+                &GasEstimationOptions::default(),
+            )
             .await?;
         Ok(fee.gas_limit)
     }
@@ -598,7 +604,11 @@ impl EthNamespace {
 
         let submit_result = self.state.tx_sender.submit_tx(tx).await;
         submit_result.map(|_| hash).map_err(|err| {
-            tracing::debug!("Send raw transaction error: {err}");
+            if err.is_user_error() {
+                tracing::debug!("Send raw transaction error: {err}");
+            } else {
+                tracing::warn!("Send raw transaction error: {err}");
+            }
             API_METRICS.submit_tx_error[&err.prom_error_code()].inc();
             err.into()
         })
@@ -28,6 +28,7 @@ use zksync_web3_decl::{
 
 use crate::api_server::{
     tree::TreeApiError,
+    tx_sender::GasEstimationOptions,
     web3::{backend_jsonrpsee::MethodTracer, RpcState},
 };
 
@@ -102,7 +103,12 @@ impl ZksNamespace {
         Ok(self
             .state
             .tx_sender
-            .get_txs_fee_in_wei(tx, scale_factor, acceptable_overestimation)
+            .get_txs_fee_in_wei(
+                tx,
+                scale_factor,
+                acceptable_overestimation,
+                &GasEstimationOptions::default(),
+            )
             .await?)
     }
 
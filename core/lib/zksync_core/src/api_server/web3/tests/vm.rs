@@ -254,6 +254,61 @@ async fn send_raw_transaction_after_snapshot_recovery() {
     .await;
 }
 
+#[derive(Debug)]
+struct AuditSubmissionTest;
+
+#[async_trait]
+impl HttpTest for AuditSubmissionTest {
+    fn transaction_executor(&self) -> MockTransactionExecutor {
+        let mut tx_executor = MockTransactionExecutor::default();
+        tx_executor.set_tx_responses(|_, _| ExecutionResult::Success { output: vec![] });
+        tx_executor
+    }
+
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let (tx_bytes, tx_hash) = SendRawTransactionTest::transaction_bytes_and_hash();
+        let audit = client
+            .audit_submission(tx_bytes.clone().into(), None)
+            .await?;
+        assert_eq!(audit.block_number, 1.into());
+        assert!(!audit.accepted);
+        assert_eq!(
+            audit.rejection_code.as_deref(),
+            Some("not-enough-balance-for-fee")
+        );
+        assert_eq!(audit.gas_used, None);
+
+        let mut storage = pool.connection().await?;
+        storage
+            .storage_logs_dal()
+            .append_storage_logs(
+                MiniblockNumber(0),
+                &[(
+                    H256::zero(),
+                    vec![SendRawTransactionTest::balance_storage_log()],
+                )],
+            )
+            .await?;
+
+        let audit = client.audit_submission(tx_bytes.into(), None).await?;
+        assert!(audit.accepted, "{audit:?}");
+        assert_eq!(audit.rejection_reason, None);
+        assert!(audit.gas_used.is_some());
+        // The transaction must not be submitted.
+        let tx_details = storage
+            .transactions_web3_dal()
+            .get_transaction_details(tx_hash)
+            .await?;
+        assert!(tx_details.is_none(), "{tx_details:?}");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn auditing_submission() {
+    test_http_server(AuditSubmissionTest).await;
+}
+
 #[derive(Debug)]
 struct TraceCallTest;
 
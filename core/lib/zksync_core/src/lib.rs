@@ -347,6 +347,9 @@ pub async fn initialize_components(
             &api_config.web3_json_rpc,
             network_config.zksync_network_id,
         );
+        tx_sender_config
+            .validate()
+            .context("invalid transaction sender config")?;
         let internal_api_config = InternalApiConfig::new(
             &network_config,
             &api_config.web3_json_rpc,
@@ -1102,15 +1105,27 @@ async fn build_tx_sender(
     master_pool: ConnectionPool<Core>,
     batch_fee_model_input_provider: Arc<dyn BatchFeeModelInputProvider>,
     storage_caches: PostgresStorageCaches,
-) -> (TxSender, VmConcurrencyBarrier) {
+) -> anyhow::Result<(TxSender, VmConcurrencyBarrier)> {
     let sequencer_sealer = SequencerSealer::new(state_keeper_config.clone());
-    let master_pool_sink = MasterPoolSink::new(master_pool);
+    // If both pools connect to the same database (e.g., in small deployments), transactions are inserted using
+    // the replica pool as well. This is safe since there's no replication lag, and it doesn't make API servers
+    // contend with other components for master pool connections.
+    let sink_pool = if replica_pool.has_same_database(&master_pool) {
+        tracing::info!(
+            "Replica and master pools point to the same database; using the replica pool for both"
+        );
+        replica_pool.clone()
+    } else {
+        master_pool
+    };
+    let master_pool_sink = MasterPoolSink::new(sink_pool);
     let tx_sender_builder = TxSenderBuilder::new(
         tx_sender_config.clone(),
         replica_pool.clone(),
         Arc::new(master_pool_sink),
     )
-    .with_sealer(Arc::new(sequencer_sealer));
+    .with_sealer(Arc::new(sequencer_sealer))
+    .require_sealer();
 
     let max_concurrency = web3_json_config.vm_concurrency_limit();
     let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
@@ -1121,12 +1136,12 @@ async fn build_tx_sender(
     let tx_sender = tx_sender_builder
         .build(
             Arc::new(batch_fee_input_provider),
-            Arc::new(vm_concurrency_limiter),
+            Some(Arc::new(vm_concurrency_limiter)),
             ApiContracts::load_from_disk(),
             storage_caches,
         )
-        .await;
-    (tx_sender, vm_barrier)
+        .await?;
+    Ok((tx_sender, vm_barrier))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1154,7 +1169,7 @@ async fn run_http_api(
         batch_fee_model_input_provider,
         storage_caches,
     )
-    .await;
+    .await?;
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
     if with_debug_namespace {
@@ -1217,7 +1232,7 @@ async fn run_ws_api(
         batch_fee_model_input_provider,
         storage_caches,
     )
-    .await;
+    .await?;
     let last_miniblock_pool = ConnectionPool::<Core>::singleton(postgres_config.replica_url()?)
         .build()
         .await
@@ -87,6 +87,8 @@ impl fmt::Display for BlockStage {
 pub(crate) enum TxStage {
     Mempool(L2TxSubmissionResult),
     Proxied,
+    /// Transaction was rejected because another transaction with the same nonce was being submitted concurrently.
+    ConcurrentNonceConflict,
     StateKeeper,
     Block(BlockStage),
 }
@@ -100,6 +102,9 @@ impl fmt::Display for TxStage {
             }
             Self::Mempool(result) => write!(formatter, "mempool_{result}"),
             Self::Proxied => formatter.write_str("proxied"),
+            Self::ConcurrentNonceConflict => {
+                formatter.write_str("mempool_concurrent_nonce_conflict")
+            }
             Self::StateKeeper => formatter.write_str("state_keeper"),
             Self::Block(stage) => fmt::Display::fmt(stage, formatter),
         }
@@ -175,6 +180,8 @@ pub(crate) struct ExternalNodeMetrics {
     pub synced: Gauge<u64>,
     /// Current sync lag of the external node.
     pub sync_lag: Gauge<u64>,
+    /// Time since the oldest sealed L1 batch that is not committed on L1 was created. Zero if all sealed batches are committed.
+    pub oldest_uncommitted_l1_batch_age: Gauge<Duration>,
     /// Number of the last L1 batch checked by the re-org detector or consistency checker.
     pub last_correct_batch: Family<CheckerComponent, Gauge<u64>>,
     /// Number of the last miniblock checked by the re-org detector or consistency checker.
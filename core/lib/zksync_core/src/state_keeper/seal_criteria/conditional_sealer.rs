@@ -9,7 +9,25 @@ use std::fmt;
 use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_types::ProtocolVersionId;
 
-use super::{criteria, SealCriterion, SealData, SealResolution, AGGREGATION_METRICS};
+use super::{
+    criteria, SealCriterion, SealData, SealResolution, UnexecutableReason, AGGREGATION_METRICS,
+};
+
+/// Seal criterion deeming a transaction unexecutable, as returned by
+/// [`ConditionalSealer::find_unexecutable_reason()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnexecutableCriterion {
+    /// Name of the criterion, the same as used in metrics.
+    pub name: &'static str,
+    /// Machine-readable classification of the criterion.
+    pub reason: UnexecutableReason,
+}
+
+impl fmt::Display for UnexecutableCriterion {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.name)
+    }
+}
 
 /// Checks if an L1 batch should be sealed after executing a transaction.
 pub trait ConditionalSealer: 'static + fmt::Debug + Send + Sync {
@@ -20,7 +38,7 @@ pub trait ConditionalSealer: 'static + fmt::Debug + Send + Sync {
         &self,
         data: &SealData,
         protocol_version: ProtocolVersionId,
-    ) -> Option<&'static str>;
+    ) -> Option<UnexecutableCriterion>;
 
     /// Returns the action that should be taken by the state keeper after executing a transaction.
     fn should_seal_l1_batch(
@@ -50,7 +68,7 @@ impl ConditionalSealer for SequencerSealer {
         &self,
         data: &SealData,
         protocol_version: ProtocolVersionId,
-    ) -> Option<&'static str> {
+    ) -> Option<UnexecutableCriterion> {
         for sealer in &self.sealers {
             const MOCK_BLOCK_TIMESTAMP: u128 = 0;
             const TX_COUNT: usize = 1;
@@ -64,7 +82,10 @@ impl ConditionalSealer for SequencerSealer {
                 protocol_version,
             );
             if matches!(resolution, SealResolution::Unexecutable(_)) {
-                return Some(sealer.prom_criterion_name());
+                return Some(UnexecutableCriterion {
+                    name: sealer.prom_criterion_name(),
+                    reason: sealer.unexecutable_reason(),
+                });
             }
         }
         None
@@ -153,7 +174,7 @@ impl ConditionalSealer for NoopSealer {
         &self,
         _data: &SealData,
         _protocol_version: ProtocolVersionId,
-    ) -> Option<&'static str> {
+    ) -> Option<UnexecutableCriterion> {
         None
     }
 
@@ -2,7 +2,9 @@ use zksync_types::ProtocolVersionId;
 
 use crate::{
     gas_tracker::new_block_gas_count,
-    state_keeper::seal_criteria::{SealCriterion, SealData, SealResolution, StateKeeperConfig},
+    state_keeper::seal_criteria::{
+        SealCriterion, SealData, SealResolution, StateKeeperConfig, UnexecutableReason,
+    },
 };
 
 /// This is a temporary solution.
@@ -46,6 +48,10 @@ impl SealCriterion for GasCriterion {
     fn prom_criterion_name(&self) -> &'static str {
         "gas"
     }
+
+    fn unexecutable_reason(&self) -> UnexecutableReason {
+        UnexecutableReason::BlockGasLimit
+    }
 }
 
 #[cfg(test)]
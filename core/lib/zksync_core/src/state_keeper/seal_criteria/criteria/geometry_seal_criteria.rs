@@ -5,7 +5,9 @@ use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_types::{tx::tx_execution_info::ExecutionMetrics, ProtocolVersionId};
 
 // Local uses
-use crate::state_keeper::seal_criteria::{SealCriterion, SealData, SealResolution};
+use crate::state_keeper::seal_criteria::{
+    SealCriterion, SealData, SealResolution, UnexecutableReason,
+};
 
 // Collected vm execution metrics should fit into geometry limits.
 // Otherwise witness generation will fail and proof won't be generated.
@@ -15,6 +17,7 @@ pub struct CircuitsCriterion;
 
 trait MetricExtractor {
     const PROM_METRIC_CRITERION_NAME: &'static str;
+    const UNEXECUTABLE_REASON: UnexecutableReason;
     fn limit_per_block(protocol_version: ProtocolVersionId) -> usize;
     fn extract(metric: &ExecutionMetrics) -> usize;
 }
@@ -62,10 +65,15 @@ where
     fn prom_criterion_name(&self) -> &'static str {
         T::PROM_METRIC_CRITERION_NAME
     }
+
+    fn unexecutable_reason(&self) -> UnexecutableReason {
+        T::UNEXECUTABLE_REASON
+    }
 }
 
 impl MetricExtractor for CircuitsCriterion {
     const PROM_METRIC_CRITERION_NAME: &'static str = "circuits";
+    const UNEXECUTABLE_REASON: UnexecutableReason = UnexecutableReason::CircuitLimit;
 
     fn limit_per_block(_protocol_version_id: ProtocolVersionId) -> usize {
         const MAX_NUMBER_OF_CIRCUITS: usize = 24100;
@@ -2,7 +2,7 @@ use multivm::utils::execution_metrics_bootloader_batch_tip_overhead;
 use zksync_types::ProtocolVersionId;
 
 use crate::state_keeper::seal_criteria::{
-    SealCriterion, SealData, SealResolution, StateKeeperConfig,
+    SealCriterion, SealData, SealResolution, StateKeeperConfig, UnexecutableReason,
 };
 
 #[derive(Debug)]
@@ -63,6 +63,10 @@ impl SealCriterion for PubDataBytesCriterion {
     fn prom_criterion_name(&self) -> &'static str {
         "pub_data_size"
     }
+
+    fn unexecutable_reason(&self) -> UnexecutableReason {
+        UnexecutableReason::PubdataLimit
+    }
 }
 
 #[cfg(test)]
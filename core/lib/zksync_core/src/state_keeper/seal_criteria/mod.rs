@@ -25,7 +25,9 @@ use zksync_utils::time::millis_since;
 mod conditional_sealer;
 pub(super) mod criteria;
 
-pub use self::conditional_sealer::{ConditionalSealer, NoopSealer, SequencerSealer};
+pub use self::conditional_sealer::{
+    ConditionalSealer, NoopSealer, SequencerSealer, UnexecutableCriterion,
+};
 use super::{extractors, metrics::AGGREGATION_METRICS, updates::UpdatesManager};
 use crate::gas_tracker::{gas_count_from_tx_and_metrics, gas_count_from_writes};
 
@@ -73,6 +75,31 @@ impl SealResolution {
     }
 }
 
+/// Machine-readable cause of a transaction being unexecutable. Numeric codes returned by [`Self::code()`]
+/// are stable and can be relied upon by API clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnexecutableReason {
+    /// Transaction produces more pubdata than fits into a batch.
+    PubdataLimit,
+    /// Transaction requires more circuits than fit into a batch.
+    CircuitLimit,
+    /// Transaction requires more gas than fits into a batch.
+    BlockGasLimit,
+    /// Transaction is rejected by another seal criterion.
+    SealCriterion,
+}
+
+impl UnexecutableReason {
+    pub fn code(self) -> u16 {
+        match self {
+            Self::PubdataLimit => 1,
+            Self::CircuitLimit => 2,
+            Self::BlockGasLimit => 3,
+            Self::SealCriterion => 4,
+        }
+    }
+}
+
 /// Information about transaction or block applicable either to a single transaction, or
 /// to the entire miniblock / L1 batch.
 #[derive(Debug, Default)]
@@ -104,6 +131,21 @@ impl SealData {
             gas_remaining: tx_metrics.gas_remaining,
         }
     }
+
+    /// Accumulates data for a transaction executed after all transactions covered by this data.
+    /// Storage writes are not deduplicated across transactions, so write metrics may be overestimated.
+    pub(crate) fn accumulate(&mut self, tx_data: Self) {
+        self.execution_metrics += tx_data.execution_metrics;
+        self.gas_count += tx_data.gas_count;
+        self.cumulative_size += tx_data.cumulative_size;
+        self.writes_metrics.initial_storage_writes += tx_data.writes_metrics.initial_storage_writes;
+        self.writes_metrics.repeated_storage_writes +=
+            tx_data.writes_metrics.repeated_storage_writes;
+        self.writes_metrics.total_updated_values_size +=
+            tx_data.writes_metrics.total_updated_values_size;
+        // The batch tip is executed after the last transaction, so only its remaining gas matters.
+        self.gas_remaining = tx_data.gas_remaining;
+    }
 }
 
 pub(super) trait SealCriterion: fmt::Debug + Send + Sync + 'static {
@@ -120,6 +162,11 @@ pub(super) trait SealCriterion: fmt::Debug + Send + Sync + 'static {
     // We need self here only for rust restrictions for creating an object from trait
     // https://doc.rust-lang.org/reference/items/traits.html#object-safety
     fn prom_criterion_name(&self) -> &'static str;
+
+    /// Classifies transactions deemed unexecutable by this criterion.
+    fn unexecutable_reason(&self) -> UnexecutableReason {
+        UnexecutableReason::SealCriterion
+    }
 }
 
 /// I/O-dependent seal criteria.
@@ -230,4 +277,40 @@ mod tests {
             "Non-empty miniblock with too recent timestamp shouldn't be sealed"
         );
     }
+
+    #[test]
+    fn unexecutable_reasons_for_criteria() {
+        let criteria: [(Box<dyn SealCriterion>, UnexecutableReason); 5] = [
+            (
+                Box::new(criteria::SlotsCriterion),
+                UnexecutableReason::SealCriterion,
+            ),
+            (
+                Box::new(criteria::GasCriterion),
+                UnexecutableReason::BlockGasLimit,
+            ),
+            (
+                Box::new(criteria::PubDataBytesCriterion {
+                    max_pubdata_per_batch: 100_000,
+                }),
+                UnexecutableReason::PubdataLimit,
+            ),
+            (
+                Box::new(criteria::CircuitsCriterion),
+                UnexecutableReason::CircuitLimit,
+            ),
+            (
+                Box::new(criteria::TxEncodingSizeCriterion),
+                UnexecutableReason::SealCriterion,
+            ),
+        ];
+        for (criterion, expected_reason) in criteria {
+            assert_eq!(
+                criterion.unexecutable_reason(),
+                expected_reason,
+                "{}",
+                criterion.prom_criterion_name()
+            );
+        }
+    }
 }
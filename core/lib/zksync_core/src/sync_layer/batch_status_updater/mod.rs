@@ -1,14 +1,19 @@
 //! Component responsible for updating L1 batch status.
 
-use std::{fmt, time::Duration};
+use std::{
+    fmt, mem,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use rand::Rng;
 use serde::Serialize;
 #[cfg(test)]
 use tokio::sync::mpsc;
-use tokio::sync::watch;
+use tokio::sync::{broadcast, watch};
 use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
 use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
 use zksync_types::{
@@ -36,27 +41,51 @@ fn l1_batch_stage_to_action_str(stage: AggregatedActionType) -> &'static str {
 
 /// Represents a change in the batch status.
 /// It may be a batch being committed, proven or executed.
-#[derive(Debug)]
-struct BatchStatusChange {
-    number: L1BatchNumber,
-    l1_tx_hash: H256,
-    happened_at: DateTime<Utc>,
+#[derive(Debug, Clone)]
+pub struct BatchStatusChange {
+    pub number: L1BatchNumber,
+    pub l1_tx_hash: H256,
+    pub happened_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Default)]
-struct StatusChanges {
-    commit: Vec<BatchStatusChange>,
-    prove: Vec<BatchStatusChange>,
-    execute: Vec<BatchStatusChange>,
+/// Batch status changes applied to the storage during a single iteration of [`BatchStatusUpdater`].
+#[derive(Debug, Clone, Default)]
+pub struct StatusChanges {
+    pub commit: Vec<BatchStatusChange>,
+    pub prove: Vec<BatchStatusChange>,
+    pub execute: Vec<BatchStatusChange>,
 }
 
 impl StatusChanges {
     /// Returns true if there are no status changes.
-    fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.commit.is_empty() && self.prove.is_empty() && self.execute.is_empty()
     }
 }
 
+/// Subscriber to the status changes applied by [`BatchStatusUpdater`].
+#[derive(Debug)]
+pub struct StatusChangesSubscriber(broadcast::Receiver<StatusChanges>);
+
+impl StatusChangesSubscriber {
+    /// Waits for the next status changes. Returns `None` if the updater has stopped.
+    ///
+    /// If the subscriber lags behind the updater, the oldest changes are skipped (with a logged warning).
+    pub async fn recv(&mut self) -> Option<StatusChanges> {
+        loop {
+            match self.0.recv().await {
+                Ok(changes) => return Some(changes),
+                Err(broadcast::error::RecvError::Lagged(skipped_count)) => {
+                    tracing::warn!(
+                        "Batch status changes subscriber lagged behind; skipped {skipped_count} changes"
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 enum UpdaterError {
     #[error("JSON-RPC error communicating with main node")]
@@ -117,6 +146,136 @@ impl MainNodeClient for HttpClient {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct FailoverState {
+    /// Index of the client used for requests.
+    active_client: usize,
+    consecutive_errors: usize,
+    /// Last time the primary client was given up on; used to periodically retry the primary client.
+    primary_failed_at: Option<Instant>,
+}
+
+/// Main node client that fails over from the primary client to fallback clients on repeated errors.
+/// While a fallback client is active, the primary client is periodically retried; if it responds successfully,
+/// it becomes active again.
+#[derive(Debug)]
+struct FailoverClient {
+    /// Primary client followed by fallback clients.
+    clients: Vec<Box<dyn MainNodeClient>>,
+    state: Mutex<FailoverState>,
+    max_consecutive_errors: usize,
+    primary_retry_interval: Duration,
+}
+
+impl FailoverClient {
+    const DEFAULT_MAX_CONSECUTIVE_ERRORS: usize = 3;
+    const DEFAULT_PRIMARY_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+    fn new(
+        primary: Box<dyn MainNodeClient>,
+        fallbacks: Vec<Box<dyn MainNodeClient>>,
+        max_consecutive_errors: usize,
+        primary_retry_interval: Duration,
+    ) -> Self {
+        let mut clients = vec![primary];
+        clients.extend(fallbacks);
+        Self {
+            clients,
+            state: Mutex::new(FailoverState {
+                active_client: 0,
+                consecutive_errors: 0,
+                primary_failed_at: None,
+            }),
+            max_consecutive_errors,
+            primary_retry_interval,
+        }
+    }
+
+    /// Selects the client to send the next request to.
+    fn select_client(&self) -> usize {
+        let state = *self.state.lock().expect("failover state is poisoned");
+        let should_retry_primary = state.primary_failed_at.map_or(false, |failed_at| {
+            failed_at.elapsed() >= self.primary_retry_interval
+        });
+        if should_retry_primary {
+            0
+        } else {
+            state.active_client
+        }
+    }
+
+    fn report_result<T>(&self, client_idx: usize, result: &EnrichedClientResult<T>) {
+        let mut state = self.state.lock().expect("failover state is poisoned");
+        let is_primary_retry = client_idx == 0 && state.active_client != 0;
+        match result {
+            Ok(_) if is_primary_retry => {
+                tracing::info!("Primary main node client has recovered; switching back to it");
+                *state = FailoverState {
+                    active_client: 0,
+                    consecutive_errors: 0,
+                    primary_failed_at: None,
+                };
+            }
+            Ok(_) => {
+                state.consecutive_errors = 0;
+            }
+            Err(err) if is_primary_retry => {
+                tracing::info!("Primary main node client is still failing: {err}");
+                state.primary_failed_at = Some(Instant::now());
+            }
+            Err(_) if client_idx != state.active_client => {
+                // Result of a request started before the active client has changed; ignore it.
+            }
+            Err(err) => {
+                state.consecutive_errors += 1;
+                if state.consecutive_errors >= self.max_consecutive_errors && self.clients.len() > 1
+                {
+                    let next_client = if client_idx + 1 < self.clients.len() {
+                        client_idx + 1
+                    } else {
+                        1 // Skip the primary client; it's retried separately.
+                    };
+                    tracing::warn!(
+                        "Main node client #{client_idx} returned {} consecutive errors, the last one being: {err}; \
+                         failing over to client #{next_client}",
+                        state.consecutive_errors
+                    );
+                    if client_idx == 0 {
+                        state.primary_failed_at = Some(Instant::now());
+                    }
+                    state.active_client = next_client;
+                    state.consecutive_errors = 0;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MainNodeClient for FailoverClient {
+    async fn resolve_l1_batch_to_miniblock(
+        &self,
+        number: L1BatchNumber,
+    ) -> EnrichedClientResult<Option<MiniblockNumber>> {
+        let client_idx = self.select_client();
+        let result = self.clients[client_idx]
+            .resolve_l1_batch_to_miniblock(number)
+            .await;
+        self.report_result(client_idx, &result);
+        result
+    }
+
+    async fn block_details(
+        &self,
+        number: MiniblockNumber,
+    ) -> EnrichedClientResult<Option<api::BlockDetails>> {
+        let client_idx = self.select_client();
+        let result = self.clients[client_idx].block_details(number).await;
+        self.report_result(client_idx, &result);
+        result
+    }
+}
+
 /// Cursors for the last executed / proven / committed L1 batch numbers.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 struct UpdaterCursor {
@@ -232,6 +391,33 @@ impl UpdaterCursor {
     }
 }
 
+/// Oldest sealed L1 batch that is not committed according to the updater cursor. Used to report
+/// the age of this batch without querying the storage on each updater iteration.
+#[derive(Debug, Default)]
+struct OldestUncommittedBatch {
+    number: L1BatchNumber,
+    /// Creation timestamp of the batch, or `None` if the batch is not sealed yet (or wasn't loaded).
+    timestamp: Option<u64>,
+}
+
+impl OldestUncommittedBatch {
+    /// Checks whether the batch with the specified number is loaded. A batch that wasn't sealed on load
+    /// is considered not loaded, so that it's reloaded once it's sealed.
+    fn is_loaded(&self, number: L1BatchNumber) -> bool {
+        self.number == number && self.timestamp.is_some()
+    }
+
+    /// Returns the time elapsed since the creation of the oldest uncommitted batch, or `None` if all sealed batches
+    /// are committed according to the `cursor` (or if the batch information is stale).
+    fn age(&self, cursor: &UpdaterCursor) -> Option<Duration> {
+        if self.number != cursor.last_committed_l1_batch.next() {
+            return None;
+        }
+        let now = u64::try_from(Utc::now().timestamp()).unwrap_or(0);
+        Some(Duration::from_secs(now.saturating_sub(self.timestamp?)))
+    }
+}
+
 /// Component responsible for fetching the batch status changes, i.e. one that monitors whether the
 /// locally applied batch was committed, proven or executed on L1.
 ///
@@ -243,9 +429,20 @@ impl UpdaterCursor {
 #[derive(Debug)]
 pub struct BatchStatusUpdater {
     client: Box<dyn MainNodeClient>,
+    /// Fallback clients; wrapped together with `client` into a [`FailoverClient`] when the updater is run.
+    fallback_clients: Vec<Box<dyn MainNodeClient>>,
+    failover_max_consecutive_errors: usize,
+    failover_primary_retry_interval: Duration,
     pool: ConnectionPool<Core>,
     health_updater: HealthUpdater,
     sleep_interval: Duration,
+    /// Relative random jitter applied to `sleep_interval`; 0 means no jitter.
+    sleep_jitter: f64,
+    /// Maximum number of batches skipped at once when looking for status changes.
+    max_skip: u32,
+    changes_broadcaster: broadcast::Sender<StatusChanges>,
+    /// If set, status changes are only logged, but neither persisted nor broadcast.
+    dry_run: bool,
     /// Test-only sender of status changes each time they are produced and applied to the storage.
     #[cfg(test)]
     changes_sender: mpsc::UnboundedSender<StatusChanges>,
@@ -253,6 +450,8 @@ pub struct BatchStatusUpdater {
 
 impl BatchStatusUpdater {
     const DEFAULT_SLEEP_INTERVAL: Duration = Duration::from_secs(5);
+    /// Capacity of the status changes channel; subscribers lagging behind by more changes will skip the oldest ones.
+    const CHANGES_CHANNEL_CAPACITY: usize = 128;
 
     pub fn new(client: HttpClient, pool: ConnectionPool<Core>) -> Self {
         Self::from_parts(Box::new(client), pool, Self::DEFAULT_SLEEP_INTERVAL)
@@ -265,26 +464,146 @@ impl BatchStatusUpdater {
     ) -> Self {
         Self {
             client,
+            fallback_clients: vec![],
+            failover_max_consecutive_errors: FailoverClient::DEFAULT_MAX_CONSECUTIVE_ERRORS,
+            failover_primary_retry_interval: FailoverClient::DEFAULT_PRIMARY_RETRY_INTERVAL,
             pool,
             health_updater: ReactiveHealthCheck::new("batch_status_updater").1,
             sleep_interval,
+            sleep_jitter: 0.0,
+            max_skip: u32::MAX,
+            changes_broadcaster: broadcast::channel(Self::CHANGES_CHANNEL_CAPACITY).0,
+            dry_run: false,
             #[cfg(test)]
             changes_sender: mpsc::unbounded_channel().0,
         }
     }
 
+    /// Sets the maximum number of batches the updater skips at once when looking for status changes.
+    /// By default, the updater jumps straight to the batches that can have their status changed
+    /// (e.g., it doesn't examine committed batches after the first unproven batch).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_skip` is zero.
+    pub fn with_max_skip(mut self, max_skip: u32) -> Self {
+        assert!(max_skip > 0, "Maximum batch skip must be positive");
+        self.max_skip = max_skip;
+        self
+    }
+
+    /// Sets the interval between polls of the main node. The default interval is 5 seconds.
+    pub fn with_sleep_interval(mut self, sleep_interval: Duration) -> Self {
+        self.sleep_interval = sleep_interval;
+        self
+    }
+
+    /// Sets the relative random jitter applied to the sleep interval on each iteration (e.g., 0.1 for ±10%).
+    /// Jitter spreads out requests from multiple nodes polling the same main node. By default, there is no jitter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `jitter` is not in the `[0, 1)` range.
+    pub fn with_sleep_jitter(mut self, jitter: f64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&jitter),
+            "Sleep jitter must be in the [0, 1) range"
+        );
+        self.sleep_jitter = jitter;
+        self
+    }
+
+    /// Sets fallback main node clients. If the primary client returns repeated errors, the updater fails over
+    /// to a fallback client for subsequent requests; the primary client is periodically retried.
+    pub fn with_fallback_clients(self, fallback_clients: Vec<HttpClient>) -> Self {
+        let fallback_clients = fallback_clients
+            .into_iter()
+            .map(|client| Box::new(client) as Box<dyn MainNodeClient>)
+            .collect();
+        self.with_boxed_fallback_clients(fallback_clients)
+    }
+
+    fn with_boxed_fallback_clients(
+        mut self,
+        fallback_clients: Vec<Box<dyn MainNodeClient>>,
+    ) -> Self {
+        self.fallback_clients = fallback_clients;
+        self
+    }
+
+    /// Sets the number of consecutive errors returned by the active main node client after which the updater
+    /// fails over to the next fallback client. The default value is 3. Has no effect if there are no fallback clients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_consecutive_errors` is zero.
+    pub fn with_failover_max_consecutive_errors(mut self, max_consecutive_errors: usize) -> Self {
+        assert!(
+            max_consecutive_errors > 0,
+            "Maximum consecutive errors before failover must be positive"
+        );
+        self.failover_max_consecutive_errors = max_consecutive_errors;
+        self
+    }
+
+    /// Sets the interval after which the primary main node client is retried once the updater has failed over
+    /// to a fallback client. The default interval is 60 seconds. Has no effect if there are no fallback clients.
+    pub fn with_failover_primary_retry_interval(mut self, interval: Duration) -> Self {
+        self.failover_primary_retry_interval = interval;
+        self
+    }
+
+    /// Wraps the primary client and fallback clients (if any) into a [`FailoverClient`].
+    fn with_failover(mut self) -> Self {
+        if !self.fallback_clients.is_empty() {
+            self.client = Box::new(FailoverClient::new(
+                self.client,
+                mem::take(&mut self.fallback_clients),
+                self.failover_max_consecutive_errors,
+                self.failover_primary_retry_interval,
+            ));
+        }
+        self
+    }
+
+    /// Enables the dry-run mode. In this mode, the updater fetches status changes from the main node and logs them,
+    /// but doesn't write them to the database or broadcast them to subscribers. The updater cursor isn't advanced
+    /// either; instead, the last logged changes are tracked separately, so that each change is logged once.
+    /// Useful to validate main node data before trusting it.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
     pub fn health_check(&self) -> ReactiveHealthCheck {
         self.health_updater.subscribe()
     }
 
+    /// Subscribes to the status changes applied to the storage by this updater. Any number of subscribers is supported.
+    pub fn subscribe(&self) -> StatusChangesSubscriber {
+        StatusChangesSubscriber(self.changes_broadcaster.subscribe())
+    }
+
     pub async fn run(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        self.with_failover().run_inner(stop_receiver).await
+    }
+
+    async fn run_inner(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
         let mut storage = self.pool.connection_tagged("sync_layer").await?;
         let mut cursor = UpdaterCursor::new(&mut storage).await?;
         drop(storage);
         tracing::info!("Initialized batch status updater cursor: {cursor:?}");
+        if self.dry_run {
+            tracing::warn!("Batch status updater is running in dry-run mode; status changes will not be persisted");
+        }
         self.health_updater
             .update(Health::from(HealthStatus::Ready).with_details(cursor));
 
+        // In the dry-run mode, fetching status changes starts from this cursor, which tracks the logged changes.
+        let mut dry_run_cursor = cursor;
+        let mut oldest_uncommitted_batch = OldestUncommittedBatch::default();
+        // First batch that wasn't fully processed by the previous iteration if it was interrupted by a network error.
+        let mut resume_batch = None;
         loop {
             if *stop_receiver.borrow() {
                 tracing::info!("Stop signal received, exiting the batch status updater routine");
@@ -294,17 +613,44 @@ impl BatchStatusUpdater {
             // Status changes are created externally, so that even if we will receive a network error
             // while requesting the changes, we will be able to process what we already fetched.
             let mut status_changes = StatusChanges::default();
+            // Batches before the resumed one were processed by the previous iteration, and their status changes
+            // are already applied, so there's no need to re-fetch them. Resuming only affects a single iteration;
+            // the following ones start from the cursor as usual, so that later status changes are picked up.
+            let fetch_cursor = if self.dry_run { dry_run_cursor } else { cursor };
+            let cursor_batch = fetch_cursor.last_executed_l1_batch.next();
+            let mut batch = resume_batch
+                .take()
+                .map_or(cursor_batch, |batch: L1BatchNumber| batch.max(cursor_batch));
             // Note that we don't update `cursor` here (it is copied), but rather only in `apply_status_changes`.
-            match self.get_status_changes(&mut status_changes, cursor).await {
+            match self
+                .get_status_changes(&mut status_changes, fetch_cursor, &mut batch)
+                .await
+            {
                 Ok(()) => { /* everything went smoothly */ }
                 Err(UpdaterError::Web3(err)) => {
-                    tracing::warn!("Failed to get status changes from the main node: {err}");
+                    tracing::warn!(
+                        "Failed to get status changes from the main node: {err}; will resume from L1 batch #{batch}"
+                    );
+                    resume_batch = Some(batch);
                 }
                 Err(UpdaterError::Internal(err)) => return Err(err),
             }
 
+            // The age is a best-effort metric, so failing to load it shouldn't stop the updater.
+            if let Err(err) = self
+                .update_oldest_uncommitted_batch(&mut oldest_uncommitted_batch, &cursor)
+                .await
+            {
+                tracing::warn!("Failed loading oldest uncommitted L1 batch: {err:#}");
+            }
+            EN_METRICS
+                .oldest_uncommitted_l1_batch_age
+                .set(oldest_uncommitted_batch.age(&cursor).unwrap_or_default());
+
             if status_changes.is_empty() {
-                tokio::time::sleep(self.sleep_interval).await;
+                tokio::time::sleep(self.jittered_sleep_interval()).await;
+            } else if self.dry_run {
+                self.log_status_changes(&mut dry_run_cursor, status_changes);
             } else {
                 self.apply_status_changes(&mut cursor, status_changes)
                     .await?;
@@ -314,7 +660,17 @@ impl BatchStatusUpdater {
         }
     }
 
-    /// Goes through the already fetched batches trying to update their statuses.
+    fn jittered_sleep_interval(&self) -> Duration {
+        if self.sleep_jitter == 0.0 {
+            return self.sleep_interval;
+        }
+        let jitter = rand::thread_rng().gen_range(-self.sleep_jitter..=self.sleep_jitter);
+        self.sleep_interval.mul_f64(1.0 + jitter)
+    }
+
+    /// Goes through the already fetched batches starting from `batch` trying to update their statuses.
+    /// On return, `batch` is set to the first batch that wasn't fully processed, which allows resuming
+    /// after a network error.
     ///
     /// Fetched changes are capped by the last locally applied batch number, so
     /// it's safe to assume that every status change can safely be applied (no status
@@ -323,6 +679,7 @@ impl BatchStatusUpdater {
         &self,
         status_changes: &mut StatusChanges,
         mut cursor: UpdaterCursor,
+        batch: &mut L1BatchNumber,
     ) -> Result<(), UpdaterError> {
         let total_latency = EN_METRICS.update_batch_statuses.start();
         let Some(last_sealed_batch) = self
@@ -336,14 +693,13 @@ impl BatchStatusUpdater {
             return Ok(()); // No L1 batches in the storage yet; do nothing.
         };
 
-        let mut batch = cursor.last_executed_l1_batch.next();
         // In this loop we try to progress on the batch statuses, utilizing the same request to the node to potentially
         // update all three statuses (e.g. if the node is still syncing), but also skipping the gaps in the statuses
         // (e.g. if the last executed batch is 10, but the last proven is 20, we don't need to check the batches 11-19).
-        while batch <= last_sealed_batch {
+        while *batch <= last_sealed_batch {
             // While we may receive `None` for the `self.current_l1_batch`, it's OK: open batch is guaranteed to not
             // be sent to L1.
-            let miniblock_number = self.client.resolve_l1_batch_to_miniblock(batch).await?;
+            let miniblock_number = self.client.resolve_l1_batch_to_miniblock(*batch).await?;
             let Some(miniblock_number) = miniblock_number else {
                 return Ok(());
             };
@@ -364,15 +720,16 @@ impl BatchStatusUpdater {
                 // No committed batches after this one.
                 break;
             } else if batch_info.base.prove_tx_hash.is_none()
-                && batch < cursor.last_committed_l1_batch
+                && *batch < cursor.last_committed_l1_batch
             {
                 // The interval between this batch and the last committed one is not proven.
-                batch = cursor.last_committed_l1_batch.next();
-            } else if batch_info.base.executed_at.is_none() && batch < cursor.last_proven_l1_batch {
+                *batch = self.skip_to(*batch, cursor.last_committed_l1_batch.next());
+            } else if batch_info.base.executed_at.is_none() && *batch < cursor.last_proven_l1_batch
+            {
                 // The interval between this batch and the last proven one is not executed.
-                batch = cursor.last_proven_l1_batch.next();
+                *batch = self.skip_to(*batch, cursor.last_proven_l1_batch.next());
             } else {
-                batch += 1;
+                *batch += 1;
             }
         }
 
@@ -380,6 +737,80 @@ impl BatchStatusUpdater {
         Ok(())
     }
 
+    /// Loads the timestamp of the oldest sealed L1 batch that is not committed according to the `cursor`
+    /// into `oldest_batch`, unless it's already loaded.
+    async fn update_oldest_uncommitted_batch(
+        &self,
+        oldest_batch: &mut OldestUncommittedBatch,
+        cursor: &UpdaterCursor,
+    ) -> anyhow::Result<()> {
+        let number = cursor.last_committed_l1_batch.next();
+        if oldest_batch.is_loaded(number) {
+            return Ok(());
+        }
+        let header = self
+            .pool
+            .connection_tagged("sync_layer")
+            .await?
+            .blocks_dal()
+            .get_l1_batch_header(number)
+            .await?;
+        *oldest_batch = OldestUncommittedBatch {
+            number,
+            timestamp: header.map(|header| header.timestamp),
+        };
+        Ok(())
+    }
+
+    /// Skips from `batch` towards `target`, advancing by at most `max_skip` batches.
+    fn skip_to(&self, batch: L1BatchNumber, target: L1BatchNumber) -> L1BatchNumber {
+        let max_target = L1BatchNumber(batch.0.saturating_add(self.max_skip));
+        target.min(max_target)
+    }
+
+    /// Logs the provided status changes without applying them and advances `cursor` past them.
+    /// Used in the dry-run mode.
+    fn log_status_changes(&self, cursor: &mut UpdaterCursor, changes: StatusChanges) {
+        let stages = [
+            (
+                AggregatedActionType::Commit,
+                &changes.commit,
+                &mut cursor.last_committed_l1_batch,
+            ),
+            (
+                AggregatedActionType::PublishProofOnchain,
+                &changes.prove,
+                &mut cursor.last_proven_l1_batch,
+            ),
+            (
+                AggregatedActionType::Execute,
+                &changes.execute,
+                &mut cursor.last_executed_l1_batch,
+            ),
+        ];
+        for (stage, stage_changes, last_logged_batch) in stages {
+            let action_str = l1_batch_stage_to_action_str(stage);
+            for change in stage_changes {
+                tracing::info!(
+                    "[dry run] L1 batch #{} would be marked as {action_str}: hash {}, happened at {}",
+                    change.number,
+                    change.l1_tx_hash,
+                    change.happened_at
+                );
+                *last_logged_batch = change.number;
+            }
+        }
+        #[cfg(test)]
+        self.changes_sender.send(changes).ok();
+    }
+
+    fn emit_status_changes(&self, changes: StatusChanges) {
+        // An error only means that there are no subscribers at the moment, which is fine.
+        self.changes_broadcaster.send(changes.clone()).ok();
+        #[cfg(test)]
+        self.changes_sender.send(changes).ok();
+    }
+
     /// Inserts the provided status changes into the database.
     /// The status changes are applied to the database by inserting bogus confirmed transactions (with
     /// some fields missing/substituted) only to satisfy API needs; this component doesn't expect the updated
@@ -472,8 +903,7 @@ impl BatchStatusUpdater {
         transaction.commit().await?;
         total_latency.observe();
 
-        #[cfg(test)]
-        self.changes_sender.send(changes).ok();
+        self.emit_status_changes(changes);
         Ok(())
     }
 }
@@ -1,11 +1,13 @@
 //! Tests for batch status updater.
 
-use std::{future, sync::Arc};
+use std::{collections::HashSet, future, sync::Arc};
 
+use assert_matches::assert_matches;
 use chrono::TimeZone;
 use test_casing::{test_casing, Product};
 use tokio::sync::{watch, Mutex};
 use zksync_contracts::BaseSystemContractsHashes;
+use zksync_health_check::CheckHealth;
 use zksync_types::{Address, ProtocolVersionId};
 
 use super::*;
@@ -203,6 +205,31 @@ impl MainNodeClient for MockMainNodeClient {
     }
 }
 
+/// Main node client recording the requested L1 batch numbers.
+#[derive(Debug)]
+struct RecordingMainNodeClient {
+    inner: MockMainNodeClient,
+    requested_batches: Arc<std::sync::Mutex<Vec<L1BatchNumber>>>,
+}
+
+#[async_trait]
+impl MainNodeClient for RecordingMainNodeClient {
+    async fn resolve_l1_batch_to_miniblock(
+        &self,
+        number: L1BatchNumber,
+    ) -> EnrichedClientResult<Option<MiniblockNumber>> {
+        self.requested_batches.lock().unwrap().push(number);
+        self.inner.resolve_l1_batch_to_miniblock(number).await
+    }
+
+    async fn block_details(
+        &self,
+        number: MiniblockNumber,
+    ) -> EnrichedClientResult<Option<api::BlockDetails>> {
+        self.inner.block_details(number).await
+    }
+}
+
 fn mock_change(number: L1BatchNumber) -> BatchStatusChange {
     BatchStatusChange {
         number,
@@ -440,3 +467,449 @@ async fn test_resuming_updater(pool: ConnectionPool<Core>, initial_batch_stages:
     stop_sender.send_replace(true);
     updater_task.await.unwrap().expect("updater failed");
 }
+
+#[tokio::test]
+async fn multiple_status_changes_subscribers() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    seal_l1_batch(&mut storage, L1BatchNumber(1)).await;
+
+    let target_batch_stages =
+        L1BatchStagesMap::new(L1BatchNumber(1), vec![L1BatchStage::Committed]);
+    let client = MockMainNodeClient::from(target_batch_stages.clone());
+    let (updater, _) = mock_updater(client, pool.clone());
+    let mut subscribers = [updater.subscribe(), updater.subscribe()];
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let updater_task = tokio::spawn(updater.run(stop_receiver));
+
+    for subscriber in &mut subscribers {
+        let changes = subscriber.recv().await.unwrap();
+        let committed_batches: Vec<_> = changes.commit.iter().map(|change| change.number).collect();
+        assert_eq!(committed_batches, [L1BatchNumber(1)]);
+        assert!(changes.prove.is_empty() && changes.execute.is_empty());
+    }
+
+    stop_sender.send_replace(true);
+    updater_task.await.unwrap().expect("updater failed");
+    // Subscribers should be notified that the updater has stopped.
+    for subscriber in &mut subscribers {
+        assert!(subscriber.recv().await.is_none());
+    }
+}
+
+#[tokio::test]
+async fn updater_in_dry_run_mode() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    for number in 1..=3 {
+        seal_l1_batch(&mut storage, L1BatchNumber(number)).await;
+    }
+
+    let target_batch_stages = L1BatchStagesMap::new(
+        L1BatchNumber(1),
+        vec![
+            L1BatchStage::Executed,
+            L1BatchStage::Proven,
+            L1BatchStage::Committed,
+        ],
+    );
+    let client = MockMainNodeClient::from(target_batch_stages.clone());
+    let (updater, mut changes_receiver) = mock_updater(client, pool.clone());
+    let updater = updater.with_dry_run(true);
+    let mut subscriber = updater.subscribe();
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let updater_task = tokio::spawn(updater.run(stop_receiver));
+
+    let mut observed_batch_stages = L1BatchStagesMap::empty(L1BatchNumber(1), 3);
+    while observed_batch_stages != target_batch_stages {
+        let changes = changes_receiver.recv().await.unwrap();
+        observed_batch_stages.update(&changes);
+    }
+    // Each change should be logged once, even though the cursor isn't advanced.
+    let next_changes =
+        tokio::time::timeout(Duration::from_millis(50), changes_receiver.recv()).await;
+    assert!(next_changes.is_err(), "{next_changes:?}");
+
+    // No changes should be persisted.
+    L1BatchStagesMap::empty(L1BatchNumber(1), 3)
+        .assert_storage(&mut storage)
+        .await;
+    let cursor = UpdaterCursor::new(&mut storage).await.unwrap();
+    assert_eq!(cursor.last_committed_l1_batch, L1BatchNumber(0));
+
+    stop_sender.send_replace(true);
+    updater_task.await.unwrap().expect("updater failed");
+    // Changes should not be broadcast to subscribers.
+    assert!(subscriber.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn reporting_oldest_uncommitted_batch_age() {
+    const BATCH_AGE: Duration = Duration::from_secs(3_600);
+
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut l1_batch = create_l1_batch(1);
+    l1_batch.timestamp = u64::try_from(Utc::now().timestamp()).unwrap() - BATCH_AGE.as_secs();
+    storage
+        .blocks_dal()
+        .insert_mock_l1_batch(&l1_batch)
+        .await
+        .unwrap();
+
+    let (updater, _) = mock_updater(MockMainNodeClient::default(), pool.clone());
+    let mut cursor = UpdaterCursor::new(&mut storage).await.unwrap();
+    assert_eq!(cursor.last_committed_l1_batch, L1BatchNumber(0));
+    let mut oldest_batch = OldestUncommittedBatch::default();
+    updater
+        .update_oldest_uncommitted_batch(&mut oldest_batch, &cursor)
+        .await
+        .unwrap();
+    assert!(oldest_batch.is_loaded(L1BatchNumber(1)));
+    let age = oldest_batch.age(&cursor).expect("no uncommitted batch");
+    // Allow for some slack since the current time is sampled separately.
+    assert!(
+        age >= BATCH_AGE && age < BATCH_AGE + Duration::from_secs(60),
+        "{age:?}"
+    );
+
+    // The age should be invalidated once the cursor changes.
+    cursor.last_committed_l1_batch = L1BatchNumber(1);
+    assert_eq!(oldest_batch.age(&cursor), None);
+    updater
+        .update_oldest_uncommitted_batch(&mut oldest_batch, &cursor)
+        .await
+        .unwrap();
+    assert!(!oldest_batch.is_loaded(L1BatchNumber(2)));
+    assert_eq!(oldest_batch.age(&cursor), None);
+}
+
+#[tokio::test]
+async fn oldest_uncommitted_batch_is_not_reloaded_for_same_cursor() {
+    let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    seal_l1_batch(&mut storage, L1BatchNumber(1)).await;
+    let cursor = UpdaterCursor::new(&mut storage).await.unwrap();
+    drop(storage);
+
+    let (updater, _) = mock_updater(MockMainNodeClient::default(), pool.clone());
+    let mut oldest_batch = OldestUncommittedBatch::default();
+    updater
+        .update_oldest_uncommitted_batch(&mut oldest_batch, &cursor)
+        .await
+        .unwrap();
+    assert!(oldest_batch.is_loaded(L1BatchNumber(1)));
+
+    // Hold the only connection in the pool; the loaded batch should be reused without accessing the storage.
+    let _held_connection = pool.connection().await.unwrap();
+    let update = updater.update_oldest_uncommitted_batch(&mut oldest_batch, &cursor);
+    tokio::time::timeout(Duration::from_secs(1), update)
+        .await
+        .expect("oldest uncommitted batch was reloaded")
+        .unwrap();
+    assert!(oldest_batch.age(&cursor).is_some());
+}
+
+#[test_casing(3, [(None, &[1, 2] as &[u32]), (Some(3), &[1, 2, 5, 8]), (Some(100), &[1, 2])])]
+#[tokio::test]
+async fn skipping_batches_with_capped_skip(max_skip: Option<u32>, expected_batches: &[u32]) {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    for number in 1..=10 {
+        seal_l1_batch(&mut storage, L1BatchNumber(number)).await;
+    }
+
+    let mut stages = vec![L1BatchStage::Committed; 10];
+    stages[0] = L1BatchStage::Proven;
+    let requested_batches = Arc::<std::sync::Mutex<_>>::default();
+    let client = RecordingMainNodeClient {
+        inner: L1BatchStagesMap::new(L1BatchNumber(1), stages).into(),
+        requested_batches: requested_batches.clone(),
+    };
+    let mut updater =
+        BatchStatusUpdater::from_parts(Box::new(client), pool.clone(), Duration::from_millis(10));
+    if let Some(max_skip) = max_skip {
+        updater = updater.with_max_skip(max_skip);
+    }
+
+    // All batches are already known to be committed, but none is proven yet.
+    let cursor = UpdaterCursor {
+        last_executed_l1_batch: L1BatchNumber(0),
+        last_proven_l1_batch: L1BatchNumber(0),
+        last_committed_l1_batch: L1BatchNumber(10),
+    };
+    let mut status_changes = StatusChanges::default();
+    let mut batch = cursor.last_executed_l1_batch.next();
+    updater
+        .get_status_changes(&mut status_changes, cursor, &mut batch)
+        .await
+        .unwrap();
+    assert_eq!(status_changes.prove.len(), 1);
+    assert_eq!(status_changes.prove[0].number, L1BatchNumber(1));
+
+    let requested_batches = requested_batches.lock().unwrap().clone();
+    let expected_batches: Vec<_> = expected_batches
+        .iter()
+        .copied()
+        .map(L1BatchNumber)
+        .collect();
+    assert_eq!(requested_batches, expected_batches);
+}
+
+#[tokio::test]
+async fn updater_idles_on_empty_storage() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+
+    let target_batch_stages = L1BatchStagesMap::new(
+        L1BatchNumber(1),
+        vec![L1BatchStage::Executed, L1BatchStage::Committed],
+    );
+    let requested_batches = Arc::<std::sync::Mutex<_>>::default();
+    let client = RecordingMainNodeClient {
+        inner: target_batch_stages.clone().into(),
+        requested_batches: requested_batches.clone(),
+    };
+    let (changes_sender, mut changes_receiver) = mpsc::unbounded_channel();
+    let mut updater =
+        BatchStatusUpdater::from_parts(Box::new(client), pool.clone(), Duration::from_millis(10));
+    updater.changes_sender = changes_sender;
+    let health_check = updater.health_check();
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let updater_task = tokio::spawn(updater.run(stop_receiver));
+
+    // Let the updater perform several iterations.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!updater_task.is_finished());
+    assert_matches!(
+        health_check.check_health().await.status(),
+        HealthStatus::Ready
+    );
+    assert!(requested_batches.lock().unwrap().is_empty());
+    assert!(changes_receiver.try_recv().is_err());
+
+    // Once L1 batches appear in the storage, the updater should start updating their statuses.
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    for number in 1..=2 {
+        seal_l1_batch(&mut storage, L1BatchNumber(number)).await;
+    }
+    let mut observed_batch_stages = L1BatchStagesMap::empty(L1BatchNumber(1), 2);
+    while observed_batch_stages != target_batch_stages {
+        let changes = changes_receiver.recv().await.unwrap();
+        observed_batch_stages.update(&changes);
+    }
+    target_batch_stages.assert_storage(&mut storage).await;
+
+    stop_sender.send_replace(true);
+    updater_task.await.unwrap().expect("updater failed");
+}
+
+/// Main node client failing all requests.
+#[derive(Debug, Default)]
+struct FailingMainNodeClient {
+    request_count: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl FailingMainNodeClient {
+    fn error(&self, method: &'static str) -> EnrichedClientError {
+        self.request_count
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        EnrichedClientError::custom("main node is down", method)
+    }
+}
+
+#[async_trait]
+impl MainNodeClient for FailingMainNodeClient {
+    async fn resolve_l1_batch_to_miniblock(
+        &self,
+        _number: L1BatchNumber,
+    ) -> EnrichedClientResult<Option<MiniblockNumber>> {
+        Err(self.error("resolve_l1_batch_to_miniblock"))
+    }
+
+    async fn block_details(
+        &self,
+        _number: MiniblockNumber,
+    ) -> EnrichedClientResult<Option<api::BlockDetails>> {
+        Err(self.error("block_details"))
+    }
+}
+
+#[tokio::test]
+async fn updater_fails_over_to_fallback_client() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    for number in 1..=3 {
+        seal_l1_batch(&mut storage, L1BatchNumber(number)).await;
+    }
+
+    let target_batch_stages = L1BatchStagesMap::new(
+        L1BatchNumber(1),
+        vec![
+            L1BatchStage::Executed,
+            L1BatchStage::Proven,
+            L1BatchStage::Committed,
+        ],
+    );
+    let primary_client = FailingMainNodeClient::default();
+    let primary_request_count = primary_client.request_count.clone();
+    let fallback_client = MockMainNodeClient::from(target_batch_stages.clone());
+    let (changes_sender, mut changes_receiver) = mpsc::unbounded_channel();
+    let mut updater = BatchStatusUpdater::from_parts(
+        Box::new(primary_client),
+        pool.clone(),
+        Duration::from_millis(10),
+    )
+    .with_boxed_fallback_clients(vec![Box::new(fallback_client)])
+    .with_failover_max_consecutive_errors(2);
+    updater.changes_sender = changes_sender;
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let updater_task = tokio::spawn(updater.run(stop_receiver));
+
+    let mut observed_batch_stages = L1BatchStagesMap::empty(L1BatchNumber(1), 3);
+    loop {
+        let changes = changes_receiver.recv().await.unwrap();
+        observed_batch_stages.update(&changes);
+        if observed_batch_stages == target_batch_stages {
+            break;
+        }
+    }
+    target_batch_stages.assert_storage(&mut storage).await;
+
+    // The primary client should've been queried until the failover, but not after it.
+    let primary_request_count = primary_request_count.load(std::sync::atomic::Ordering::SeqCst);
+    assert_eq!(primary_request_count, 2);
+
+    stop_sender.send_replace(true);
+    updater_task.await.unwrap().expect("updater failed");
+}
+
+/// Main node client failing the first request for the specified L1 batch.
+#[derive(Debug)]
+struct FlakyMainNodeClient {
+    inner: RecordingMainNodeClient,
+    failing_batch: L1BatchNumber,
+    failed: std::sync::atomic::AtomicBool,
+}
+
+#[async_trait]
+impl MainNodeClient for FlakyMainNodeClient {
+    async fn resolve_l1_batch_to_miniblock(
+        &self,
+        number: L1BatchNumber,
+    ) -> EnrichedClientResult<Option<MiniblockNumber>> {
+        if number == self.failing_batch
+            && !self.failed.swap(true, std::sync::atomic::Ordering::SeqCst)
+        {
+            self.inner.requested_batches.lock().unwrap().push(number);
+            let err =
+                EnrichedClientError::custom("connection reset", "resolve_l1_batch_to_miniblock");
+            return Err(err);
+        }
+        self.inner.resolve_l1_batch_to_miniblock(number).await
+    }
+
+    async fn block_details(
+        &self,
+        number: MiniblockNumber,
+    ) -> EnrichedClientResult<Option<api::BlockDetails>> {
+        self.inner.block_details(number).await
+    }
+}
+
+#[tokio::test]
+async fn resuming_after_network_error_mid_catch_up() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    for number in 1..=5 {
+        seal_l1_batch(&mut storage, L1BatchNumber(number)).await;
+    }
+
+    let target_batch_stages =
+        L1BatchStagesMap::new(L1BatchNumber(1), vec![L1BatchStage::Committed; 5]);
+    let requested_batches = Arc::<std::sync::Mutex<_>>::default();
+    let client = FlakyMainNodeClient {
+        inner: RecordingMainNodeClient {
+            inner: target_batch_stages.clone().into(),
+            requested_batches: requested_batches.clone(),
+        },
+        failing_batch: L1BatchNumber(4),
+        failed: false.into(),
+    };
+    let (changes_sender, mut changes_receiver) = mpsc::unbounded_channel();
+    let mut updater =
+        BatchStatusUpdater::from_parts(Box::new(client), pool.clone(), Duration::from_millis(10));
+    updater.changes_sender = changes_sender;
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let updater_task = tokio::spawn(updater.run(stop_receiver));
+
+    let mut observed_batch_stages = L1BatchStagesMap::empty(L1BatchNumber(1), 5);
+    loop {
+        let changes = changes_receiver.recv().await.unwrap();
+        observed_batch_stages.update(&changes);
+        if observed_batch_stages == target_batch_stages {
+            break;
+        }
+    }
+    target_batch_stages.assert_storage(&mut storage).await;
+    stop_sender.send_replace(true);
+    updater_task.await.unwrap().expect("updater failed");
+
+    // After the error, the updater should resume from the failed batch rather than re-fetch batches 1..=3.
+    let requested_batches = requested_batches.lock().unwrap().clone();
+    let expected_batches = [1, 2, 3, 4, 4, 5].map(L1BatchNumber);
+    assert_eq!(
+        requested_batches[..6],
+        expected_batches,
+        "{requested_batches:?}"
+    );
+}
+
+#[tokio::test]
+async fn jittering_sleep_interval() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let (updater, _) = mock_updater(MockMainNodeClient::default(), pool);
+    let updater = updater.with_sleep_interval(Duration::from_secs(10));
+    assert_eq!(updater.jittered_sleep_interval(), Duration::from_secs(10));
+
+    let updater = updater.with_sleep_jitter(0.1);
+    let sleep_intervals: HashSet<_> = (0..100)
+        .map(|_| updater.jittered_sleep_interval())
+        .collect();
+    assert!(sleep_intervals.len() > 1, "{sleep_intervals:?}");
+    let jitter_band = Duration::from_secs(9)..=Duration::from_secs(11);
+    for interval in sleep_intervals {
+        assert!(jitter_band.contains(&interval), "{interval:?}");
+    }
+}
+
+#[tokio::test]
+#[should_panic(expected = "Sleep jitter must be in the [0, 1) range")]
+async fn setting_invalid_sleep_jitter() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let (updater, _) = mock_updater(MockMainNodeClient::default(), pool);
+    updater.with_sleep_jitter(1.0);
+}
@@ -92,6 +92,9 @@ impl WiringLayer for TxSenderLayer {
         }));
 
         // Build `TxSender`.
+        self.tx_sender_config
+            .validate()
+            .map_err(|err| WiringError::Configuration(format!("{err:#}")))?;
         let mut tx_sender = TxSenderBuilder::new(self.tx_sender_config, replica_pool, tx_sink);
         if let Some(sealer) = sealer {
             tx_sender = tx_sender.with_sealer(sealer);
@@ -99,11 +102,11 @@ impl WiringLayer for TxSenderLayer {
         let tx_sender = tx_sender
             .build(
                 fee_input,
-                Arc::new(vm_concurrency_limiter),
+                Some(Arc::new(vm_concurrency_limiter)),
                 self.api_contracts,
                 storage_caches,
             )
-            .await;
+            .await?;
         context.insert_resource(TxSenderResource(tx_sender))?;
 
         Ok(())
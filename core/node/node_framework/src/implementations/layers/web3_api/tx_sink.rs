@@ -4,7 +4,9 @@ use zksync_core::api_server::tx_sender::{master_pool_sink::MasterPoolSink, proxy
 use zksync_web3_decl::jsonrpsee::http_client::{transport::HttpBackend, HttpClient};
 
 use crate::{
-    implementations::resources::{pools::MasterPoolResource, web3_api::TxSinkResource},
+    implementations::resources::{
+        pools::MasterPoolResource, sync_state::SyncStateResource, web3_api::TxSinkResource,
+    },
     service::ServiceContext,
     wiring_layer::{WiringError, WiringLayer},
 };
@@ -36,7 +38,13 @@ impl WiringLayer for TxSinkLayer {
                 let client = HttpClient::<HttpBackend>::builder()
                     .build(main_node_url)
                     .map_err(|err| WiringError::Internal(err.into()))?;
-                TxSinkResource(Arc::new(TxProxy::new(client)))
+                let mut tx_proxy = TxProxy::new(client);
+                match context.get_resource::<SyncStateResource>().await {
+                    Ok(sync_state) => tx_proxy = tx_proxy.with_sync_state(sync_state.0),
+                    Err(WiringError::ResourceLacking(_)) => {}
+                    Err(err) => return Err(err),
+                }
+                TxSinkResource(Arc::new(tx_proxy))
             }
         };
         context.insert_resource(tx_sink)?;